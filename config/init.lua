@@ -21,6 +21,7 @@ way_cooler.windows = {
   borders = { -- Options for borders
     root_borders = false, -- Display borders in root containers by default
     size = 20, -- The width of the borders between windows in pixels
+    -- active_size = 30, -- The width of the focused window's borders, defaults to size
     inactive_color = "386890", -- Color of the borders for inactive containers
     active_color = "57beb9" -- Color of active container borders
   },
@@ -7,6 +7,7 @@ use rlua::{self, Lua, ToLua, Table, Value, UserData, AnyUserData, Function,
            UserDataMethods, MetaMethod};
 use super::object::{self, Object};
 use super::property::Property;
+use super::signal;
 
 pub type Checker = Arc<Fn(Object) -> bool + Send + Sync>;
 
@@ -127,6 +128,12 @@ impl <'lua> Class<'lua> {
                  lua.create_function(set_index_miss_handler)?.bind(user_data.clone())?)?;
         meta.set("set_newindex_miss_handler",
                  lua.create_function(set_newindex_miss_handler)?.bind(user_data.clone())?)?;
+        meta.set("connect_signal",
+                 lua.create_function(connect_signal)?.bind(user_data.clone())?)?;
+        meta.set("disconnect_signal",
+                 lua.create_function(disconnect_signal)?.bind(user_data.clone())?)?;
+        meta.set("emit_signal",
+                 lua.create_function(emit_signal)?.bind(user_data.clone())?)?;
         meta.set("__index", meta.clone())?;
         table.set_metatable(Some(meta.clone()));
         user_data.set_user_value(table)?;
@@ -139,6 +146,29 @@ impl <'lua> Class<'lua> {
     pub fn checker(&self) -> rlua::Result<Option<Checker>> {
         self.class.borrow::<ClassState>().map(|class| class.checker.clone())
     }
+
+    /// The signals connected to the class itself, rather than to an instance.
+    pub fn signals(&self) -> rlua::Result<Table<'lua>> {
+        let table = self.class.get_user_value::<Table>()?;
+        let meta = table.get_metatable()
+            .expect("Class had no meta table!");
+        meta.get::<_, Table>("signals")
+    }
+}
+
+fn connect_signal(lua: &Lua, (class, signal, func): (AnyUserData, String, Function))
+                  -> rlua::Result<()> {
+    signal::connect_class_signal(lua, class.into(), signal, &[func])
+}
+
+fn disconnect_signal(lua: &Lua, (class, signal): (AnyUserData, String))
+                     -> rlua::Result<()> {
+    signal::disconnect_class_signal(lua, class.into(), signal)
+}
+
+fn emit_signal(lua: &Lua, (class, signal, args): (AnyUserData, String, Value))
+               -> rlua::Result<()> {
+    signal::emit_class_signal(lua, class.into(), signal, args)
 }
 
 fn set_index_miss_handler<'lua>(_: &'lua Lua, (class, func): (AnyUserData, Function))
@@ -1,14 +1,18 @@
 //! TODO Fill in
 use std::fmt::{self, Display, Formatter};
 use std::default::Default;
-use rlua::{self, Table, Lua, UserData, ToLua, Value};
-use super::object::{Object, Objectable};
-use super::class::{Class, ClassBuilder};
+use rlua::{self, Table, Lua, UserData, ToLua, Value, AnyUserData, UserDataMethods};
+use uuid::Uuid;
+use super::object::{self, Object, Objectable};
+use super::class::{self, Class, ClassBuilder};
+
+/// Handle to the client objects that have been made, by the id of their view.
+pub const CLIENTS_HANDLE: &'static str = "__clients";
 
 #[derive(Clone, Debug)]
 pub struct ClientState {
-    // TODO Fill in
-    dummy: i32
+    /// The id of the view in the layout
+    id: Option<Uuid>
 }
 
 pub struct Client<'lua>(Object<'lua>);
@@ -16,21 +20,37 @@ pub struct Client<'lua>(Object<'lua>);
 impl Default for ClientState {
     fn default() -> Self {
         ClientState {
-            dummy: 0
+            id: None
         }
     }
 }
 
-/* This is currently unused.
- * TODO: Figure out if this will be needed later.
-
 impl <'lua> Client<'lua> {
-    fn new(lua: &Lua) -> rlua::Result<Object> {
+    fn new(lua: &'lua Lua, id: Uuid) -> rlua::Result<Object<'lua>> {
         let class = class::class_setup(lua, "client")?;
-        Ok(Client::allocate(lua, class)?.build())
+        let client = Client::allocate(lua, class)?.build();
+        Client::cast(client.clone())?.get_object_mut()?.id = Some(id);
+        Ok(client)
+    }
+
+    /// Gets the id of the view this is the client of.
+    pub fn id(&self) -> rlua::Result<Option<Uuid>> {
+        Ok(self.state()?.id)
     }
 }
-*/
+
+/// Gets the client of the view with the id, making it the first time it's
+/// asked for so that a view is always the same client object.
+pub fn client_of<'lua>(lua: &'lua Lua, id: Uuid) -> rlua::Result<Client<'lua>> {
+    let clients = lua.named_registry_value::<Table>(CLIENTS_HANDLE)?;
+    let key = id.to_string();
+    if let Some(client) = clients.get::<_, Option<AnyUserData>>(key.as_str())? {
+        return Client::cast(client.into())
+    }
+    let client = Client::new(lua, id)?;
+    clients.set(key, client.object.clone())?;
+    Client::cast(client)
+}
 
 impl Display for ClientState {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
@@ -44,9 +64,14 @@ impl <'lua> ToLua<'lua> for Client<'lua> {
     }
 }
 
-impl UserData for ClientState {}
+impl UserData for ClientState {
+    fn add_methods(methods: &mut UserDataMethods<Self>) {
+        object::default_add_methods(methods);
+    }
+}
 
 pub fn init(lua: &Lua) -> rlua::Result<Class> {
+    lua.set_named_registry_value(CLIENTS_HANDLE, lua.create_table()?.to_lua(lua)?)?;
     method_setup(lua, Class::builder(lua, "client", None)?)?
         .save_class("client")?
         .build()
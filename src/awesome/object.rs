@@ -226,6 +226,12 @@ pub fn default_index<'lua>(lua: &'lua Lua,
                     }
                 }
                 if let Some(meta) = class_table.get_metatable() {
+                    // Not a property, so it might be a method of the class
+                    if !index.starts_with("__") {
+                        if let Value::Function(method) = meta.raw_get::<_, Value>(index)? {
+                            return Ok(Value::Function(method))
+                        }
+                    }
                     match meta.get::<_, Function>("__index_miss_handler") {
                         Ok(function) => {
                             return function.bind(obj)?.call(index)
@@ -267,6 +273,8 @@ pub fn default_newindex<'lua>(_: &'lua Lua,
                 if let Some(newindex) = prop.cb_newindex {
                     return newindex.bind(obj.clone())?.call(val)
                 }
+                use rlua::Error::RuntimeError;
+                return Err(RuntimeError(format!("property \"{}\" is read-only", index)))
             }
         }
         if let Some(meta) = class_table.get_metatable() {
@@ -44,7 +44,9 @@ pub struct ScreenState {
     // The screen outputs information
     pub outputs: Vec<Output>,
     // Some XID indetifying this screen
-    pub xid: u32
+    pub xid: u32,
+    // The position of the screen in the screen list, starting at 1
+    pub index: usize
 }
 
 impl_objectable!(Screen, ScreenState);
@@ -68,7 +70,8 @@ impl Default for ScreenState {
             geometry: Geometry::zero(),
             workarea: Geometry::zero(),
             outputs: vec![],
-            xid: 0
+            xid: 0,
+            index: 0
         }
     }
 }
@@ -86,13 +89,32 @@ impl <'lua> Screen<'lua> {
         Ok(Screen::allocate(lua, class)?.build())
     }
 
-    fn init_screens(&mut self, output: WlcOutput, outputs: Vec<Output>) -> rlua::Result<()> {
-        let mut state = self.get_object_mut()?;
-        let resolution = output.get_resolution().unwrap();
-        state.outputs = outputs;
-        state.geometry.size = resolution;
-        state.workarea.size = resolution;
-        Ok(())
+    fn init_screens(&mut self, output: WlcOutput, outputs: Vec<Output>, index: usize)
+                    -> rlua::Result<()> {
+        {
+            let mut state = self.get_object_mut()?;
+            let resolution = output.get_resolution().unwrap();
+            state.outputs = outputs;
+            state.geometry.size = resolution;
+            state.workarea.size = resolution;
+            state.index = index;
+        }
+        self.0.table()?.set("tags", Vec::<AnyUserData>::new())
+    }
+
+    pub fn index(&self) -> rlua::Result<usize> {
+        Ok(self.state()?.index)
+    }
+
+    /// The tags on this screen, stored on the object itself since
+    /// they are Lua objects.
+    pub fn tags(&self) -> rlua::Result<Vec<AnyUserData<'lua>>> {
+        Ok(self.0.table()?.get::<_, Option<Vec<AnyUserData>>>("tags")?
+           .unwrap_or_else(Vec::new))
+    }
+
+    pub fn set_tags(&mut self, tags: Vec<AnyUserData<'lua>>) -> rlua::Result<()> {
+        self.0.table()?.set("tags", tags)
     }
 
     fn get_geometry(&self, lua: &'lua Lua) -> rlua::Result<Table<'lua>> {
@@ -126,9 +148,9 @@ pub fn init(lua: &Lua) -> rlua::Result<Class> {
         .save_class("screen")?
         .build()?;
     let mut screens: Vec<Screen> = vec![];
-    for output in WlcOutput::list() {
+    for (index, output) in WlcOutput::list().into_iter().enumerate() {
         let mut screen = Screen::cast(Screen::new(lua)?)?;
-        screen.init_screens(output, vec![output.into()])?;
+        screen.init_screens(output, vec![output.into()], index + 1)?;
         // TODO Move to Screen impl like the others
         screens.push(screen);
     }
@@ -155,7 +177,15 @@ fn property_setup<'lua>(lua: &'lua Lua, builder: ClassBuilder<'lua>)
         .property(Property::new("workarea".into(),
                                 None,
                                 Some(lua.create_function(get_workarea)?),
-                                None))
+                                None))?
+        .property(Property::new("index".into(),
+                                None,
+                                Some(lua.create_function(get_index)?),
+                                None))?
+        .property(Property::new("tags".into(),
+                                None,
+                                Some(lua.create_function(get_tags)?),
+                                Some(lua.create_function(set_tags)?)))
 }
 
 fn get_geometry<'lua>(lua: &'lua Lua, object: AnyUserData<'lua>) -> rlua::Result<Table<'lua>> {
@@ -168,6 +198,21 @@ fn get_workarea<'lua>(lua: &'lua Lua, object: AnyUserData<'lua>) -> rlua::Result
     screen.get_workarea(lua)
 }
 
+fn get_index<'lua>(_: &'lua Lua, object: AnyUserData<'lua>) -> rlua::Result<Value<'lua>> {
+    Ok(Value::Integer(Screen::cast(object.into())?.index()? as _))
+}
+
+fn get_tags<'lua>(_: &'lua Lua, object: AnyUserData<'lua>)
+                  -> rlua::Result<Vec<AnyUserData<'lua>>> {
+    Screen::cast(object.into())?.tags()
+}
+
+fn set_tags<'lua>(_: &'lua Lua, (object, tags): (AnyUserData<'lua>, Vec<AnyUserData<'lua>>))
+                  -> rlua::Result<Value<'lua>> {
+    Screen::cast(object.into())?.set_tags(tags)?;
+    Ok(Value::Nil)
+}
+
 /// Ok this requires some explanation...
 /// Lua gives us the previous value in the loop, with the first one being nil
 /// since there was nothing there before.
@@ -252,3 +297,46 @@ fn index<'lua>(lua: &'lua Lua,
         Ok(value) => Ok(value)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use rlua::Lua;
+    use rustwlc::{Geometry, Point, Size};
+    use super::super::object::Objectable;
+    use super::super::screen::{self, Screen};
+    use super::super::tag;
+
+    #[test]
+    fn screen_geometry_test() {
+        let lua = Lua::new();
+        screen::init(&lua).unwrap();
+        tag::init(&lua).unwrap();
+        let mut screen = Screen::cast(Screen::new(&lua).unwrap()).unwrap();
+        {
+            let mut state = screen.get_object_mut().unwrap();
+            state.geometry = Geometry {
+                origin: Point { x: 10, y: 20 },
+                size: Size { w: 800, h: 600 }
+            };
+            state.workarea = state.geometry;
+            state.index = 1;
+        }
+        lua.globals().set("a_screen", screen).unwrap();
+        lua.eval::<()>(r#"
+local geometry = a_screen.geometry
+assert(geometry.x == 10)
+assert(geometry.y == 20)
+assert(geometry.width == 800)
+assert(geometry.height == 600)
+assert(a_screen.workarea.width == 800)
+assert(a_screen.index == 1)
+assert(#a_screen.tags == 0)
+a_screen.tags = { tag{ name = "1" }, tag{ name = "2" } }
+assert(#a_screen.tags == 2)
+assert(a_screen.tags[2].name == "2")
+-- Read-only properties can't be written to
+assert(not pcall(function() a_screen.geometry = {} end))
+assert(a_screen.geometry.width == 800)
+"#, None).unwrap()
+    }
+}
@@ -6,6 +6,7 @@
 
 use rlua::{self, Lua, Table, ToLuaMulti, Value, Function, ToLua};
 use super::{GLOBAL_SIGNALS, Object};
+use super::class::Class;
 
 /// Connects functions to a signal. Creates a new entry in the table if it
 /// doesn't exist.
@@ -59,6 +60,35 @@ pub fn emit_object_signal<'lua, A>(lua: &'lua Lua,
     emit_signals(lua, signals, name, args)
 }
 
+/// Connects functions to a signal on the class, which is emitted for
+/// events that concern every instance (e.g when one is created).
+pub fn connect_class_signal(lua: &Lua, class: Class, name: String, funcs: &[Function])
+                            -> rlua::Result<()> {
+    let signals = class.signals()?;
+    connect_signals(lua, signals, name, funcs)
+}
+
+pub fn disconnect_class_signal(lua: &Lua, class: Class, name: String)
+                               -> rlua::Result<()> {
+    let signals = class.signals()?;
+    disconnect_signals(lua, signals, name)
+}
+
+/// Evaluate the functions associated with a signal on the class.
+///
+/// Unlike `emit_object_signal`, nothing is prepended to the arguments,
+/// so the object the signal is about should be passed in them.
+pub fn emit_class_signal<'lua, A>(lua: &'lua Lua,
+                                  class: Class<'lua>,
+                                  name: String,
+                                  args: A)
+                                  -> rlua::Result<()>
+    where A: ToLuaMulti<'lua> + Clone
+{
+    let signals = class.signals()?;
+    emit_signals(lua, signals, name, args)
+}
+
 fn emit_signals<'lua, A>(_: &'lua Lua,
                          signals: Table<'lua>,
                          name: String,
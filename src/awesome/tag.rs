@@ -2,14 +2,26 @@
 
 use std::fmt::{self, Display, Formatter};
 use std::default::Default;
-use rlua::{self, Table, Lua, UserData, ToLua, Value, UserDataMethods};
+use rlua::{self, Table, Lua, UserData, ToLua, Value, AnyUserData, UserDataMethods};
+use uuid::Uuid;
 use super::object::{self, Object, Objectable};
+use super::signal;
+use super::property::Property;
 use super::class::{self, Class, ClassBuilder};
+use super::client;
+use ::layout::{try_lock_tree, Tree};
+
+/// Handle to the list of tags that have not been deleted, in creation order.
+pub const TAGS_HANDLE: &'static str = "__tags";
 
 #[derive(Clone, Debug)]
 pub struct TagState {
-    // TODO Fill in
-    dummy: i32
+    name: String,
+    selected: bool,
+    activated: bool,
+    index: usize,
+    /// The views on the tag
+    clients: Vec<Uuid>
 }
 
 pub struct Tag<'lua>(Object<'lua>);
@@ -17,7 +29,11 @@ pub struct Tag<'lua>(Object<'lua>);
 impl Default for TagState {
     fn default() -> Self {
         TagState {
-            dummy: 0
+            name: String::new(),
+            selected: false,
+            activated: false,
+            index: 0,
+            clients: Vec::new()
         }
     }
 }
@@ -25,9 +41,74 @@ impl Default for TagState {
 impl <'lua> Tag<'lua> {
     fn new(lua: &'lua Lua, args: Table) -> rlua::Result<Object<'lua>> {
         let class = class::class_setup(lua, "tag")?;
-        Ok(Tag::allocate(lua, class)?
-           .handle_constructor_argument(args)?
-           .build())
+        let tag = Tag::allocate(lua, class.clone())?
+            .handle_constructor_argument(args)?
+            .build();
+        let mut tags = lua.named_registry_value::<Vec<AnyUserData>>(TAGS_HANDLE)?;
+        tags.push(tag.object.clone());
+        lua.set_named_registry_value(TAGS_HANDLE, tags.to_lua(lua)?)?;
+        // The tag wasn't in the list yet when its constructor arguments
+        // selected it, so the layout still has to be told about it.
+        if Tag::cast(tag.clone())?.selected()? {
+            update_shown_tags(lua)?;
+        }
+        signal::emit_class_signal(lua, class, "tag::new".into(), tag.clone())?;
+        Ok(tag)
+    }
+
+    pub fn name(&self) -> rlua::Result<String> {
+        let tag = self.state()?;
+        Ok(tag.name.clone())
+    }
+
+    pub fn set_name(&mut self, name: String) -> rlua::Result<()> {
+        let mut tag = self.get_object_mut()?;
+        tag.name = name;
+        Ok(())
+    }
+
+    pub fn selected(&self) -> rlua::Result<bool> {
+        let tag = self.state()?;
+        Ok(tag.selected)
+    }
+
+    pub fn set_selected(&mut self, selected: bool) -> rlua::Result<()> {
+        let mut tag = self.get_object_mut()?;
+        tag.selected = selected;
+        Ok(())
+    }
+
+    pub fn activated(&self) -> rlua::Result<bool> {
+        let tag = self.state()?;
+        Ok(tag.activated)
+    }
+
+    pub fn set_activated(&mut self, activated: bool) -> rlua::Result<()> {
+        let mut tag = self.get_object_mut()?;
+        tag.activated = activated;
+        Ok(())
+    }
+
+    pub fn index(&self) -> rlua::Result<usize> {
+        let tag = self.state()?;
+        Ok(tag.index)
+    }
+
+    pub fn set_index(&mut self, index: usize) -> rlua::Result<()> {
+        let mut tag = self.get_object_mut()?;
+        tag.index = index;
+        Ok(())
+    }
+
+    pub fn clients(&self) -> rlua::Result<Vec<Uuid>> {
+        let tag = self.state()?;
+        Ok(tag.clients.clone())
+    }
+
+    pub fn set_clients(&mut self, clients: Vec<Uuid>) -> rlua::Result<()> {
+        let mut tag = self.get_object_mut()?;
+        tag.clients = clients;
+        Ok(())
     }
 }
 
@@ -50,16 +131,398 @@ impl UserData for TagState {
 }
 
 pub fn init(lua: &Lua) -> rlua::Result<Class> {
+    lua.set_named_registry_value(TAGS_HANDLE, lua.create_table()?.to_lua(lua)?)?;
     method_setup(lua, Class::builder(lua, "tag", None)?)?
         .save_class("tag")?
         .build()
 }
 
 fn method_setup<'lua>(lua: &'lua Lua, builder: ClassBuilder<'lua>) -> rlua::Result<ClassBuilder<'lua>> {
-    // TODO Do properly
-    use super::dummy;
-    builder.method("connect_signal".into(), lua.create_function(dummy)?)?
-           .method("__call".into(), lua.create_function(|lua, args: Table| Tag::new(lua, args))?)
+    builder.method("__call".into(), lua.create_function(|lua, args: Table| Tag::new(lua, args))?)?
+           .method("clients".into(), lua.create_function(clients)?)?
+           .method("delete".into(), lua.create_function(delete)?)?
+           .method("select".into(), lua.create_function(select)?)?
+           .method("toggle".into(), lua.create_function(toggle)?)?
+           .method("view_only".into(), lua.create_function(view_only)?)?
+           .property(Property::new("name".into(),
+                                   Some(lua.create_function(set_name)?),
+                                   Some(lua.create_function(get_name)?),
+                                   Some(lua.create_function(set_name)?)))?
+           .property(Property::new("selected".into(),
+                                   Some(lua.create_function(set_selected)?),
+                                   Some(lua.create_function(get_selected)?),
+                                   Some(lua.create_function(set_selected)?)))?
+           .property(Property::new("activated".into(),
+                                   Some(lua.create_function(set_activated)?),
+                                   Some(lua.create_function(get_activated)?),
+                                   Some(lua.create_function(set_activated)?)))?
+           .property(Property::new("index".into(),
+                                   Some(lua.create_function(set_index)?),
+                                   Some(lua.create_function(get_index)?),
+                                   Some(lua.create_function(set_index)?)))
+}
+
+fn set_name<'lua>(lua: &'lua Lua, (obj, val): (AnyUserData<'lua>, String))
+                  -> rlua::Result<Value<'lua>> {
+    let mut tag = Tag::cast(obj.clone().into())?;
+    tag.set_name(val.clone())?;
+    signal::emit_object_signal(lua,
+                               obj.into(),
+                               "property::name".into(),
+                               val)?;
+    Ok(Value::Nil)
+}
+
+fn get_name<'lua>(lua: &'lua Lua, obj: AnyUserData<'lua>)
+                  -> rlua::Result<Value<'lua>> {
+    Tag::cast(obj.into())?.name()?.to_lua(lua)
+}
+
+fn set_selected<'lua>(lua: &'lua Lua, (obj, val): (AnyUserData<'lua>, bool))
+                      -> rlua::Result<Value<'lua>> {
+    change_selected(lua, obj, val)?;
+    update_shown_tags(lua)?;
+    Ok(Value::Nil)
+}
+
+/// Selects or unselects the tag and emits "property::selected", without
+/// telling the layout about it yet.
+fn change_selected<'lua>(lua: &'lua Lua, obj: AnyUserData<'lua>, val: bool)
+                         -> rlua::Result<()> {
+    let mut tag = Tag::cast(obj.clone().into())?;
+    tag.set_selected(val)?;
+    signal::emit_object_signal(lua,
+                               obj.into(),
+                               "property::selected".into(),
+                               val)
+}
+
+/// Tells the layout which tags are selected, so that the views on them
+/// (and only those) are shown.
+fn update_shown_tags(lua: &Lua) -> rlua::Result<()> {
+    let tags = lua.named_registry_value::<Vec<AnyUserData>>(TAGS_HANDLE)?;
+    let mut selected = Vec::new();
+    for tag in tags {
+        let tag = Tag::cast(tag.into())?;
+        if tag.selected()? {
+            selected.push(tag.name()?);
+        }
+    }
+    match try_lock_tree() {
+        Ok(mut tree) => tree.show_tags(&selected).unwrap_or_else(|err| {
+            warn!("Could not show the selected tags {:?}: {}", selected, err);
+        }),
+        Err(_) => warn!("Could not lock the tree to show the selected tags")
+    }
+    Ok(())
+}
+
+/// Updates the views on the tag from the layout, so that they follow views
+/// being added, tagged, moved and removed.
+fn update_clients(tag: &mut Tag) -> rlua::Result<()> {
+    match try_lock_tree() {
+        Ok(tree) => update_clients_from(tag, &tree),
+        Err(_) => {
+            use rlua::Error::RuntimeError;
+            Err(RuntimeError("Could not lock the tree to get the clients".into()))
+        }
+    }
+}
+
+/// Sets the views on the tag to those the tree has on it.
+fn update_clients_from(tag: &mut Tag, tree: &Tree) -> rlua::Result<()> {
+    let name = tag.name()?;
+    tag.set_clients(tree.views_on_tag(&name))
+}
+
+/// Whether the two objects are the same tag, rather than two tags that
+/// happen to have the same properties.
+fn same_tag(a: &AnyUserData, b: &AnyUserData) -> rlua::Result<bool> {
+    let a = a.borrow::<TagState>()?;
+    let b = b.borrow::<TagState>()?;
+    Ok(&*a as *const TagState == &*b as *const TagState)
+}
+
+/// Selects the tag, keeping the other selected tags selected.
+fn select<'lua>(lua: &'lua Lua, obj: AnyUserData<'lua>) -> rlua::Result<()> {
+    set_selected(lua, (obj, true)).map(|_| ())
+}
+
+/// Selects the tag if it wasn't selected, otherwise unselects it,
+/// like `awful.tag.viewtoggle`.
+fn toggle<'lua>(lua: &'lua Lua, obj: AnyUserData<'lua>) -> rlua::Result<()> {
+    let selected = Tag::cast(obj.clone().into())?.selected()?;
+    set_selected(lua, (obj, !selected)).map(|_| ())
+}
+
+/// Selects the tag and unselects every other tag, like `awful.tag.viewonly`.
+fn view_only<'lua>(lua: &'lua Lua, obj: AnyUserData<'lua>) -> rlua::Result<()> {
+    let tags = lua.named_registry_value::<Vec<AnyUserData>>(TAGS_HANDLE)?;
+    for tag in tags {
+        let selected = same_tag(&tag, &obj)?;
+        if Tag::cast(tag.clone().into())?.selected()? != selected {
+            change_selected(lua, tag, selected)?;
+        }
+    }
+    if !Tag::cast(obj.clone().into())?.selected()? {
+        change_selected(lua, obj, true)?;
+    }
+    update_shown_tags(lua)
+}
+
+fn get_selected<'lua>(_: &'lua Lua, obj: AnyUserData<'lua>)
+                      -> rlua::Result<Value<'lua>> {
+    Ok(Value::Boolean(Tag::cast(obj.into())?.selected()?))
+}
+
+fn set_activated<'lua>(lua: &'lua Lua, (obj, val): (AnyUserData<'lua>, bool))
+                       -> rlua::Result<Value<'lua>> {
+    let mut tag = Tag::cast(obj.clone().into())?;
+    tag.set_activated(val)?;
+    signal::emit_object_signal(lua,
+                               obj.into(),
+                               "property::activated".into(),
+                               val)?;
+    Ok(Value::Nil)
+}
+
+fn get_activated<'lua>(_: &'lua Lua, obj: AnyUserData<'lua>)
+                       -> rlua::Result<Value<'lua>> {
+    Ok(Value::Boolean(Tag::cast(obj.into())?.activated()?))
+}
+
+fn set_index<'lua>(lua: &'lua Lua, (obj, val): (AnyUserData<'lua>, usize))
+                   -> rlua::Result<Value<'lua>> {
+    let mut tag = Tag::cast(obj.clone().into())?;
+    tag.set_index(val)?;
+    signal::emit_object_signal(lua,
+                               obj.into(),
+                               "property::index".into(),
+                               val)?;
+    Ok(Value::Nil)
+}
+
+fn get_index<'lua>(_: &'lua Lua, obj: AnyUserData<'lua>)
+                   -> rlua::Result<Value<'lua>> {
+    Ok(Value::Integer(Tag::cast(obj.into())?.index()? as _))
+}
+
+/// Gets the clients on the tag, see `LayoutTree::views_on_tag`.
+fn clients<'lua>(lua: &'lua Lua, obj: AnyUserData<'lua>) -> rlua::Result<Table<'lua>> {
+    let mut tag = Tag::cast(obj.into())?;
+    update_clients(&mut tag)?;
+    clients_table(lua, &tag)
+}
+
+/// Makes a table of the client objects for the views on the tag.
+fn clients_table<'lua>(lua: &'lua Lua, tag: &Tag<'lua>) -> rlua::Result<Table<'lua>> {
+    let table = lua.create_table()?;
+    for (index, id) in tag.clients()?.into_iter().enumerate() {
+        table.set(index + 1, client::client_of(lua, id)?)?;
+    }
+    Ok(table)
+}
+
+/// Unselects the tag and removes it, emitting "tag::removed".
+///
+/// If the tag was selected another tag is selected in its place, so that
+/// there is always a selected tag while there are tags left.
+fn delete<'lua>(lua: &'lua Lua, obj: AnyUserData<'lua>) -> rlua::Result<()> {
+    let tags = lua.named_registry_value::<Vec<AnyUserData>>(TAGS_HANDLE)?;
+    let tags_len = tags.len();
+    let mut remaining = Vec::with_capacity(tags.len());
+    for tag in tags {
+        if !same_tag(&tag, &obj)? {
+            remaining.push(tag);
+        }
+    }
+    if remaining.len() == tags_len {
+        use rlua::Error::RuntimeError;
+        return Err(RuntimeError("Tag has already been deleted".into()))
+    }
+    let was_selected = Tag::cast(obj.clone().into())?.selected()?;
+    if was_selected {
+        set_selected(lua, (obj.clone(), false))?;
+    }
+    {
+        let mut tag = Tag::cast(obj.clone().into())?;
+        tag.set_activated(false)?;
+    }
+    signal::emit_object_signal(lua,
+                               obj.clone().into(),
+                               "property::activated".into(),
+                               false)?;
+    lua.set_named_registry_value(TAGS_HANDLE, remaining.clone().to_lua(lua)?)?;
+    signal::emit_object_signal(lua, obj.into(), "tag::removed".into(), ())?;
+    if was_selected {
+        let any_selected = remaining.iter()
+            .map(|tag| Tag::cast(tag.clone().into()).and_then(|tag| tag.selected()))
+            .collect::<rlua::Result<Vec<_>>>()?
+            .into_iter().any(|selected| selected);
+        if !any_selected {
+            if let Some(tag) = remaining.into_iter().next() {
+                set_selected(lua, (tag, true))?;
+            }
+        }
+    }
+    Ok(())
 }
 
 impl_objectable!(Tag, TagState);
+
+#[cfg(test)]
+mod test {
+    use rlua::{Lua, AnyUserData};
+    use rustwlc::WlcView;
+    use super::super::tag::{self, Tag, update_clients_from, clients_table};
+    use super::super::client::{self, Client};
+    use super::super::object::Objectable;
+    use ::layout::{Handle, Tree};
+
+    #[test]
+    fn tag_name_test() {
+        let lua = Lua::new();
+        tag::init(&lua).unwrap();
+        lua.globals().set("a_tag", Tag::new(&lua, lua.create_table().unwrap()).unwrap())
+            .unwrap();
+        lua.eval::<()>(r#"
+assert(a_tag.name == "")
+a_tag.name = "web"
+assert(a_tag.name == "web")
+"#, None).unwrap()
+    }
+
+    #[test]
+    fn tag_index_dispatch_test() {
+        let lua = Lua::new();
+        tag::init(&lua).unwrap();
+        lua.eval::<()>(r#"
+local t = tag{ name = "web" }
+assert(t.name == "web")
+t.name = "mail"
+assert(t.name == "mail")
+t.selected = true
+assert(t.selected)
+-- Anything that isn't a property is looked up in the methods
+assert(type(t.clients) == "function")
+assert(#t:clients() == 0)
+assert(t.not_a_property == nil)
+"#, None).unwrap()
+    }
+
+    #[test]
+    fn tag_new_signal_test() {
+        let lua = Lua::new();
+        tag::init(&lua).unwrap();
+        lua.eval::<()>(r#"
+count = 0
+tag.connect_signal("tag::new", function(t)
+    assert(t.name == "")
+    count = count + 1
+end)
+"#, None).unwrap();
+        lua.globals().set("a_tag", Tag::new(&lua, lua.create_table().unwrap()).unwrap())
+            .unwrap();
+        assert_eq!(lua.globals().get::<_, i32>("count").unwrap(), 1);
+        lua.eval::<()>(r#"
+local _ = tag{}
+assert(count == 2)
+tag.disconnect_signal("tag::new")
+local _ = tag{}
+assert(count == 2)
+"#, None).unwrap()
+    }
+
+    #[test]
+    fn tag_selected_signal_test() {
+        let lua = Lua::new();
+        tag::init(&lua).unwrap();
+        lua.globals().set("a_tag", Tag::new(&lua, lua.create_table().unwrap()).unwrap())
+            .unwrap();
+        lua.eval::<()>(r#"
+hit = false
+a_tag:connect_signal("property::selected", function(tag) hit = true end)
+assert(not a_tag.selected)
+a_tag.selected = true
+assert(hit)
+assert(a_tag.selected)
+assert(not a_tag.activated)
+a_tag.index = 3
+assert(a_tag.index == 3)
+"#, None).unwrap()
+    }
+
+    #[test]
+    fn tag_selection_test() {
+        let lua = Lua::new();
+        tag::init(&lua).unwrap();
+        lua.eval::<()>(r#"
+local web = tag{ name = "web", activated = true }
+local mail = tag{ name = "mail", activated = true }
+changes = 0
+web:connect_signal("property::selected", function(t) changes = changes + 1 end)
+web:select()
+assert(web.selected and changes == 1)
+mail:toggle()
+assert(web.selected and mail.selected)
+web:toggle()
+assert(not web.selected and mail.selected and changes == 2)
+web:view_only()
+assert(web.selected and not mail.selected and changes == 3)
+-- Nothing changes, so nothing is emitted
+web:view_only()
+assert(changes == 3)
+"#, None).unwrap()
+    }
+
+    #[test]
+    fn tag_delete_test() {
+        let lua = Lua::new();
+        tag::init(&lua).unwrap();
+        lua.eval::<()>(r#"
+local web = tag{ name = "web", selected = true, activated = true }
+local mail = tag{ name = "mail", activated = true }
+removed = nil
+web:connect_signal("tag::removed", function(t) removed = t.name end)
+assert(#web:clients() == 0)
+web:delete()
+assert(removed == "web")
+assert(not web.selected)
+assert(not web.activated)
+-- Another tag is selected in its place
+assert(mail.selected)
+assert(not pcall(function() web:delete() end))
+"#, None).unwrap()
+    }
+
+    #[test]
+    fn tag_clients_test() {
+        let lua = Lua::new();
+        tag::init(&lua).unwrap();
+        client::init(&lua).unwrap();
+        let mut tree = Tree::new();
+        let view = WlcView::dummy(534);
+        tree.add_output(WlcView::dummy(535).as_output()).unwrap();
+        tree.add_view(view).unwrap();
+        let id = tree.lookup_handle(Handle::View(view)).unwrap();
+        tree.add_view_to_tags(id, vec!["clients_test".into()]).unwrap();
+        let obj = lua.eval::<AnyUserData>(r#"tag{ name = "clients_test" }"#, None)
+            .unwrap();
+        let mut tag = Tag::cast(obj.into()).unwrap();
+        update_clients_from(&mut tag, &tree).unwrap();
+        assert_eq!(tag.clients().unwrap(), vec![id]);
+        let first = clients_table(&lua, &tag).unwrap();
+        let second = clients_table(&lua, &tag).unwrap();
+        lua.globals().set("first", first).unwrap();
+        lua.globals().set("second", second).unwrap();
+        lua.eval::<()>(r#"
+-- A view is always the same client
+assert(first[1] == second[1])
+"#, None).unwrap();
+        let clients = lua.globals().get::<_, Vec<AnyUserData>>("first").unwrap()
+            .into_iter()
+            .map(|client| Client::cast(client.into()).unwrap().id().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(clients, vec![Some(id)]);
+    }
+}
@@ -0,0 +1,159 @@
+//! Assigning new views to workspaces by their application id,
+//! like i3's `assign`.
+
+use rustwlc::WlcView;
+
+use super::super::{LayoutTree, TreeError};
+use super::super::core::container::{Container, ContainerType};
+
+/// A rule that puts new views whose application id matches the pattern
+/// on a workspace.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Assignment {
+    /// Matched against the application id, see `Assignment::matches`.
+    pub pattern: String,
+    /// The name of the workspace the views are put on.
+    pub workspace: String,
+    /// Whether the user is switched to the workspace along with the view.
+    pub follow: bool
+}
+
+impl Assignment {
+    /// Determines if the application id matches the pattern of the rule.
+    ///
+    /// A pattern with `*` or `?` in it is a glob that must match the whole
+    /// application id, any other pattern matches if it's a part of it.
+    pub fn matches(&self, app_id: &str) -> bool {
+        if self.pattern.contains('*') || self.pattern.contains('?') {
+            glob_matches(self.pattern.as_bytes(), app_id.as_bytes())
+        } else {
+            app_id.contains(self.pattern.as_str())
+        }
+    }
+}
+
+/// Matches the text against a glob, where `*` is any run of characters
+/// and `?` is any one character.
+fn glob_matches(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((&b'*', rest)) => {
+            (0..text.len() + 1).any(|skip| glob_matches(rest, &text[skip..]))
+        },
+        Some((&b'?', rest)) => {
+            !text.is_empty() && glob_matches(rest, &text[1..])
+        },
+        Some((&c, rest)) => {
+            text.first() == Some(&c) && glob_matches(rest, &text[1..])
+        }
+    }
+}
+
+impl LayoutTree {
+    /// Adds a rule putting new views whose application id matches the
+    /// pattern on the workspace, which is made if it doesn't exist yet.
+    ///
+    /// If `follow` is set, the user is switched to that workspace as well.
+    /// Rules are tried in the order they were added.
+    pub fn add_assignment(&mut self, app_id_pattern: &str, target_workspace: &str,
+                          follow: bool) {
+        self.assignments.push(Assignment {
+            pattern: app_id_pattern.into(),
+            workspace: target_workspace.into(),
+            follow: follow
+        });
+    }
+
+    /// Removes all of the assignment rules.
+    pub fn clear_assignments(&mut self) {
+        self.assignments.clear();
+    }
+
+    /// Gets the rule the view is assigned by, if it is to be put on
+    /// a workspace other than the current one.
+    pub fn assignment_for(&self, view: WlcView) -> Option<Assignment> {
+        let app_id = view.get_app_id();
+        let assignment = match self.assignments.iter().find(|rule| rule.matches(&app_id)) {
+            Some(assignment) => assignment,
+            None => return None
+        };
+        match self.current_workspace() {
+            Ok(name) if name != assignment.workspace => Some(assignment.clone()),
+            _ => None
+        }
+    }
+
+    /// Adds the view to the workspace of the rule it was assigned by.
+    ///
+    /// Unless the rule follows the view, the user stays where they were
+    /// and the view is only focused within its workspace.
+    pub fn add_assigned_view(&mut self, view: WlcView, assignment: Assignment)
+                             -> Result<&Container, TreeError> {
+        let old_workspace = self.current_workspace()?.to_string();
+        let prev_workspace = self.prev_workspace.clone();
+        self.switch_to_workspace(&assignment.workspace);
+        if self.current_workspace().ok() != Some(assignment.workspace.as_str()) {
+            warn!("Could not switch to workspace {} for assigned view {:?}",
+                  assignment.workspace, view);
+            return Err(TreeError::UuidNotAssociatedWith(ContainerType::Workspace))
+        }
+        let id = self.add_view(view)?.get_id();
+        if !assignment.follow {
+            self.switch_to_workspace(&old_workspace);
+            self.prev_workspace = prev_workspace;
+        }
+        self.lookup(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Assignment;
+    use super::super::super::core::tree::tests::basic_tree;
+    use super::super::super::core::container::ContainerType;
+    use rustwlc::WlcView;
+
+    #[test]
+    fn assignment_patterns() {
+        let rule = |pattern: &str| Assignment {
+            pattern: pattern.into(),
+            workspace: "2".into(),
+            follow: false
+        };
+        assert!(rule("fox").matches("firefox"));
+        assert!(!rule("chrome").matches("firefox"));
+        assert!(rule("fire*").matches("firefox"));
+        assert!(rule("*fox").matches("firefox"));
+        assert!(rule("f?ref?x").matches("firefox"));
+        assert!(!rule("fire*").matches("a firefox"));
+        assert!(!rule("firefo?").matches("firefo"));
+        assert!(rule("*").matches(""));
+    }
+
+    #[test]
+    fn assigned_views_land_on_their_workspace() {
+        let mut tree = basic_tree();
+        let active_id = tree.get_active_container().unwrap().get_id();
+        tree.add_assignment("*", "3", false);
+        let id = tree.add_view(WlcView::dummy(1)).unwrap().get_id();
+        let node_ix = tree.tree.lookup_id(id).unwrap();
+        let workspace_ix = tree.tree.ancestor_of_type(node_ix, ContainerType::Workspace)
+            .unwrap();
+        assert_eq!(tree.tree[workspace_ix].get_name(), Some("3"));
+        // The user stays where they were
+        assert_eq!(tree.current_workspace(), Ok("1"));
+        assert_eq!(tree.get_active_container().unwrap().get_id(), active_id);
+
+        // Following the view switches to its workspace
+        tree.clear_assignments();
+        tree.add_assignment("*", "4", true);
+        let id = tree.add_view(WlcView::dummy(2)).unwrap().get_id();
+        assert_eq!(tree.current_workspace(), Ok("4"));
+        assert_eq!(tree.get_active_container().unwrap().get_id(), id);
+        // Views opened on the assigned workspace are added as usual
+        let other_id = tree.add_view(WlcView::dummy(3)).unwrap().get_id();
+        assert_eq!(tree.current_workspace(), Ok("4"));
+        assert_eq!(tree.get_active_container().unwrap().get_id(), other_id);
+        tree.validate();
+    }
+}
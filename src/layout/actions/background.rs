@@ -1,11 +1,15 @@
 //! A collection of methods that modify the background of the outputs.
 use super::super::{Container, ContainerType, LayoutTree, TreeError,
-                   MaybeBackground, IncompleteBackground};
+                   MaybeBackground, IncompleteBackground, BackgroundMode};
 use super::super::commands::CommandResult;
 
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
 use uuid::Uuid;
 use wayland_sys::server::wl_client;
-use rustwlc::WlcView;
+use rustwlc::{WlcOutput, WlcView};
+use ::layout::core::background::BACKGROUND_PROGRAM;
 use rustwlc::wayland::wlc_view_get_wl_client;
 
 impl LayoutTree {
@@ -47,6 +51,10 @@ impl LayoutTree {
             Container::Output { ref mut background, .. } => {
                 match *background {
                     None => {
+                        let mut bg = bg;
+                        if let Some((_, mode)) = self.default_background {
+                            bg.mode = mode;
+                        }
                         *background = Some(bg.into());
                         Ok(())
                     },
@@ -65,4 +73,84 @@ impl LayoutTree {
                                               vec![ContainerType::Output]))
         }
     }
+
+    /// Sets how the background of the output fills it, and lays out
+    /// the output so it takes effect.
+    ///
+    /// The mode is kept when the output changes resolution.
+    pub fn set_background_mode(&mut self, output: WlcOutput, mode: BackgroundMode)
+                               -> CommandResult {
+        let root_ix = self.tree.root_ix();
+        let output_ix = self.tree.descendant_with_handle(root_ix, output.into())
+            .ok_or(TreeError::OutputNotFound(output))?;
+        match self.tree[output_ix] {
+            Container::Output { ref mut background, .. } => {
+                match *background {
+                    Some(ref mut background) => background.set_mode(mode),
+                    None => {
+                        warn!("Tried to set background mode on {:?}, \
+                               which has no background", output);
+                        return Ok(())
+                    }
+                }
+            },
+            _ => unreachable!()
+        }
+        self.layout(output_ix);
+        Ok(())
+    }
+
+    /// Sets the image drawn on outputs when they are added, and how it
+    /// fills them. Outputs that are already there keep their background.
+    ///
+    /// Backgrounds loaded after this take the mode, unless it's changed
+    /// with `set_background_mode`.
+    pub fn set_default_background(&mut self, path: PathBuf, mode: BackgroundMode) {
+        self.default_background = Some((path, mode));
+    }
+
+    /// Spawns the program that draws the default background, if there is
+    /// one, for a new output. It attaches itself once it's connected.
+    pub fn spawn_default_background(&self, output: WlcOutput) {
+        let path = match self.default_background {
+            Some((ref path, _)) => path,
+            None => return
+        };
+        info!("Spawning default background {:?} for {:?}", path, output);
+        let result = Command::new(BACKGROUND_PROGRAM)
+            .arg(path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        if let Err(err) = result {
+            warn!("Could not spawn {} for the default background: {:?}",
+                  BACKGROUND_PROGRAM, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::ptr;
+    use super::super::super::core::tree::tests::basic_tree;
+    use super::super::super::{BackgroundMode, ContainerType, IncompleteBackground,
+                              MaybeBackground};
+    use super::super::super::Container;
+
+    #[test]
+    fn default_background_sets_mode() {
+        let mut tree = basic_tree();
+        tree.set_default_background(PathBuf::from("/tmp/wallpaper.png"),
+                                    BackgroundMode::Fill);
+        let output_ix = tree.active_ix_of(ContainerType::Output).unwrap();
+        let output_id = tree.tree[output_ix].get_id();
+        tree.attach_incomplete_background(IncompleteBackground::new(ptr::null_mut()),
+                                          output_id).unwrap();
+        match tree.tree[output_ix] {
+            Container::Output { background: Some(MaybeBackground::Incomplete(bg)), .. } =>
+                assert_eq!(bg.mode, BackgroundMode::Fill),
+            _ => panic!("Output had no incomplete background")
+        }
+    }
 }
@@ -1,8 +1,75 @@
 use petgraph::graph::NodeIndex;
+use rustwlc::WlcView;
 
 use super::super::{LayoutTree, TreeError};
 use super::super::commands::CommandResult;
-use super::super::core::container::{Container, ContainerType};
+use super::super::core::container::{Container, ContainerType, Layout};
+use ::render::Color;
+
+/// When the borders of tiled views are hidden, like i3's `hide_edge_borders`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HideEdgeBorders {
+    /// Borders are always drawn.
+    None,
+    /// Borders are hidden when the view is the only tiled view on its workspace.
+    Smart,
+    /// Borders of tiled views are never drawn.
+    Both
+}
+
+/// Colors of the borders in each of their states, set by the user.
+///
+/// Colors that are `None` fall back to the ones in the registry.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BorderColors {
+    /// The borders of the focused container and its ancestors.
+    pub active: Option<Color>,
+    /// The borders of every other container.
+    pub inactive: Option<Color>,
+    /// The borders of views that want the user's attention.
+    pub urgent: Option<Color>,
+    /// The background of the title bar of the focused container.
+    pub focused_title: Option<Color>,
+    /// The text of the title bar of the focused container.
+    pub focused_title_text: Option<Color>
+}
+
+impl BorderColors {
+    /// Gets the color of the borders of a container, if one was set for
+    /// its state. Urgent colors only apply to unfocused containers.
+    pub fn border_color(&self, active: bool, urgent: bool) -> Option<Color> {
+        match (active, urgent) {
+            (true, _) => self.active,
+            (false, true) => self.urgent.or(self.inactive),
+            (false, false) => self.inactive
+        }
+    }
+
+    /// Sets the colors of the borders of the container for its state,
+    /// leaving the ones that weren't set alone.
+    pub fn apply(&self, container: &mut Container, active: bool) {
+        let urgent = container.urgent();
+        match *container {
+            Container::View { ref mut borders, .. } |
+            Container::Container { ref mut borders, .. } => {
+                if let Some(borders) = borders.as_mut() {
+                    if let Some(color) = self.border_color(active, urgent) {
+                        borders.set_color(Some(color));
+                    }
+                    if active {
+                        if let Some(color) = self.focused_title {
+                            borders.set_title_color(Some(color));
+                        }
+                        if let Some(color) = self.focused_title_text {
+                            borders.set_title_font_color(Some(color));
+                        }
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+}
 
 /// The mode the borders can be in. This affects the color primarily.
 pub enum Mode {
@@ -40,10 +107,129 @@ impl LayoutTree {
                     Mode::Active => container.active_border_color()?,
                     Mode::Inactive => container.clear_border_color()?
                 }
+                let active = match focus {
+                    Mode::Active => true,
+                    Mode::Inactive => false
+                };
+                self.border_colors.apply(container, active);
                 container.draw_borders()?;
             }
             node_ix = self.tree.parent_of(node_ix)?;
         }
         Ok(())
     }
+
+    /// Sets the colors of the borders, and redraws the borders
+    /// on the visible workspaces with them.
+    pub fn set_border_colors(&mut self, colors: BorderColors) -> CommandResult {
+        self.border_colors = colors;
+        let root_ix = self.tree.root_ix();
+        for node_ix in self.tree.all_descendants_of(root_ix) {
+            if self.tree[node_ix].get_type() != ContainerType::Workspace ||
+                !self.workspace_visible(node_ix)? {
+                continue
+            }
+            for child_ix in self.tree.all_descendants_of(node_ix) {
+                let active = self.tree.on_path(child_ix);
+                let container = &mut self.tree[child_ix];
+                if active {
+                    container.active_border_color()?;
+                } else {
+                    container.clear_border_color()?;
+                }
+                self.border_colors.apply(container, active);
+                container.draw_borders()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Updates the title drawn in the borders of the view, e.g when the
+    /// client changed its title.
+    ///
+    /// Only the borders showing the title are redrawn: the view's own if
+    /// it draws a title bar, and the title strip of its parent if that is
+    /// tabbed or stacked. Views that aren't in the tree are ignored.
+    pub fn update_view_title(&mut self, view: WlcView, title: String) -> CommandResult {
+        let node_ix = match self.tree.lookup_view(view) {
+            Some(node_ix) => node_ix,
+            None => return Ok(())
+        };
+        let draw_title = match self.tree[node_ix] {
+            Container::View { ref mut borders, .. } => {
+                match borders.as_mut() {
+                    Some(borders) => {
+                        borders.set_title(title.clone());
+                        borders.draw_title
+                    },
+                    None => false
+                }
+            },
+            _ => unreachable!()
+        };
+        if draw_title {
+            self.tree[node_ix].draw_borders()?;
+        }
+        let parent_ix = self.tree.parent_of(node_ix)?;
+        let index = self.tree.grounded_children(parent_ix).iter()
+            .position(|&child_ix| child_ix == node_ix);
+        match self.tree[parent_ix] {
+            Container::Container { layout: Layout::Tabbed, ref mut borders, .. } |
+            Container::Container { layout: Layout::Stacked, ref mut borders, .. } => {
+                let children = borders.as_mut()
+                    .and_then(|borders| borders.children.as_mut());
+                if let (Some(children), Some(index)) = (children, index) {
+                    if index < children.titles.len() {
+                        children.titles[index] = title;
+                    }
+                }
+            },
+            _ => return Ok(())
+        }
+        self.tree[parent_ix].draw_borders()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BorderColors;
+    use super::super::super::core::tree::tests::basic_tree;
+    use ::render::Color;
+    use rustwlc::WlcView;
+
+    #[test]
+    fn update_view_title_ignores_unknown_views() {
+        let mut tree = basic_tree();
+        let view = WlcView::dummy(1);
+        assert_eq!(tree.update_view_title(view, "unknown".into()), Ok(()));
+        tree.add_view(view).unwrap();
+        assert_eq!(tree.update_view_title(view, "known".into()), Ok(()));
+        tree.validate();
+    }
+
+    #[test]
+    fn border_colors_by_state() {
+        let active: Color = 0xFF0000u32.into();
+        let inactive: Color = 0x00FF00u32.into();
+        let urgent: Color = 0x0000FFu32.into();
+        let mut colors = BorderColors {
+            active: Some(active),
+            inactive: Some(inactive),
+            .. BorderColors::default()
+        };
+        assert_eq!(colors.border_color(true, false), Some(active));
+        assert_eq!(colors.border_color(false, false), Some(inactive));
+        // Without an urgent color, urgent views look like the others
+        assert_eq!(colors.border_color(false, true), Some(inactive));
+        colors.urgent = Some(urgent);
+        assert_eq!(colors.border_color(false, true), Some(urgent));
+        assert_eq!(colors.border_color(true, true), Some(active));
+        assert_eq!(BorderColors::default().border_color(true, false), None);
+
+        let mut tree = basic_tree();
+        tree.set_border_colors(colors).unwrap();
+        assert_eq!(tree.border_colors, colors);
+        tree.validate();
+    }
 }
@@ -0,0 +1,183 @@
+//! Selecting views in the tree by their properties, like i3's
+//! `[class="..."]` criteria.
+
+use uuid::Uuid;
+
+use super::super::LayoutTree;
+use super::super::commands::CommandResult;
+use super::super::core::TreeError;
+use super::super::core::container::{Container, ContainerType};
+
+/// A set of predicates a view must satisfy to match.
+///
+/// Fields that are `None` are not checked, so the default criteria
+/// matches every view. Strings must match exactly, except for
+/// `title_contains`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Criteria {
+    pub class: Option<String>,
+    pub app_id: Option<String>,
+    pub title: Option<String>,
+    /// Matches views whose title has this in it
+    pub title_contains: Option<String>,
+    pub floating: Option<bool>,
+    /// Matches the view that holds this mark
+    pub mark: Option<String>,
+    /// Matches the view with this id in the tree
+    pub id: Option<Uuid>
+}
+
+impl Criteria {
+    /// Determines if the container satisfies all of the criteria.
+    ///
+    /// Only views can match, all other containers return false.
+    pub fn matches(&self, container: &Container) -> bool {
+        let handle = match *container {
+            Container::View { handle, .. } => handle,
+            _ => return false
+        };
+        if let Some(ref class) = self.class {
+            if handle.get_class() != *class {
+                return false
+            }
+        }
+        if let Some(ref app_id) = self.app_id {
+            if container.app_id() != Some(app_id.as_str()) {
+                return false
+            }
+        }
+        if let Some(ref title) = self.title {
+            if handle.get_title() != *title {
+                return false
+            }
+        }
+        if let Some(ref title) = self.title_contains {
+            if !handle.get_title().contains(title.as_str()) {
+                return false
+            }
+        }
+        if let Some(floating) = self.floating {
+            if container.floating() != floating {
+                return false
+            }
+        }
+        if let Some(ref mark) = self.mark {
+            if !container.marks().map(|marks| marks.contains(mark)).unwrap_or(false) {
+                return false
+            }
+        }
+        if let Some(id) = self.id {
+            if container.get_id() != id {
+                return false
+            }
+        }
+        true
+    }
+}
+
+impl LayoutTree {
+    /// Gets the ids of every view in the tree that matches the criteria,
+    /// in tree order, followed by the matching views in the scratchpad.
+    pub fn containers_matching(&self, criteria: &Criteria) -> Vec<Uuid> {
+        self.all_descendants_with_scratchpad().into_iter()
+            .filter(|&node_ix| self.tree[node_ix].get_type() == ContainerType::View)
+            .filter(|&node_ix| criteria.matches(&self.tree[node_ix]))
+            .map(|node_ix| self.tree[node_ix].get_id())
+            .collect()
+    }
+
+    /// Gets the ids of every view with the application id, in tree order.
+    pub fn views_with_app_id(&self, app_id: &str) -> Vec<Uuid> {
+        self.containers_matching(&Criteria {
+            app_id: Some(app_id.into()),
+            .. Criteria::default()
+        })
+    }
+
+    /// Focuses on the first view that matches the criteria, switching to
+    /// its workspace if it's not the active one.
+    ///
+    /// Views that can't be focused, or that are in the scratchpad, are skipped.
+    pub fn focus_by_criteria(&mut self, criteria: Criteria) -> CommandResult {
+        let node_ix = self.containers_matching(&criteria).into_iter()
+            .filter_map(|id| self.tree.lookup_id(id))
+            .find(|&node_ix| {
+                self.tree[node_ix].focusable() &&
+                    self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)
+                    .map(|workspace_ix| !self.is_scratchpad(workspace_ix))
+                    .unwrap_or(false)
+            })
+            .ok_or_else(|| TreeError::NoMatchingView(criteria))?;
+        let workspace_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)?;
+        if self.active_ix_of(ContainerType::Workspace) != Some(workspace_ix) {
+            let name = self.tree[workspace_ix].name();
+            self.switch_to_workspace(&name);
+        }
+        self.set_active_node(node_ix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Criteria;
+    use super::super::super::core::tree::tests::basic_tree;
+    use super::super::super::core::TreeError;
+    use super::super::super::core::container::ContainerType;
+    use rustwlc::WlcView;
+
+    #[test]
+    fn views_with_app_id_test() {
+        let mut tree = basic_tree();
+        let id = tree.add_view(WlcView::dummy(1)).unwrap().get_id();
+        let node_ix = tree.tree.lookup_id(id).unwrap();
+        let app_id = tree.tree[node_ix].app_id().unwrap().to_string();
+        assert_eq!(app_id, WlcView::dummy(1).get_app_id());
+        let views = tree.views_with_app_id(&app_id);
+        assert!(views.contains(&id));
+        assert!(views.iter().all(|&view_id| {
+            let view_ix = tree.tree.lookup_id(view_id).unwrap();
+            tree.tree[view_ix].app_id() == Some(app_id.as_str())
+        }));
+        let root_ix = tree.tree.root_ix();
+        assert_eq!(tree.tree[root_ix].app_id(), None);
+        assert!(tree.views_with_app_id("not an app id").is_empty());
+    }
+
+    #[test]
+    fn focus_by_criteria_test() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let marked_id = tree.get_active_container().unwrap().get_id();
+        tree.mark_container(marked_id, "editor".into(), false).unwrap();
+        tree.switch_to_workspace("1");
+        // Switches over to the workspace of the view
+        tree.focus_by_criteria(Criteria {
+            mark: Some("editor".into()),
+            .. Criteria::default()
+        }).unwrap();
+        assert_eq!(tree.get_active_container().unwrap().get_id(), marked_id);
+        let workspace_ix = tree.active_ix_of(ContainerType::Workspace).unwrap();
+        assert_eq!(tree.tree[workspace_ix].name(), "2");
+        tree.switch_to_workspace("1");
+        let id = tree.get_active_container().unwrap().get_id();
+        tree.switch_to_workspace("2");
+        tree.focus_by_criteria(Criteria { id: Some(id), .. Criteria::default() }).unwrap();
+        assert_eq!(tree.get_active_container().unwrap().get_id(), id);
+        let title = WlcView::dummy(0).get_title();
+        assert!(tree.focus_by_criteria(Criteria {
+            title_contains: Some(title),
+            .. Criteria::default()
+        }).is_ok());
+        let criteria = Criteria {
+            mark: Some("not a mark".into()),
+            .. Criteria::default()
+        };
+        assert_eq!(tree.focus_by_criteria(criteria.clone()),
+                   Err(TreeError::NoMatchingView(criteria)));
+        // Views that can't be focused don't count
+        tree.set_focusable(id, false).unwrap();
+        let criteria = Criteria { id: Some(id), .. Criteria::default() };
+        assert_eq!(tree.focus_by_criteria(criteria.clone()),
+                   Err(TreeError::NoMatchingView(criteria)));
+    }
+}
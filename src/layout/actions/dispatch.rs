@@ -0,0 +1,639 @@
+//! A small i3-msg style command language, so that the tree can be driven by
+//! plain strings coming in from external clients.
+
+use std::str::FromStr;
+
+use rustwlc::{Point, Size, WlcView};
+use uuid::Uuid;
+
+use super::super::LayoutTree;
+use super::super::commands::CommandResult;
+use super::super::core::{Direction, TreeError};
+use super::super::core::container::{Container, ContainerType, Layout};
+use super::criteria::Criteria;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CommandErr {
+    /// The command string had nothing in it.
+    Empty,
+    /// The first word of the command was not a known command.
+    UnknownCommand(String),
+    /// The command was known, but the argument given was missing or invalid.
+    /// Gives the command name and the offending argument.
+    InvalidArgument(String, String),
+    /// A quote was opened but never closed.
+    UnterminatedQuote(String),
+    /// A `;` or `,` was found where a single command was expected.
+    UnexpectedSeparator(char),
+    /// The `[...]` criteria were malformed, or not at the start of a command.
+    InvalidCriteria(String),
+    /// The command at this index in a `;` separated chain failed,
+    /// the rest of the chain was not run.
+    Failed(usize, Box<TreeError>)
+}
+
+impl From<CommandErr> for TreeError {
+    fn from(err: CommandErr) -> TreeError {
+        TreeError::Command(err)
+    }
+}
+
+/// A parsed command, ready to be run against the tree.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    /// Move the focus in a direction
+    Focus(Direction),
+    /// Focus on the matched view, e.g `[con_mark="a"] focus`
+    FocusMatched,
+    /// Move the active container in a direction
+    Move(Direction),
+    /// Move the active container to the workspace with this name
+    MoveToWorkspace(String),
+    /// Move the active floating view so its top left corner is here
+    MovePosition(i32, i32),
+    /// Move the active floating view to the center of its output
+    MoveCenter,
+    /// Resize the active floating view to this width and height
+    ResizeSet(u32, u32),
+    /// Switch to the workspace with this name
+    Workspace(String),
+    /// Change the layout of the active container's parent
+    Layout(Layout),
+    /// Split the active view in this direction when the next view is added
+    Split(Layout),
+    /// Split the active container, oriented by its shape if the default
+    /// orientation is auto
+    SplitAuto,
+    /// Set the fullscreen flag of the active container.
+    /// `None` means toggle it.
+    Fullscreen(Option<bool>),
+    /// Close the active container
+    Kill
+}
+
+/// A piece of a command string, after quotes have been handled.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+    Word(String),
+    /// `,`, separates commands that share the same criteria
+    Comma,
+    /// `;`, separates commands that are run one after the other
+    Semicolon,
+    /// The raw contents of a `[...]` criteria block
+    Criteria(String)
+}
+
+/// A group of `,` separated commands that share the same criteria.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Statement {
+    /// The views to run the commands on.
+    /// If `None`, the commands are run on the active container.
+    pub criteria: Option<Criteria>,
+    pub commands: Vec<Command>
+}
+
+/// Splits the command string into words and separators.
+///
+/// Quoted strings (with either `"` or `'`) are kept as a single word,
+/// so workspace names can contain spaces and separators.
+/// A `[` starts a criteria block, which is kept as is until the closing `]`.
+fn tokenize(cmd: &str) -> Result<Vec<Token>, CommandErr> {
+    let mut tokens = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = cmd.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                let quoted = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c => break,
+                        Some('\\') => {
+                            if let Some(escaped) = chars.next() {
+                                quoted.push(escaped);
+                            }
+                        },
+                        Some(other) => quoted.push(other),
+                        None => return Err(CommandErr::UnterminatedQuote(cmd.into()))
+                    }
+                }
+            },
+            '[' if word.is_none() => {
+                let mut raw = String::new();
+                let mut quote = None;
+                loop {
+                    match chars.next() {
+                        Some(']') if quote.is_none() => break,
+                        Some(q @ '"') | Some(q @ '\'') => {
+                            if quote == Some(q) {
+                                quote = None;
+                            } else if quote.is_none() {
+                                quote = Some(q);
+                            }
+                            raw.push(q);
+                        },
+                        Some(other) => raw.push(other),
+                        None => return Err(CommandErr::InvalidCriteria(cmd.into()))
+                    }
+                }
+                tokens.push(Token::Criteria(raw));
+            },
+            ';' | ',' => {
+                if let Some(word) = word.take() {
+                    tokens.push(Token::Word(word));
+                }
+                tokens.push(if c == ';' { Token::Semicolon } else { Token::Comma });
+            },
+            c if c.is_whitespace() => {
+                if let Some(word) = word.take() {
+                    tokens.push(Token::Word(word));
+                }
+            },
+            c => word.get_or_insert_with(String::new).push(c)
+        }
+    }
+    if let Some(word) = word {
+        tokens.push(Token::Word(word));
+    }
+    Ok(tokens)
+}
+
+/// Parses the inside of a `[...]` block, e.g `class="firefox" floating=true`.
+fn parse_criteria(raw: &str) -> Result<Criteria, CommandErr> {
+    let invalid = || CommandErr::InvalidCriteria(raw.into());
+    let mut criteria = Criteria::default();
+    for token in tokenize(raw)? {
+        let pair = match token {
+            Token::Word(pair) => pair,
+            _ => return Err(invalid())
+        };
+        let mut split = pair.splitn(2, '=');
+        let key = split.next().unwrap_or("");
+        let value = split.next().ok_or_else(|| invalid())?.to_string();
+        match key {
+            "class" => criteria.class = Some(value),
+            "app_id" => criteria.app_id = Some(value),
+            "title" => criteria.title = Some(value),
+            "title_contains" => criteria.title_contains = Some(value),
+            "con_mark" => criteria.mark = Some(value),
+            "con_id" => criteria.id = Some(Uuid::parse_str(&value).map_err(|_| invalid())?),
+            "floating" => criteria.floating = Some(match value.as_str() {
+                "true" => true,
+                "false" => false,
+                _ => return Err(invalid())
+            }),
+            _ => return Err(invalid())
+        }
+    }
+    Ok(criteria)
+}
+
+/// Parses a chain of commands.
+///
+/// The statements are split on `;` and are run in order, each one has
+/// `,` separated commands that share the same (optional) criteria.
+/// Empty commands (e.g a trailing `;`) are skipped.
+pub fn parse_chain(cmd: &str) -> Result<Vec<Statement>, CommandErr> {
+    let mut chain = Vec::new();
+    let mut statement = Statement::default();
+    let mut words = Vec::new();
+    for token in tokenize(cmd)? {
+        match token {
+            Token::Word(word) => words.push(word),
+            Token::Criteria(raw) => {
+                // Criteria can only be given once, before any commands
+                if !words.is_empty() || !statement.commands.is_empty()
+                    || statement.criteria.is_some() {
+                    return Err(CommandErr::InvalidCriteria(raw))
+                }
+                statement.criteria = Some(parse_criteria(&raw)?);
+            },
+            Token::Comma => {
+                if !words.is_empty() {
+                    statement.commands.push(Command::from_words(&words)?);
+                    words.clear();
+                }
+            },
+            Token::Semicolon => {
+                if !words.is_empty() {
+                    statement.commands.push(Command::from_words(&words)?);
+                    words.clear();
+                }
+                if !statement.commands.is_empty() {
+                    chain.push(statement);
+                    statement = Statement::default();
+                } else if statement.criteria.is_some() {
+                    return Err(CommandErr::Empty)
+                }
+            }
+        }
+    }
+    if !words.is_empty() {
+        statement.commands.push(Command::from_words(&words)?);
+    }
+    if !statement.commands.is_empty() {
+        chain.push(statement);
+    } else if statement.criteria.is_some() {
+        return Err(CommandErr::Empty)
+    }
+    if chain.is_empty() {
+        return Err(CommandErr::Empty)
+    }
+    Ok(chain)
+}
+
+impl Command {
+    /// Parses a single command, e.g "focus left" or "workspace 3".
+    pub fn parse(cmd: &str) -> Result<Command, CommandErr> {
+        let mut words = Vec::new();
+        for token in tokenize(cmd)? {
+            match token {
+                Token::Word(word) => words.push(word),
+                Token::Comma => return Err(CommandErr::UnexpectedSeparator(',')),
+                Token::Semicolon => return Err(CommandErr::UnexpectedSeparator(';')),
+                Token::Criteria(raw) => return Err(CommandErr::InvalidCriteria(raw))
+            }
+        }
+        Command::from_words(&words)
+    }
+
+    /// Parses a single command that has already been split into words.
+    fn from_words(words: &[String]) -> Result<Command, CommandErr> {
+        let name = match words.first() {
+            Some(name) => name.as_str(),
+            None => return Err(CommandErr::Empty)
+        };
+        let args = words[1..].join(" ");
+        let invalid = || CommandErr::InvalidArgument(name.into(), args.clone());
+        match name {
+            "focus" if args.is_empty() => Ok(Command::FocusMatched),
+            "focus" => parse_direction(&args).map(Command::Focus).ok_or_else(|| invalid()),
+            "move" => {
+                // Accepts both "move to workspace x" and "move container to workspace x"
+                let rest = if words.get(1).map(String::as_str) == Some("container") {
+                    &words[2..]
+                } else {
+                    &words[1..]
+                };
+                if rest.len() > 2 && rest[0] == "to" && rest[1] == "workspace" {
+                    return Ok(Command::MoveToWorkspace(rest[2..].join(" ")))
+                }
+                if rest.len() == 2 && rest[0] == "position" && rest[1] == "center" {
+                    return Ok(Command::MoveCenter)
+                }
+                if rest.first().map(String::as_str) == Some("position") {
+                    return parse_pair(&rest[1..])
+                        .map(|(x, y)| Command::MovePosition(x, y))
+                        .ok_or_else(|| invalid())
+                }
+                parse_direction(&args).map(Command::Move).ok_or_else(|| invalid())
+            },
+            "workspace" => {
+                if args.is_empty() {
+                    return Err(invalid())
+                }
+                Ok(Command::Workspace(args.clone()))
+            },
+            "layout" => {
+                match args.as_str() {
+                    "tabbed" => Ok(Command::Layout(Layout::Tabbed)),
+                    "stacked" | "stacking" => Ok(Command::Layout(Layout::Stacked)),
+                    "splith" => Ok(Command::Layout(Layout::Horizontal)),
+                    "splitv" => Ok(Command::Layout(Layout::Vertical)),
+                    _ => Err(invalid())
+                }
+            },
+            "split" => {
+                match args.as_str() {
+                    "v" | "vertical" => Ok(Command::Split(Layout::Vertical)),
+                    "h" | "horizontal" => Ok(Command::Split(Layout::Horizontal)),
+                    "auto" => Ok(Command::SplitAuto),
+                    _ => Err(invalid())
+                }
+            },
+            "fullscreen" => {
+                match args.as_str() {
+                    "" | "toggle" => Ok(Command::Fullscreen(None)),
+                    "enable" => Ok(Command::Fullscreen(Some(true))),
+                    "disable" => Ok(Command::Fullscreen(Some(false))),
+                    _ => Err(invalid())
+                }
+            },
+            "resize" => {
+                if words.get(1).map(String::as_str) != Some("set") {
+                    return Err(invalid())
+                }
+                parse_pair(&words[2..])
+                    .map(|(w, h)| Command::ResizeSet(w, h))
+                    .ok_or_else(|| invalid())
+            },
+            "kill" => {
+                if !args.is_empty() {
+                    return Err(invalid())
+                }
+                Ok(Command::Kill)
+            },
+            _ => Err(CommandErr::UnknownCommand(name.into()))
+        }
+    }
+}
+
+/// Parses two numbers, each optionally followed by "px", e.g "10 px 20".
+fn parse_pair<T: FromStr>(words: &[String]) -> Option<(T, T)> {
+    let mut numbers = words.iter().filter(|word| word.as_str() != "px");
+    let first = numbers.next()?.trim_right_matches("px").parse().ok()?;
+    let second = numbers.next()?.trim_right_matches("px").parse().ok()?;
+    if numbers.next().is_some() {
+        return None
+    }
+    Some((first, second))
+}
+
+fn parse_direction(arg: &str) -> Option<Direction> {
+    match arg {
+        "left" => Some(Direction::Left),
+        "right" => Some(Direction::Right),
+        "up" => Some(Direction::Up),
+        "down" => Some(Direction::Down),
+        _ => None
+    }
+}
+
+impl LayoutTree {
+    /// Parses and runs a command string against the tree.
+    ///
+    /// Commands can be chained with `;` and `,`. The whole string is parsed
+    /// before anything is run, so unknown or malformed commands return a
+    /// `TreeError::Command` without changing the tree. If a command fails
+    /// the rest of the chain is skipped, and the error is returned along with
+    /// the index of the failing statement.
+    ///
+    /// A statement can start with criteria (e.g `[class="firefox"]`), in which
+    /// case its commands are run on every matching view instead of the
+    /// active container.
+    pub fn execute_command(&mut self, cmd: &str) -> CommandResult {
+        let chain = parse_chain(cmd)?;
+        for (index, statement) in chain.into_iter().enumerate() {
+            self.run_statement(statement)
+                .map_err(|err| CommandErr::Failed(index, Box::new(err)))?;
+        }
+        Ok(())
+    }
+
+    fn run_statement(&mut self, statement: Statement) -> CommandResult {
+        match statement.criteria {
+            None => {
+                for command in statement.commands {
+                    self.run_command(command)?;
+                }
+            },
+            Some(criteria) => {
+                // Gather the matches up front, running the commands can
+                // change what would match.
+                for id in self.containers_matching(&criteria) {
+                    for command in statement.commands.iter().cloned() {
+                        self.run_command_on(command, id)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs an already parsed command against the tree.
+    pub fn run_command(&mut self, command: Command) -> CommandResult {
+        match command {
+            Command::Focus(direction) => self.move_focus(direction),
+            Command::Workspace(name) => {
+                self.switch_to_workspace(&name);
+                Ok(())
+            },
+            Command::Layout(layout) => self.set_active_layout(layout),
+            Command::SplitAuto => self.split_active(),
+            command => {
+                let id = self.get_active_container()
+                    .ok_or(TreeError::NoActiveContainer)?.get_id();
+                self.run_command_on(command, id)
+            }
+        }
+    }
+
+    /// Runs an already parsed command against the container behind the UUID,
+    /// instead of the active container.
+    ///
+    /// Commands that only make sense for the focused container (e.g `layout`)
+    /// focus on the container first.
+    pub fn run_command_on(&mut self, command: Command, id: Uuid) -> CommandResult {
+        match command {
+            Command::Move(direction) => self.move_container(id, direction),
+            Command::MoveToWorkspace(name) => {
+                self.lookup(id)?;
+                self.send_to_workspace(id, &name)
+            },
+            Command::MovePosition(x, y) => self.move_floating_to(id, Point { x: x, y: y }),
+            Command::MoveCenter => self.center_floating(id),
+            Command::Split(layout) => self.set_split_direction(id, layout),
+            Command::FocusMatched => self.focus_by_criteria(Criteria {
+                id: Some(id),
+                .. Criteria::default()
+            }),
+            Command::ResizeSet(w, h) => self.resize_floating_to(id, Size { w: w, h: h }),
+            Command::Fullscreen(toggle) => {
+                let fullscreen = self.lookup(id)?.fullscreen();
+                self.set_fullscreen(id, toggle.unwrap_or(!fullscreen))
+            },
+            Command::Kill => {
+                let node_ix = self.tree.lookup_id(id)
+                    .ok_or(TreeError::NodeNotFound(id))?;
+                let view: Option<WlcView> = match self.tree[node_ix] {
+                    Container::View { handle, .. } => Some(handle),
+                    Container::Container { .. } => None,
+                    _ => return Err(TreeError::UuidWrongType(id, vec!(
+                        ContainerType::View, ContainerType::Container)))
+                };
+                match view {
+                    // Views are removed from the tree in the view_destroyed callback
+                    Some(handle) => handle.close(),
+                    None => {
+                        let workspace_ix = self.tree
+                            .ancestor_of_type(node_ix, ContainerType::Workspace)?;
+                        self.remove_view_or_container(node_ix)?;
+                        self.close_dynamic_workspace(workspace_ix);
+                    }
+                }
+                Ok(())
+            },
+            command => {
+                self.set_active_container(id)?;
+                self.run_command(command)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::core::tree::tests::basic_tree;
+    use super::super::super::core::container::ContainerType;
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(Command::parse("focus left"), Ok(Command::Focus(Direction::Left)));
+        assert_eq!(Command::parse("  move   down "), Ok(Command::Move(Direction::Down)));
+        assert_eq!(Command::parse("workspace my ws"),
+                   Ok(Command::Workspace("my ws".into())));
+        assert_eq!(Command::parse("layout stacking"), Ok(Command::Layout(Layout::Stacked)));
+        assert_eq!(Command::parse("split v"), Ok(Command::Split(Layout::Vertical)));
+        assert_eq!(Command::parse("split auto"), Ok(Command::SplitAuto));
+        assert_eq!(Command::parse("fullscreen toggle"), Ok(Command::Fullscreen(None)));
+        assert_eq!(Command::parse("kill"), Ok(Command::Kill));
+        assert_eq!(Command::parse("move position 10 px 20 px"),
+                   Ok(Command::MovePosition(10, 20)));
+        assert_eq!(Command::parse("move container position -5 7px"),
+                   Ok(Command::MovePosition(-5, 7)));
+        assert_eq!(Command::parse("move position center"), Ok(Command::MoveCenter));
+        assert_eq!(Command::parse("resize set 640 480"), Ok(Command::ResizeSet(640, 480)));
+        assert!(Command::parse("resize set 640").is_err());
+        assert!(Command::parse("resize grow 10").is_err());
+        assert_eq!(Command::parse(""), Err(CommandErr::Empty));
+        assert_eq!(Command::parse("frobnicate now"),
+                   Err(CommandErr::UnknownCommand("frobnicate".into())));
+        assert_eq!(Command::parse("focus sideways"),
+                   Err(CommandErr::InvalidArgument("focus".into(), "sideways".into())));
+    }
+
+    #[test]
+    fn execute_commands() {
+        let mut tree = basic_tree();
+        tree.add_view(WlcView::root()).unwrap();
+        let active_ix = tree.active_container.unwrap();
+        let parent_ix = tree.tree.parent_of(active_ix).unwrap();
+        assert_eq!(tree.tree.children_of(parent_ix)[1], active_ix);
+        tree.execute_command("move left").unwrap();
+        assert_eq!(tree.tree.children_of(parent_ix)[0], active_ix);
+
+        tree.execute_command("layout tabbed").unwrap();
+        assert_eq!(tree.tree[parent_ix].get_layout(), Ok(Layout::Tabbed));
+
+        tree.execute_command("fullscreen toggle").unwrap();
+        assert!(tree.get_active_container().unwrap().fullscreen());
+        tree.execute_command("fullscreen toggle").unwrap();
+        assert!(!tree.get_active_container().unwrap().fullscreen());
+
+        tree.execute_command("workspace 2").unwrap();
+        assert_eq!(tree.current_workspace(), Ok("2"));
+        let active_ix = tree.active_container.unwrap();
+        assert_eq!(tree.tree[active_ix].get_type(), ContainerType::View);
+
+        assert_eq!(tree.execute_command("frobnicate"),
+                   Err(TreeError::Command(CommandErr::UnknownCommand("frobnicate".into()))));
+        assert_eq!(tree.execute_command("split diagonal"),
+                   Err(TreeError::Command(CommandErr::InvalidArgument(
+                       "split".into(), "diagonal".into()))));
+    }
+
+    #[test]
+    fn parse_chained_commands() {
+        let statement = |commands| Statement { criteria: None, commands: commands };
+        assert_eq!(parse_chain("split v; layout tabbed"),
+                   Ok(vec![statement(vec![Command::Split(Layout::Vertical)]),
+                           statement(vec![Command::Layout(Layout::Tabbed)])]));
+        assert_eq!(parse_chain("focus left,move right ;"),
+                   Ok(vec![statement(vec![Command::Focus(Direction::Left),
+                                          Command::Move(Direction::Right)])]));
+        assert_eq!(parse_chain("workspace \"my; ws\";kill"),
+                   Ok(vec![statement(vec![Command::Workspace("my; ws".into())]),
+                           statement(vec![Command::Kill])]));
+        assert_eq!(parse_chain("workspace 'it\\'s'"),
+                   Ok(vec![statement(vec![Command::Workspace("it's".into())])]));
+        assert_eq!(parse_chain(" ; "), Err(CommandErr::Empty));
+        assert_eq!(parse_chain("workspace \"3"),
+                   Err(CommandErr::UnterminatedQuote("workspace \"3".into())));
+        assert_eq!(Command::parse("kill; kill"), Err(CommandErr::UnexpectedSeparator(';')));
+    }
+
+    #[test]
+    fn execute_chained_commands() {
+        let mut tree = basic_tree();
+        tree.add_view(WlcView::root()).unwrap();
+        let active_ix = tree.active_container.unwrap();
+        let root_ix = tree.tree.parent_of(active_ix).unwrap();
+        let active_id = tree.tree[active_ix].get_id();
+        tree.execute_command("split v; layout tabbed").unwrap();
+        // The split is only used by the next view added
+        assert_eq!(tree.pending_split, Some((active_id, Layout::Vertical)));
+        assert_eq!(tree.tree.parent_of(active_ix).unwrap(), root_ix);
+        assert_eq!(tree.tree[root_ix].get_layout(), Ok(Layout::Tabbed));
+    }
+
+    #[test]
+    fn chain_stops_on_error() {
+        let mut tree = basic_tree();
+        let active_ix = tree.active_container.unwrap();
+        let parent_ix = tree.tree.parent_of(active_ix).unwrap();
+        match tree.execute_command("layout stacked; move up; layout tabbed") {
+            Err(TreeError::Command(CommandErr::Failed(1, _))) => {},
+            result => panic!("Expected the second command to fail, got {:?}", result)
+        }
+        assert_eq!(tree.tree[parent_ix].get_layout(), Ok(Layout::Stacked));
+    }
+
+    #[test]
+    fn parse_criteria_commands() {
+        let criteria = Criteria {
+            app_id: Some("fire fox".into()),
+            floating: Some(false),
+            .. Criteria::default()
+        };
+        assert_eq!(parse_chain("[app_id=\"fire fox\" floating=false] move to workspace 3, kill"),
+                   Ok(vec![Statement {
+                       criteria: Some(criteria),
+                       commands: vec![Command::MoveToWorkspace("3".into()), Command::Kill]
+                   }]));
+        assert_eq!(Command::parse("move container to workspace \"a b\""),
+                   Ok(Command::MoveToWorkspace("a b".into())));
+        assert_eq!(parse_chain("[colour=red] kill"),
+                   Err(CommandErr::InvalidCriteria("colour=red".into())));
+        assert_eq!(parse_chain("kill [class=x]"),
+                   Err(CommandErr::InvalidCriteria("class=x".into())));
+        assert_eq!(parse_chain("[class=x]"), Err(CommandErr::Empty));
+        assert_eq!(parse_chain("[con_mark=a title_contains=\"b c\"] focus"),
+                   Ok(vec![Statement {
+                       criteria: Some(Criteria {
+                           title_contains: Some("b c".into()),
+                           mark: Some("a".into()),
+                           .. Criteria::default()
+                       }),
+                       commands: vec![Command::FocusMatched]
+                   }]));
+        assert_eq!(parse_chain("[con_id=x] focus"),
+                   Err(CommandErr::InvalidCriteria("con_id=x".into())));
+    }
+
+    #[test]
+    fn criteria_apply_to_matching_views() {
+        let mut tree = basic_tree();
+        // Float the view in workspace 1, so only the two
+        // tiled views in workspace 2 match.
+        let ws_1_id = tree.get_active_container().unwrap().get_id();
+        tree.float_container(ws_1_id).unwrap();
+        let class = WlcView::root().get_class();
+        let criteria = Criteria {
+            class: Some(class.clone()),
+            floating: Some(false),
+            .. Criteria::default()
+        };
+        let matches = tree.containers_matching(&criteria);
+        assert_eq!(matches.len(), 2);
+        assert!(!matches.contains(&ws_1_id));
+        let cmd = format!("[class=\"{}\" floating=false] move to workspace 3", class);
+        tree.execute_command(&cmd).unwrap();
+        let moved = tree.views_in_workspace("3").unwrap();
+        assert_eq!(moved.len(), 2);
+        for id in matches {
+            assert!(moved.contains(&id));
+        }
+        assert!(!tree.views_in_workspace("1").unwrap().is_empty());
+    }
+}
@@ -1,12 +1,36 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+
 use super::super::commands::CommandResult;
 use super::super::{LayoutTree, TreeError};
 use super::super::core::Direction;
 use super::super::core::container::{Container, ContainerType, Layout};
 
 use petgraph::graph::NodeIndex;
-use rustwlc::WlcView;
+use rustwlc::{Geometry, WlcOutput, WlcView};
 use uuid::Uuid;
 
+/// How many containers are remembered for `focus_back`.
+const FOCUS_HISTORY_SIZE: usize = 32;
+
+/// What `move_focus` does when it's at the edge of a container.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FocusWrapping {
+    /// Move to the sibling of the parent, never wrapping around.
+    No,
+    /// Wrap around, but only when there's nothing left to move out to.
+    Yes,
+    /// Always wrap around within the container.
+    Force
+}
+
+impl Default for FocusWrapping {
+    fn default() -> Self {
+        FocusWrapping::No
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum FocusError {
     /// Reached a container where we can keep climbing the tree no longer.
@@ -18,10 +42,181 @@ pub enum FocusError {
     NotAView(Uuid),
     /// Tried to focus on a container (first one),
     /// but that container was superseded by a fullscreen container (second one)
-    BlockedByFullscreen(Uuid, Uuid)
+    BlockedByFullscreen(Uuid, Uuid),
+    /// Tried to focus on a view that never takes the focus.
+    NotFocusable(Uuid)
+}
+
+impl fmt::Display for FocusError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FocusError::ReachedLimit(node_ix) =>
+                write!(f, "can not move the focus past node {}", node_ix.index()),
+            FocusError::NotAView(id) =>
+                write!(f, "container {} is not a view", id),
+            FocusError::BlockedByFullscreen(id, fullscreen_id) =>
+                write!(f, "can not focus on {}, fullscreen container {} is in the way",
+                       id, fullscreen_id),
+            FocusError::NotFocusable(id) =>
+                write!(f, "view {} never takes the focus", id)
+        }
+    }
+}
+
+impl Error for FocusError {}
+
+/// Information about a focused view, for external consumers
+/// like window title bar widgets.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClientInfo {
+    /// The id of the view in the tree
+    pub id: Uuid,
+    pub app_id: String,
+    pub title: String,
+    pub geometry: Geometry,
+    /// The name of the workspace the view is in
+    pub workspace: String
+}
+
+/// Where the active container is in the tree, gathered in one go so it
+/// can't change between lookups.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActiveContext {
+    /// The id of the active container
+    pub id: Uuid,
+    /// The name of the workspace it is in
+    pub workspace: String,
+    /// The output it is shown on
+    pub output: WlcOutput,
+    pub floating: bool,
+    pub fullscreen: bool,
+    /// Whether its parent is a tabbed container
+    pub tabbed: bool
+}
+
+/// A view in the tree, as listed by `all_views`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ViewInfo {
+    /// The id of the view in the tree
+    pub id: Uuid,
+    pub app_id: String,
+    pub title: String,
+    /// The name of the workspace the view is in
+    pub workspace: String,
+    /// The output the view is on, `None` if it is in the scratchpad
+    pub output: Option<WlcOutput>,
+    pub floating: bool,
+    pub focused: bool,
+    /// Whether the view has been put away in the scratchpad
+    pub scratchpad: bool,
+    pub geometry: Geometry
 }
 
 impl LayoutTree {
+    /// Gets information about the focused view.
+    ///
+    /// If there is no active container, or if it is not a view,
+    /// `None` is returned.
+    pub fn focused_client_info(&self) -> Option<ClientInfo> {
+        let active_ix = self.active_container?;
+        let handle = match self.tree[active_ix] {
+            Container::View { handle, .. } => handle,
+            _ => return None
+        };
+        let geometry = self.tree[active_ix].get_geometry()?;
+        let workspace_ix = self.tree
+            .ancestor_of_type(active_ix, ContainerType::Workspace).ok()?;
+        let workspace = self.tree[workspace_ix].get_name()?.to_string();
+        Some(ClientInfo {
+            id: self.tree[active_ix].get_id(),
+            app_id: handle.get_app_id(),
+            title: handle.get_title(),
+            geometry: geometry,
+            workspace: workspace
+        })
+    }
+
+    /// Gets the active container along with its workspace, output and how
+    /// it is shown.
+    ///
+    /// If there is no active container, `None` is returned.
+    pub fn active_container_context(&self) -> Option<ActiveContext> {
+        let active_ix = self.active_container?;
+        let container = self.tree.get(active_ix)?;
+        let workspace_ix = self.tree
+            .ancestor_of_type(active_ix, ContainerType::Workspace).ok()?;
+        let output_ix = self.tree.parent_of(workspace_ix).ok()?;
+        let output = match self.tree[output_ix] {
+            Container::Output { handle, .. } => handle,
+            _ => return None
+        };
+        let tabbed = self.tree.parent_of(active_ix).ok()
+            .map(|parent_ix| self.tree[parent_ix].get_layout() == Ok(Layout::Tabbed))
+            .unwrap_or(false);
+        Some(ActiveContext {
+            id: container.get_id(),
+            workspace: self.tree[workspace_ix].get_name()?.to_string(),
+            output: output,
+            floating: container.floating(),
+            fullscreen: container.fullscreen(),
+            tabbed: tabbed
+        })
+    }
+
+    /// Lists every view in the tree, for bars and window switchers.
+    ///
+    /// They are ordered by output, then by workspace, then by where they
+    /// are in the workspace. Views in the scratchpad come last.
+    pub fn all_views(&self) -> Vec<ViewInfo> {
+        fn views_beneath(tree: &LayoutTree, node_ix: NodeIndex, views: &mut Vec<NodeIndex>) {
+            for child_ix in tree.tree.children_of(node_ix) {
+                match tree.tree[child_ix].get_type() {
+                    ContainerType::View => views.push(child_ix),
+                    _ => views_beneath(tree, child_ix, views)
+                }
+            }
+        }
+        let mut workspaces = Vec::new();
+        for output_ix in self.tree.children_of(self.tree.root_ix()) {
+            let output = match self.tree[output_ix] {
+                Container::Output { handle, .. } => handle,
+                _ => continue
+            };
+            for workspace_ix in self.tree.children_of(output_ix) {
+                workspaces.push((Some(output), workspace_ix));
+            }
+        }
+        if let Some(scratchpad_ix) = self.scratchpad {
+            workspaces.push((None, scratchpad_ix));
+        }
+        let mut views = Vec::new();
+        for (output, workspace_ix) in workspaces {
+            let mut view_ixs = Vec::new();
+            views_beneath(self, workspace_ix, &mut view_ixs);
+            for view_ix in view_ixs {
+                let container = &self.tree[view_ix];
+                let (handle, effective_geometry) = match *container {
+                    Container::View { handle, effective_geometry, .. } =>
+                        (handle, effective_geometry),
+                    _ => unreachable!()
+                };
+                views.push(ViewInfo {
+                    id: container.get_id(),
+                    app_id: container.app_id().unwrap_or("").into(),
+                    title: handle.get_title(),
+                    workspace: self.tree[workspace_ix].name(),
+                    output: output,
+                    floating: container.floating(),
+                    focused: self.active_container == Some(view_ix),
+                    scratchpad: self.is_scratchpad(workspace_ix),
+                    // Same as `geometry_of`, a client might not report one
+                    geometry: handle.get_geometry().unwrap_or(effective_geometry)
+                });
+            }
+        }
+        views
+    }
+
     /// Focuses on the container by the uuid, if it points to a View.
     /// Otherwise, an error is returned.
     pub fn focus_on(&mut self, uuid: Uuid) -> CommandResult {
@@ -43,16 +238,8 @@ impl LayoutTree {
         self.tree.set_ancestor_paths_active(node_ix);
         let workspace_ix = self.tree.ancestor_of_type(node_ix,
                                                       ContainerType::Workspace)?;
-        for node_ix in self.tree.all_descendants_of(workspace_ix) {
-            match self.tree[node_ix] {
-                Container::View { floating, handle, .. } => {
-                    if floating {
-                        handle.bring_to_front();
-                    }
-                },
-                _ => {}
-            }
-        }
+        let stack = self.floating_stack(workspace_ix);
+        self.bring_stack_to_front(&stack);
         if let Container::View { handle, floating, ..} = self.tree[node_ix] {
             if floating {
                 handle.bring_to_front();
@@ -67,7 +254,8 @@ impl LayoutTree {
     /// If Vertical, up and down will move within siblings.
     /// Other wise, it moves to the next sibling of the parent container.
     ///
-    /// If the edge of the children is hit, it does not wrap around,
+    /// If the edge of the children is hit, what happens depends on the
+    /// `FocusWrapping` of the tree. By default it does not wrap around,
     /// but moves between ancestor siblings.
     pub fn move_focus(&mut self, direction: Direction) -> CommandResult {
         if let Some(prev_active_ix) = self.active_container {
@@ -76,8 +264,15 @@ impl LayoutTree {
                 return Err(TreeError::Focus(
                     FocusError::BlockedByFullscreen(active_id, fullscreen_id)))
             }
-            let new_active_ix = self.move_focus_recurse(prev_active_ix, direction)
+            let mut new_active_ix = self.move_focus_recurse(prev_active_ix, direction, None)
                 .unwrap_or(prev_active_ix);
+            // Keep going past views that can't be focused
+            let mut passed = vec![prev_active_ix];
+            while !self.tree[new_active_ix].focusable() && !passed.contains(&new_active_ix) {
+                passed.push(new_active_ix);
+                new_active_ix = self.move_focus_recurse(new_active_ix, direction, None)
+                    .unwrap_or(prev_active_ix);
+            }
             try!(self.set_active_node(new_active_ix));
             match self.tree[self.active_container.unwrap()] {
                 Container::View { ref handle, .. } => handle.focus(),
@@ -90,13 +285,41 @@ impl LayoutTree {
         Ok(())
     }
 
-    fn move_focus_recurse(&mut self, node_ix: NodeIndex, direction: Direction)
+    /// Sets the active container to the neighbour of the active container
+    /// in the given direction.
+    ///
+    /// Unlike `move_focus`, if the neighbour is a container then it becomes
+    /// the active container itself, instead of a view inside of it.
+    pub fn focus_container_in_dir(&mut self, direction: Direction) -> CommandResult {
+        let active_ix = self.active_container.ok_or(TreeError::NoActiveContainer)?;
+        let active_id = self.tree[active_ix].get_id();
+        if let Some(fullscreen_id) = self.in_fullscreen_workspace(active_id)? {
+            return Err(TreeError::Focus(
+                FocusError::BlockedByFullscreen(active_id, fullscreen_id)))
+        }
+        let (_, neighbour_id) = self.container_in_dir(active_id, direction)?;
+        let neighbour_ix = self.tree.lookup_id(neighbour_id)
+            .ok_or(TreeError::NodeNotFound(neighbour_id))?;
+        self.set_active_node(neighbour_ix)?;
+        self.tree.set_ancestor_paths_active(neighbour_ix);
+        self.validate();
+        Ok(())
+    }
+
+    /// Finds the view to focus on when moving from the node in the direction.
+    ///
+    /// `outermost` is the farthest node up the tree that was at the edge of
+    /// a container laid out along the direction, which is where the focus
+    /// wraps around with `FocusWrapping::Yes`.
+    fn move_focus_recurse(&mut self, node_ix: NodeIndex, direction: Direction,
+                          outermost: Option<NodeIndex>)
                           -> Result<NodeIndex, TreeError> {
         match self.tree[node_ix].get_type() {
             ContainerType::View | ContainerType::Container => { /* continue */ },
             _ => return Err(TreeError::UuidWrongType(self.tree[node_ix].get_id(),
                                                      vec!(ContainerType::View, ContainerType::Container)))
         }
+        let mut outermost = outermost;
         let parent_ix = self.tree.parent_of(node_ix)
             .expect("Active ix had no parent");
         match self.tree[parent_ix] {
@@ -126,42 +349,118 @@ impl LayoutTree {
                             maybe_new_index.unwrap() < siblings.len() {
                                 // There is a sibling to move to.
                                 let new_index = maybe_new_index.unwrap();
-                                let new_active_ix = siblings[new_index];
-                                match self.tree[new_active_ix].get_type() {
-                                    ContainerType::Container => {
-                                        let path_ix = self.tree.follow_path(new_active_ix);
-                                        // If the path wasn't complete, find the first view and focus on that
-                                        let node_ix = try!(self.tree.descendant_of_type(path_ix, ContainerType::View)
-                                                           .map_err(|err| TreeError::PetGraph(err)));
-                                        let parent_ix = try!(self.tree.parent_of(node_ix)
-                                                             .map_err(|err| TreeError::PetGraph(err)));
-                                        match self.tree[node_ix].get_type() {
-                                            ContainerType::View | ContainerType::Container => {},
-                                            _ => panic!("Following path did not lead to a container or a view!")
-                                        }
-                                        trace!("Moving to different view {:?} in container {:?}",
-                                                self.tree[node_ix], self.tree[parent_ix]);
-                                        return Ok(node_ix);
-                                    },
-                                    ContainerType::View => {
-                                        trace!("Moving to other view {:?}", self.tree[new_active_ix]);
-                                        return Ok(new_active_ix)
-                                    },
-                                    _ => unreachable!()
-                                };
+                                return self.focus_target(siblings[new_index]);
                             }
+                        if self.focus_wrapping == FocusWrapping::Force &&
+                            siblings.len() > 1 {
+                                return self.focus_target(
+                                    Self::wrapped_sibling(&siblings, direction));
+                            }
+                        outermost = Some(node_ix);
                     },
                     _ => { /* We are moving out of siblings, recurse */ }
                 }
             }
             Container::Workspace { .. } => {
+                if self.focus_wrapping == FocusWrapping::Yes {
+                    if let Some(edge_ix) = outermost {
+                        let wrap_parent_ix = self.tree.parent_of(edge_ix)
+                            .expect("Node had no parent");
+                        let siblings = self.tree.children_of(wrap_parent_ix);
+                        let wrapped_ix = Self::wrapped_sibling(&siblings, direction);
+                        if wrapped_ix != edge_ix {
+                            return self.focus_target(wrapped_ix);
+                        }
+                    }
+                }
                 return Err(TreeError::Focus(FocusError::ReachedLimit(parent_ix)));
             }
             _ => unreachable!()
         }
         let parent_ix = self.tree.parent_of(node_ix)
             .expect("Node had no parent");
-        return self.move_focus_recurse(parent_ix, direction);
+        return self.move_focus_recurse(parent_ix, direction, outermost);
+    }
+
+    /// Gets the sibling on the other end of the siblings from the direction.
+    fn wrapped_sibling(siblings: &[NodeIndex], direction: Direction) -> NodeIndex {
+        match direction {
+            Direction::Right | Direction::Down => siblings[0],
+            Direction::Left | Direction::Up => siblings[siblings.len() - 1]
+        }
+    }
+
+    /// Gets the view to focus on when moving the focus to the node.
+    ///
+    /// If it's a container, that's the view it last had focused,
+    /// or its first view if it never had one.
+    fn focus_target(&self, new_active_ix: NodeIndex) -> Result<NodeIndex, TreeError> {
+        match self.tree[new_active_ix].get_type() {
+            ContainerType::Container => {
+                let path_ix = self.tree.follow_path(new_active_ix);
+                // If the path wasn't complete, find the first view and focus on that
+                let node_ix = try!(self.tree.descendant_of_type(path_ix, ContainerType::View)
+                                   .map_err(|err| TreeError::PetGraph(err)));
+                let parent_ix = try!(self.tree.parent_of(node_ix)
+                                     .map_err(|err| TreeError::PetGraph(err)));
+                match self.tree[node_ix].get_type() {
+                    ContainerType::View | ContainerType::Container => {},
+                    _ => panic!("Following path did not lead to a container or a view!")
+                }
+                trace!("Moving to different view {:?} in container {:?}",
+                        self.tree[node_ix], self.tree[parent_ix]);
+                Ok(node_ix)
+            },
+            ContainerType::View => {
+                trace!("Moving to other view {:?}", self.tree[new_active_ix]);
+                Ok(new_active_ix)
+            },
+            _ => unreachable!()
+        }
+    }
+
+    /// Sets whether the view behind the UUID can take the keyboard focus.
+    ///
+    /// Views that can't are skipped when moving or cycling the focus.
+    /// If the view is focused it keeps the focus until it moves elsewhere.
+    pub fn set_focusable(&mut self, id: Uuid, focusable: bool) -> CommandResult {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        self.tree[node_ix].set_focusable(focusable)
+            .map_err(|_| TreeError::UuidWrongType(id, vec!(ContainerType::View)))
+    }
+
+    /// Sets what `move_focus` does at the edge of a container.
+    pub fn set_focus_wrapping(&mut self, wrapping: FocusWrapping) {
+        self.focus_wrapping = wrapping;
+    }
+
+    /// Updates the active container after the active one, which was at the
+    /// index among the tiled children of the parent node, was removed.
+    ///
+    /// The sibling before it in reading order is focused on, or the one after
+    /// it if it was the first, so focus stays close to where it was.
+    /// If the parent has no tiled children left, or there is a fullscreen
+    /// container in the workspace, this falls back to `focus_on_next_container`.
+    pub fn focus_on_nearest_child(&mut self, parent_ix: NodeIndex, index: usize) {
+        let parent_id = self.tree[parent_ix].get_id();
+        if let Ok(Some(_)) = self.in_fullscreen_workspace(parent_id) {
+            return self.focus_on_next_container(parent_ix)
+        }
+        let children = self.tree.grounded_children(parent_ix);
+        if children.is_empty() {
+            return self.focus_on_next_container(parent_ix)
+        }
+        let sibling_ix = children[index.saturating_sub(1).min(children.len() - 1)];
+        match self.tree.lowest_active_view(sibling_ix) {
+            Some(view_ix) => {
+                trace!("Active container set to nearest view at {:?}", view_ix);
+                let id = self.tree[view_ix].get_id();
+                self.set_active_container(id)
+                    .expect("Could not set active container");
+            },
+            None => self.focus_on_next_container(parent_ix)
+        }
     }
 
     /// Updates the current active container to be the next container or view
@@ -264,6 +563,7 @@ impl LayoutTree {
                 if let Ok(view_ix) = self.tree.descendant_of_type(child_ix,
                                                                     ContainerType::View) {
                     match self.tree[view_ix] {
+                        Container::View { focusable: false, .. } => continue,
                         Container::View { handle, id, .. } => {
                             info!("Floating view found, focusing on {:#?}", handle);
                             handle.focus();
@@ -289,6 +589,179 @@ impl LayoutTree {
         });
     }
 
+    /// Focuses on the next view in the active workspace, wrapping around
+    /// to the first one after the last. See `views_in_focus_cycle` for the order.
+    ///
+    /// Does nothing if there are no views in the workspace.
+    pub fn focus_next(&mut self) -> CommandResult {
+        self.cycle_focus(true)
+    }
+
+    /// Focuses on the previous view in the active workspace, wrapping around
+    /// to the last one before the first.
+    ///
+    /// Does nothing if there are no views in the workspace.
+    pub fn focus_prev(&mut self) -> CommandResult {
+        self.cycle_focus(false)
+    }
+
+    /// Moves the focus up to the parent of the active container,
+    /// without changing which view has keyboard focus.
+    ///
+    /// The root container of a workspace has no parent that can be focused.
+    pub fn focus_parent(&mut self) -> CommandResult {
+        let active_ix = self.active_container
+            .ok_or(TreeError::NoActiveContainer)?;
+        if self.tree.is_root_container(active_ix) {
+            let id = self.tree[active_ix].get_id();
+            return Err(TreeError::InvalidOperationOnRootContainer(id))
+        }
+        let parent_ix = self.tree.parent_of(active_ix)?;
+        self.set_active_node(parent_ix)
+    }
+
+    /// Moves the focus from the active container back down the active path,
+    /// to the view that was last focused in it.
+    ///
+    /// Does nothing if the active container is already a view.
+    pub fn focus_child(&mut self) -> CommandResult {
+        let active_ix = self.active_container
+            .ok_or(TreeError::NoActiveContainer)?;
+        let child_ix = self.tree.follow_path(active_ix);
+        if child_ix == active_ix {
+            return Ok(())
+        }
+        self.set_active_node(child_ix)
+    }
+
+    /// Focuses on the container that was focused before the active one,
+    /// going further back each time it's called.
+    ///
+    /// Containers that have been removed since, or put in the scratchpad,
+    /// are skipped. Does nothing if there is nothing to go back to.
+    pub fn focus_back(&mut self) -> CommandResult {
+        let active_id = self.active_container.map(|active_ix| self.tree[active_ix].get_id());
+        if active_id.is_some() && self.focus_history.back().cloned() == active_id {
+            self.focus_history.pop_back();
+        }
+        while let Some(id) = self.focus_history.pop_back() {
+            let node_ix = match self.tree.lookup_id(id) {
+                Some(node_ix) => node_ix,
+                None => continue
+            };
+            if Some(node_ix) == self.active_container {
+                continue
+            }
+            let in_scratchpad = self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)
+                .map(|workspace_ix| self.is_scratchpad(workspace_ix))
+                .unwrap_or(true);
+            if in_scratchpad {
+                continue
+            }
+            return self.set_active_node(node_ix)
+        }
+        Ok(())
+    }
+
+    /// Remembers that the container was just focused, for `focus_back`.
+    pub fn push_focus_history(&mut self, id: Uuid) {
+        if self.focus_history.back() == Some(&id) {
+            return
+        }
+        self.focus_history.push_back(id);
+        if self.focus_history.len() > FOCUS_HISTORY_SIZE {
+            self.focus_history.pop_front();
+        }
+    }
+
+    /// Gets every view in the tree, the most recently focused first.
+    ///
+    /// The view at the end of the active path always comes first. Views that
+    /// aren't in the focus history follow the ones that are, in tree order.
+    pub fn views_in_focus_order(&self) -> Vec<Uuid> {
+        let root_ix = self.tree.root_ix();
+        let mut seen = HashSet::new();
+        let mut result = Vec::new();
+        let active_ix = self.tree.follow_path(root_ix);
+        let history = self.focus_history.iter().rev()
+            .filter_map(|&id| self.tree.lookup_id(id));
+        let rest = self.tree.all_descendants_of(root_ix);
+        for node_ix in Some(active_ix).into_iter().chain(history).chain(rest) {
+            if self.tree[node_ix].get_type() != ContainerType::View {
+                continue
+            }
+            let id = self.tree[node_ix].get_id();
+            if seen.insert(id) {
+                result.push(id);
+            }
+        }
+        result
+    }
+
+    /// Gets the ids of the containers on the active path,
+    /// from the root down to the end of the path.
+    ///
+    /// This never panics, if the tree is in a bad state the path found
+    /// until then is returned.
+    pub fn active_path_uuids(&self) -> Vec<Uuid> {
+        let mut result = Vec::new();
+        let mut next_ix = Some(self.tree.root_ix());
+        while let Some(cur_ix) = next_ix {
+            let id = match self.tree.get(cur_ix) {
+                Some(container) => container.get_id(),
+                None => break
+            };
+            if result.contains(&id) {
+                warn!("Active path loops back to {}", id);
+                break
+            }
+            result.push(id);
+            next_ix = self.tree.next_active_node(cur_ix);
+        }
+        result
+    }
+
+    fn cycle_focus(&mut self, forward: bool) -> CommandResult {
+        let root_c_ix = match self.root_container_ix() {
+            Some(root_c_ix) => root_c_ix,
+            None => return Ok(())
+        };
+        let views = self.views_in_focus_cycle(root_c_ix);
+        if views.is_empty() {
+            return Ok(())
+        }
+        let position = self.active_container
+            .and_then(|active_ix| views.iter().position(|&ix| ix == active_ix));
+        let next = match (position, forward) {
+            (Some(index), true) => (index + 1) % views.len(),
+            (Some(index), false) => (index + views.len() - 1) % views.len(),
+            (None, true) => 0,
+            (None, false) => views.len() - 1
+        };
+        self.set_active_node(views[next])
+    }
+
+    /// Gets the views under the root container in the order they are cycled
+    /// through: the tiled views depth first by their position, then
+    /// the floating views in the order they were floated.
+    fn views_in_focus_cycle(&self, root_c_ix: NodeIndex) -> Vec<NodeIndex> {
+        fn tiled_views(tree: &LayoutTree, node_ix: NodeIndex, views: &mut Vec<NodeIndex>) {
+            match tree.tree[node_ix].get_type() {
+                ContainerType::View => views.push(node_ix),
+                _ => for child_ix in tree.tree.grounded_children(node_ix) {
+                    tiled_views(tree, child_ix, views)
+                }
+            }
+        }
+        let mut views = Vec::new();
+        tiled_views(self, root_c_ix, &mut views);
+        for floating_ix in self.tree.floating_children(root_c_ix) {
+            tiled_views(self, floating_ix, &mut views);
+        }
+        views.retain(|&view_ix| self.tree[view_ix].focusable());
+        views
+    }
+
     /// If the currently focused view is floating, then the non-floating at the end of
     /// the path becomes the focused view. Otherwise, the first floating view becomes
     /// the focused view.
@@ -359,10 +832,15 @@ impl LayoutTree {
 #[cfg(test)]
 mod tests {
     use super::super::super::core::tree::tests::basic_tree;
+    use super::{FocusError, FocusWrapping, FOCUS_HISTORY_SIZE};
+    use super::super::super::LayoutTree;
+    use petgraph::graph::NodeIndex;
+    use super::super::super::core::{Direction, TreeError};
+    use super::super::super::core::container::{ContainerType, Handle, Layout};
     use rustwlc::*;
 
-    /// Tests the new algorithm, the one that i3 uses, to determine which
-    /// sibling to focus on when the active one is closed.
+    /// Tests which sibling is focused on when the active one is closed,
+    /// which is the nearest one, not the one focused on last.
     #[test]
     fn test_sibling_focus_algorithm() {
         let mut tree = basic_tree();
@@ -376,10 +854,9 @@ mod tests {
 
         tree.focus_on(view_3).unwrap();
         tree.focus_on(view_1).unwrap();
-        // should focus 1 -> 3 -> 5 -> 4 -> 2
-        // because this the "stack" of viewing them.
-        // NOTE that adding a view implicitly focuses and adds it to the "stack"
-        let views = vec![view_1, view_3, view_5, view_4, view_2];
+        // should focus 1 -> 2 -> 3 -> 4 -> 5
+        // because each one is the first, so the one after it is the nearest.
+        let views = vec![view_1, view_2, view_3, view_4, view_5];
         for view in views {
             let active_ix = tree.tree.lookup_id(view);
             assert_eq!(tree.active_container, active_ix);
@@ -387,6 +864,232 @@ mod tests {
         }
     }
 
+    #[test]
+    fn removing_focuses_nearest_sibling() {
+        let mut tree = basic_tree();
+        let fake_view = WlcView::root();
+        tree.switch_to_workspace("some_unique_workspace");
+        let view_1 = tree.add_view(fake_view).unwrap().get_id();
+        let view_2 = tree.add_view(fake_view).unwrap().get_id();
+        let view_3 = tree.add_view(fake_view).unwrap().get_id();
+        let root_c_ix = tree.tree.parent_of(tree.active_container.unwrap()).unwrap();
+        assert_eq!(tree.tree[root_c_ix].get_layout(), Ok(Layout::Horizontal));
+        // The last one focused was the third, but the first is next to it
+        tree.focus_on(view_2).unwrap();
+        tree.remove_active().unwrap();
+        assert_eq!(tree.get_active_container().unwrap().get_id(), view_1);
+        // The first has nothing before it, so the one after it is used
+        tree.remove_active().unwrap();
+        assert_eq!(tree.get_active_container().unwrap().get_id(), view_3);
+        // When the workspace is empty the root container is focused
+        tree.remove_active().unwrap();
+        assert_eq!(tree.active_container, Some(root_c_ix));
+    }
+
+    #[test]
+    fn focus_next_and_prev_wrap() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("some_unique_workspace");
+        // Nothing to cycle through
+        let root_c = tree.active_container;
+        tree.focus_next().unwrap();
+        tree.focus_prev().unwrap();
+        assert_eq!(tree.active_container, root_c);
+
+        let fake_view = WlcView::root();
+        let view_1 = tree.add_view(fake_view).unwrap().get_id();
+        let view_2 = tree.add_view(fake_view).unwrap().get_id();
+        let view_3 = tree.add_view(fake_view).unwrap().get_id();
+        tree.float_container(view_2).unwrap();
+        tree.focus_on(view_1).unwrap();
+        // Floating views come after the tiled ones
+        for &view in &[view_3, view_2, view_1] {
+            tree.focus_next().unwrap();
+            assert_eq!(tree.get_active_container().unwrap().get_id(), view);
+        }
+        for &view in &[view_2, view_3, view_1] {
+            tree.focus_prev().unwrap();
+            assert_eq!(tree.get_active_container().unwrap().get_id(), view);
+        }
+    }
+
+    #[test]
+    fn focus_parent_and_child() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let view_ix = tree.active_container.unwrap();
+        let container_ix = tree.tree.parent_of(view_ix).unwrap();
+        let root_c_ix = tree.tree.parent_of(container_ix).unwrap();
+        tree.focus_parent().unwrap();
+        assert_eq!(tree.active_container, Some(container_ix));
+        tree.focus_parent().unwrap();
+        assert_eq!(tree.active_container, Some(root_c_ix));
+        let root_c_id = tree.tree[root_c_ix].get_id();
+        assert_eq!(tree.focus_parent(),
+                   Err(TreeError::InvalidOperationOnRootContainer(root_c_id)));
+        tree.focus_child().unwrap();
+        assert_eq!(tree.active_container, Some(view_ix));
+        // Already at the bottom
+        tree.focus_child().unwrap();
+        assert_eq!(tree.active_container, Some(view_ix));
+    }
+
+    #[test]
+    fn focus_back_undoes_focus_changes() {
+        let mut tree = basic_tree();
+        let first_ix = tree.active_container.unwrap();
+        tree.switch_to_workspace("2");
+        let second_ix = tree.active_container.unwrap();
+        let parent_ix = tree.tree.parent_of(second_ix).unwrap();
+        let third_ix = tree.tree.children_of(parent_ix).into_iter()
+            .find(|&child_ix| child_ix != second_ix).unwrap();
+        tree.set_active_node(third_ix).unwrap();
+        tree.focus_back().unwrap();
+        assert_eq!(tree.active_container, Some(second_ix));
+        tree.focus_back().unwrap();
+        assert_eq!(tree.active_container, Some(first_ix));
+        // Removed containers are skipped
+        tree.set_active_node(third_ix).unwrap();
+        tree.set_active_node(second_ix).unwrap();
+        tree.remove_view_or_container(third_ix).unwrap();
+        tree.set_active_node(second_ix).unwrap();
+        tree.focus_back().unwrap();
+        assert_eq!(tree.active_container, Some(first_ix));
+        // The history is bounded
+        for _ in 0..(FOCUS_HISTORY_SIZE * 2) {
+            tree.set_active_node(first_ix).unwrap();
+            tree.set_active_node(second_ix).unwrap();
+        }
+        assert_eq!(tree.focus_history.len(), FOCUS_HISTORY_SIZE);
+    }
+
+    #[test]
+    fn active_path_uuids_test() {
+        let mut tree = basic_tree();
+        let path = tree.active_path_uuids();
+        let active_ix = tree.active_container.unwrap();
+        let mut expected = vec![tree.tree[active_ix].get_id()];
+        let mut cur_ix = active_ix;
+        while let Ok(parent_ix) = tree.tree.parent_of(cur_ix) {
+            expected.insert(0, tree.tree[parent_ix].get_id());
+            cur_ix = parent_ix;
+        }
+        assert_eq!(path, expected);
+        // Root, output, workspace, root container and the view
+        assert_eq!(path.len(), 5);
+        tree.switch_to_workspace("2");
+        let path = tree.active_path_uuids();
+        assert_eq!(path.len(), 6);
+        assert_eq!(path.last(), Some(&tree.get_active_container().unwrap().get_id()));
+    }
+
+    #[test]
+    fn active_container_context_test() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let active_ix = tree.active_container.unwrap();
+        let output_ix = tree.tree.ancestor_of_type(active_ix, ContainerType::Output).unwrap();
+        let context = tree.active_container_context().unwrap();
+        assert_eq!(context.id, tree.tree[active_ix].get_id());
+        assert_eq!(context.workspace, "2");
+        assert_eq!(tree.tree[output_ix].get_handle(), Ok(Handle::Output(context.output)));
+        assert!(!context.floating && !context.fullscreen && !context.tabbed);
+        tree.set_active_layout(Layout::Tabbed).unwrap();
+        assert!(tree.active_container_context().unwrap().tabbed);
+        tree.active_container = None;
+        assert_eq!(tree.active_container_context(), None);
+    }
+
+    #[test]
+    fn unfocusable_views_are_skipped() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let active_ix = tree.active_container.unwrap();
+        let parent_ix = tree.tree.parent_of(active_ix).unwrap();
+        let other_ix = *tree.tree.children_of(parent_ix).iter()
+            .find(|&&child_ix| child_ix != active_ix).unwrap();
+        let other_id = tree.tree[other_ix].get_id();
+        tree.set_focusable(other_id, false).unwrap();
+        assert_eq!(tree.set_active_node(other_ix),
+                   Err(TreeError::Focus(FocusError::NotFocusable(other_id))));
+        assert_eq!(tree.active_container, Some(active_ix));
+        tree.focus_next().unwrap();
+        assert_eq!(tree.active_container, Some(active_ix));
+        for direction in &[Direction::Left, Direction::Right] {
+            tree.move_focus(*direction).unwrap();
+            assert_eq!(tree.active_container, Some(active_ix));
+        }
+        tree.set_focusable(other_id, true).unwrap();
+        tree.focus_next().unwrap();
+        assert_eq!(tree.active_container, Some(other_ix));
+    }
+
+    #[test]
+    fn all_views_test() {
+        let mut tree = basic_tree();
+        let first_id = tree.get_active_container().unwrap().get_id();
+        let views = tree.all_views();
+        assert_eq!(views.len(), 3);
+        assert_eq!(views[0].id, first_id);
+        assert!(views[0].focused && !views[1].focused && !views[2].focused);
+        let workspaces: Vec<_> = views.iter().map(|view| view.workspace.as_str()).collect();
+        assert_eq!(workspaces, vec!["1", "2", "2"]);
+        assert!(views.iter().all(|view| view.output.is_some() && !view.scratchpad));
+        // Put away views are listed last
+        let second_id = views[1].id;
+        tree.move_to_scratchpad(second_id).unwrap();
+        let views = tree.all_views();
+        assert_eq!(views.len(), 3);
+        assert_eq!(views[2].id, second_id);
+        assert!(views[2].scratchpad);
+        assert_eq!(views[2].output, None);
+    }
+
+    #[test]
+    fn views_in_focus_order_test() {
+        let mut tree = basic_tree();
+        let first_ix = tree.active_container.unwrap();
+        tree.switch_to_workspace("2");
+        let second_ix = tree.active_container.unwrap();
+        let parent_ix = tree.tree.parent_of(second_ix).unwrap();
+        let third_ix = tree.tree.children_of(parent_ix).into_iter()
+            .find(|&child_ix| child_ix != second_ix).unwrap();
+        let ids = |ixs: &[NodeIndex], tree: &LayoutTree| ixs.iter()
+            .map(|&ix| tree.tree[ix].get_id()).collect::<Vec<_>>();
+        tree.set_active_node(third_ix).unwrap();
+        assert_eq!(tree.views_in_focus_order(),
+                   ids(&[third_ix, second_ix, first_ix], &tree));
+        // Focusing on a container keeps its view first
+        tree.focus_parent().unwrap();
+        assert_eq!(tree.views_in_focus_order()[0], tree.tree[third_ix].get_id());
+        // Removed views are left out
+        let third_id = tree.tree[third_ix].get_id();
+        tree.remove_view_or_container(third_ix).unwrap();
+        assert!(!tree.views_in_focus_order().contains(&third_id));
+        assert_eq!(tree.views_in_focus_order().len(), 2);
+    }
+
+    #[test]
+    fn focus_container_in_dir_test() {
+        let mut tree = basic_tree();
+        let view_1 = tree.get_active_container().unwrap().get_id();
+        let view_2 = tree.add_view(WlcView::root()).unwrap().get_id();
+        // Wraps the second view in a new vertical container
+        tree.toggle_active_layout(Layout::Vertical).unwrap();
+        let container_ix = tree.tree.parent_of(tree.tree.lookup_id(view_2).unwrap())
+            .unwrap();
+        assert_eq!(tree.tree[container_ix].get_type(), ContainerType::Container);
+        tree.focus_on(view_1).unwrap();
+        tree.focus_container_in_dir(Direction::Right).unwrap();
+        assert_eq!(tree.active_container, Some(container_ix));
+        assert!(tree.tree.on_path(container_ix));
+        // And back to the view on the left
+        tree.focus_container_in_dir(Direction::Left).unwrap();
+        assert_eq!(tree.active_container, tree.tree.lookup_id(view_1));
+        // Nothing further left
+        assert!(tree.focus_container_in_dir(Direction::Left).is_err());
+    }
+
     /// Tests that after sending a floating view to a new workspace,
     /// there are no duplicate active numbers (and we can focus on that
     /// workspace with no problem)
@@ -410,15 +1113,108 @@ mod tests {
         assert_eq!(tree.tree.lookup_id(floating_view_2), tree.active_container);
 
         // now send the view to the workspace with the tiled window.
-        tree.send_active_to_workspace(target_workspace);
+        tree.send_active_to_workspace(target_workspace).unwrap();
         tree.switch_to_workspace(target_workspace);
         assert_eq!(tree.tree.lookup_id(floating_view_2), tree.active_container);
 
         // and again with the other one
         tree.switch_to_workspace(source_workspace);
-        tree.send_active_to_workspace(target_workspace);
+        tree.send_active_to_workspace(target_workspace).unwrap();
         tree.switch_to_workspace(target_workspace);
         assert_eq!(tree.tree.lookup_id(floating_view_1), tree.active_container);
 
     }
+
+    #[test]
+    fn focus_hook_reports_client_info() {
+        use std::sync::{Arc, Mutex};
+        let mut tree = basic_tree();
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        {
+            let reported = reported.clone();
+            tree.hooks.add_focus_hook(move |info| {
+                reported.lock().unwrap().push(info.clone())
+            });
+        }
+        tree.switch_to_workspace("2");
+        let active_ix = tree.active_container.unwrap();
+        let id = tree.tree[active_ix].get_id();
+        tree.set_active_container(id).unwrap();
+        let info = tree.focused_client_info().unwrap();
+        let handle = WlcView::root();
+        assert_eq!(info.id, id);
+        assert_eq!(info.title, handle.get_title());
+        assert_eq!(info.app_id, handle.get_app_id());
+        assert_eq!(info.workspace, "2");
+        assert_eq!(Some(info.geometry), tree.tree[active_ix].get_geometry());
+        assert_eq!(reported.lock().unwrap().last(), Some(&info));
+    }
+
+    #[test]
+    fn focus_change_hook_reports_old_and_new() {
+        use std::sync::{Arc, Mutex};
+        let mut tree = basic_tree();
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        {
+            let changes = changes.clone();
+            tree.on_focus_change(move |old, new| changes.lock().unwrap().push((old, new)));
+        }
+        tree.switch_to_workspace("2");
+        let active_ix = tree.active_container.unwrap();
+        let active_id = tree.tree[active_ix].get_id();
+        let parent_ix = tree.tree.parent_of(active_ix).unwrap();
+        let other_ix = *tree.tree.children_of(parent_ix).iter()
+            .find(|&&child_ix| child_ix != active_ix).unwrap();
+        let other_id = tree.tree[other_ix].get_id();
+        tree.set_active_node(other_ix).unwrap();
+        assert_eq!(changes.lock().unwrap().last(), Some(&(Some(active_id), other_id)));
+        // Focusing on what is already focused is not a change
+        let count = changes.lock().unwrap().len();
+        tree.set_active_node(other_ix).unwrap();
+        assert_eq!(changes.lock().unwrap().len(), count);
+        // Nor is being blocked by a fullscreen view
+        tree.set_fullscreen(other_id, true).unwrap();
+        let count = changes.lock().unwrap().len();
+        assert!(tree.set_active_node(active_ix).is_err());
+        assert_eq!(changes.lock().unwrap().len(), count);
+    }
+
+    /// Moving right from the last of two views in a horizontal container,
+    /// with each kind of focus wrapping.
+    #[test]
+    fn focus_wrapping_modes() {
+        let move_right = |wrapping: FocusWrapping, nested: bool| {
+            let mut tree = basic_tree();
+            tree.switch_to_workspace("3");
+            let outer_id = tree.add_view(WlcView::dummy(1)).unwrap().get_id();
+            let mut first_id = outer_id;
+            if nested {
+                // Splitting makes [outer, [first, second]]
+                first_id = tree.add_view(WlcView::dummy(2)).unwrap().get_id();
+                let first_ix = tree.tree.lookup_id(first_id).unwrap();
+                tree.split_view(first_ix, Layout::Horizontal).unwrap();
+            }
+            let second_id = tree.add_view(WlcView::dummy(3)).unwrap().get_id();
+            assert_eq!(tree.get_active_container().unwrap().get_id(), second_id);
+            tree.set_focus_wrapping(wrapping);
+            tree.move_focus(Direction::Right).unwrap();
+            tree.validate();
+            let active_id = tree.get_active_container().unwrap().get_id();
+            if active_id == second_id {
+                "second"
+            } else if active_id == first_id {
+                "first"
+            } else {
+                assert_eq!(active_id, outer_id);
+                "outer"
+            }
+        };
+        assert_eq!(move_right(FocusWrapping::No, false), "second");
+        assert_eq!(move_right(FocusWrapping::Yes, false), "first");
+        assert_eq!(move_right(FocusWrapping::Force, false), "first");
+        // Nested in a horizontal container, only force stays within it
+        assert_eq!(move_right(FocusWrapping::No, true), "second");
+        assert_eq!(move_right(FocusWrapping::Yes, true), "outer");
+        assert_eq!(move_right(FocusWrapping::Force, true), "first");
+    }
 }
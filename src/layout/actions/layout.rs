@@ -1,6 +1,7 @@
 use std::cmp;
 use std::collections::HashSet;
 use std::iter::FromIterator;
+use std::ops::Deref;
 
 use petgraph::graph::NodeIndex;
 use rustwlc::{WlcView, Geometry, Point, Size, ResizeEdge};
@@ -8,7 +9,7 @@ use rustwlc::{WlcView, Geometry, Point, Size, ResizeEdge};
 use super::super::{LayoutTree, TreeError};
 use super::super::commands::CommandResult;
 use super::super::core::container::{Container, ContainerType, ContainerErr,
-                                    Layout, Handle};
+                                    Layout, Handle, MIN_SIZE};
 use super::super::core::background::MaybeBackground;
 use super::borders;
 use ::layout::core::borders::Borders;
@@ -23,11 +24,55 @@ pub enum LayoutErr {
     AlreadyFloating(Uuid)
 }
 
+/// Which way new splits are oriented, like i3's `default_orientation`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Orientation {
+    /// Horizontal if the container being split is wider than it is tall,
+    /// otherwise vertical
+    Auto,
+    Horizontal,
+    Vertical
+}
+
 impl LayoutTree {
     /// Given the index of some container in the tree, lays out the children of
     /// that container based on what type of container it is and how big of an
     /// area is allocated for it and its children.
+    ///
+    /// If layout is suspended, the layout is deferred until it is resumed.
     pub fn layout(&mut self, node_ix: NodeIndex) {
+        if self.suspend_count > 0 {
+            self.pending_layout = true;
+            return
+        }
+        self.layout_depth += 1;
+        self.layout_node(node_ix);
+        self.layout_depth -= 1;
+        if self.layout_depth == 0 {
+            self.hooks.reflowed(node_ix);
+        }
+    }
+
+    /// Stops the tree from being laid out until `resume_layout` is called.
+    ///
+    /// This is useful when making many changes at once, so that the tree is
+    /// only laid out once at the end. Calls can be nested.
+    pub fn suspend_layout(&mut self) {
+        self.suspend_count += 1;
+    }
+
+    /// Resumes laying out the tree. If a layout was requested while it was
+    /// suspended, the whole tree is laid out.
+    pub fn resume_layout(&mut self) {
+        self.suspend_count = self.suspend_count.saturating_sub(1);
+        if self.suspend_count == 0 && self.pending_layout {
+            self.pending_layout = false;
+            let root_ix = self.tree.root_ix();
+            self.layout(root_ix);
+        }
+    }
+
+    fn layout_node(&mut self, node_ix: NodeIndex) {
         match self.tree[node_ix].get_type() {
             ContainerType::Root => {
                 for output_ix in self.tree.children_of(node_ix) {
@@ -47,7 +92,8 @@ impl LayoutTree {
                             // update the background size
                             match *background {
                                 Some(MaybeBackground::Complete(background)) => {
-                                    background.handle.set_geometry(ResizeEdge::empty(), actual_geometry)
+                                    let geometry = background.geometry(actual_geometry);
+                                    background.handle.set_geometry(ResizeEdge::empty(), geometry)
                                 },
                                 _ => {}
                             }
@@ -112,8 +158,24 @@ impl LayoutTree {
             }
             ContainerType::Workspace => {
                 self.tree[node_ix].set_geometry(ResizeEdge::empty(), geometry);
+                // Only the tiled views are pushed in from the edges
+                let tiled_geometry = if self.smart_gaps_apply(node_ix) {
+                    geometry
+                } else {
+                    let outer_gap = self.outer_gap;
+                    Geometry {
+                        origin: Point {
+                            x: geometry.origin.x + outer_gap as i32,
+                            y: geometry.origin.y + outer_gap as i32
+                        },
+                        size: Size {
+                            w: geometry.size.w.saturating_sub(outer_gap * 2),
+                            h: geometry.size.h.saturating_sub(outer_gap * 2)
+                        }
+                    }
+                };
                 for child_ix in self.tree.grounded_children(node_ix) {
-                    self.layout_helper(child_ix, geometry, fullscreen_apps);
+                    self.layout_helper(child_ix, tiled_geometry, fullscreen_apps);
                 }
                 // place floating children above everything else
                 let root_ix = self.tree.children_of(node_ix)[0];
@@ -133,6 +195,7 @@ impl LayoutTree {
                 };
                 match layout {
                     Layout::Horizontal => {
+                        self.fit_min_sizes(node_ix, true, geometry.size.w);
                         let children = self.tree.grounded_children(node_ix);
                         let children_len = children.len();
                         let mut scale = LayoutTree::calculate_scale(children.iter().map(|child_ix| {
@@ -182,6 +245,7 @@ impl LayoutTree {
                         }
                     }
                     Layout::Vertical => {
+                        self.fit_min_sizes(node_ix, false, geometry.size.h);
                         let children = self.tree.grounded_children(node_ix);
                         let children_len = children.len();
                         let mut scale = LayoutTree::calculate_scale(children.iter().map(|child_ix| {
@@ -273,6 +337,7 @@ impl LayoutTree {
 
             ContainerType::View => {
                 self.tree[node_ix].set_geometry(ResizeEdge::empty(), geometry);
+                self.update_hidden_borders(node_ix);
                 self.update_view_geo_for_borders(node_ix)
                     .expect("Couldn't add border gaps to horizontal container");
             }
@@ -285,8 +350,9 @@ impl LayoutTree {
     /// This removes the container from its parent and makes its new parent-
     /// the workspace it resides in.
     ///
-    /// The view will have a geometry of 1/2 the height/width, and set right in the
-    /// middle of the screen.
+    /// The view is put back where it was the last time it was floating.
+    /// If it never was, it's 3/4 the height/width of its workspace,
+    /// and set right in the middle of it.
     ///
     /// This will change the active container, but **not** the active path,
     /// it will remain pointing at the previous parent container.
@@ -300,30 +366,33 @@ impl LayoutTree {
             warn!("Trying to float an already floating container");
             return Err(TreeError::Layout(LayoutErr::AlreadyFloating(id)));
         }
-        let output_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Output)
+        let workspace_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)
                              .map_err(|err| TreeError::PetGraph(err))?;
-        let output_size = match self.tree[output_ix] {
-            Container::Output { handle, .. } => {
-                handle.get_resolution().expect("Output had no resolution")
-            },
-            _ => unreachable!()
-        };
+        let workspace_geometry = self.tree[workspace_ix].get_geometry()
+            .expect("Workspace had no geometry");
         {
             let container = &mut self.tree[node_ix];
             container.set_floating(true)
                 .map_err(|_|
                          TreeError::UuidWrongType(id, vec!(ContainerType::View,
                                                            ContainerType::Container)))?;
-            let new_geometry = Geometry {
-                    size: Size {
-                        h: output_size.h / 2,
-                        w: output_size.w / 2
-                    },
-                    origin: Point {
-                        x: (output_size.w / 2 - output_size.w / 4) as i32 ,
-                        y: (output_size.h / 2 - output_size.h / 4) as i32
-                    }
+            // Put it back where it was the last time it was floating,
+            // otherwise center it in the workspace.
+            let new_geometry = container.last_floating_geometry().unwrap_or_else(|| {
+                let size = Size {
+                    w: workspace_geometry.size.w * 3 / 4,
+                    h: workspace_geometry.size.h * 3 / 4
                 };
+                Geometry {
+                    origin: Point {
+                        x: workspace_geometry.origin.x +
+                            (workspace_geometry.size.w - size.w) as i32 / 2,
+                        y: workspace_geometry.origin.y +
+                            (workspace_geometry.size.h - size.h) as i32 / 2
+                    },
+                    size: size
+                }
+            });
             match container.get_type() {
                 ContainerType::View | ContainerType::Container => {
                     container.set_geometry(ResizeEdge::empty(), new_geometry);
@@ -375,6 +444,9 @@ impl LayoutTree {
         }
         {
             let container = &mut self.tree[floating_ix];
+            if let Some(geometry) = container.get_geometry() {
+                container.set_last_floating_geometry(geometry).ok();
+            }
             container.set_floating(false)
                  .map_err(|_| TreeError::UuidWrongType(id, vec!(ContainerType::View,
                                                                 ContainerType::Container)))?;
@@ -404,6 +476,66 @@ impl LayoutTree {
         Ok(())
     }
 
+    /// Tiles the floating view again, next to the tiled view that was
+    /// focused last in its workspace.
+    ///
+    /// If no tiled view in the workspace was focused, it's put at the end
+    /// of the root container of the workspace. The view keeps its borders,
+    /// and its floating geometry is remembered for when it's floated again.
+    pub fn tile_container(&mut self, id: Uuid) -> CommandResult {
+        let floating_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        match self.tree[floating_ix] {
+            Container::View { floating: true, .. } => {},
+            Container::View { .. } =>
+                return Err(TreeError::Layout(LayoutErr::AlreadyGrounded(id))),
+            _ => return Err(TreeError::UuidWrongType(id, vec![ContainerType::View]))
+        }
+        let workspace_ix = self.tree.ancestor_of_type(floating_ix, ContainerType::Workspace)?;
+        let root_c_ix = self.tree.children_of(workspace_ix)[0];
+        let sibling_ix = self.focus_history.iter().rev()
+            .filter(|&&other_id| other_id != id)
+            .filter_map(|&other_id| self.tree.lookup_id(other_id))
+            .find(|&other_ix| match self.tree[other_ix] {
+                Container::View { floating: false, .. } =>
+                    self.tree.ancestor_of_type(other_ix, ContainerType::Workspace)
+                    == Ok(workspace_ix),
+                _ => false
+            });
+        let (parent_ix, pos) = match sibling_ix {
+            Some(sibling_ix) => {
+                let parent_ix = self.tree.parent_of(sibling_ix)?;
+                let pos = *self.tree.get_edge_weight_between(parent_ix, sibling_ix)
+                    .expect("Sibling had no edge weight").deref() + 1;
+                (parent_ix, pos)
+            },
+            None => (root_c_ix, self.tree.children_of(root_c_ix).len() as u32)
+        };
+        {
+            let container = &mut self.tree[floating_ix];
+            if let Some(geometry) = container.get_geometry() {
+                container.set_last_floating_geometry(geometry).ok();
+            }
+            container.set_floating(false)
+                .map_err(|_| TreeError::UuidWrongType(id, vec!(ContainerType::View)))?;
+        }
+        self.tree.move_into(floating_ix, parent_ix)?;
+        self.tree.set_child_pos(floating_ix, pos);
+        let draw_title = match self.tree[parent_ix].get_layout()? {
+            Layout::Tabbed | Layout::Stacked => false,
+            Layout::Horizontal | Layout::Vertical => true
+        };
+        match self.tree[floating_ix] {
+            Container::View { ref mut borders, .. } => {
+                borders.as_mut().map(|b| b.draw_title = draw_title);
+            },
+            _ => {}
+        }
+        self.layout(workspace_ix);
+        self.validate();
+        Ok(())
+    }
+
     /// If the node is floating, places it at its reported position, above all
     /// other nodes.
     fn place_floating(&mut self, node_ix: NodeIndex,
@@ -462,6 +594,7 @@ impl LayoutTree {
                     },
                     _ => unreachable!()
                 }
+                self.emit_layout_changed(parent_ix);
                 return Ok(())
             }
 
@@ -489,12 +622,94 @@ impl LayoutTree {
             // add_container sets the active container to be the new container
             self.set_active_node(active_ix)?;
             let parent_ix = self.tree.parent_of(active_ix)?;
+            self.emit_layout_changed(parent_ix);
             self.layout(parent_ix);
         }
         self.validate();
         Ok(())
     }
 
+    /// Sets the layout the next view added next to the view behind the UUID
+    /// will be split with.
+    ///
+    /// Unlike `toggle_active_layout` nothing changes right away, instead the
+    /// next `add_view` wraps the view in a new container with that layout.
+    /// The split is forgotten once it's been used, or when the focus moves
+    /// off the view.
+    pub fn set_split_direction(&mut self, id: Uuid, layout: Layout) -> CommandResult {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        if self.tree[node_ix].get_type() != ContainerType::View {
+            return Err(TreeError::UuidWrongType(id, vec!(ContainerType::View)))
+        }
+        if self.tree[node_ix].floating() {
+            return Err(TreeError::ContainerWasFloating(node_ix))
+        }
+        self.pending_split = Some((id, layout));
+        Ok(())
+    }
+
+    /// Sets which way new splits are oriented, when they aren't given a
+    /// layout explicitly (e.g `split auto`, or the root container of a new
+    /// workspace).
+    ///
+    /// Setting it to horizontal or vertical also sets the default layout of
+    /// new workspaces, see `set_default_layout`.
+    pub fn set_default_orientation(&mut self, orientation: Orientation) {
+        self.default_orientation = orientation;
+        match orientation {
+            Orientation::Horizontal => self.default_layout = Layout::Horizontal,
+            Orientation::Vertical => self.default_layout = Layout::Vertical,
+            Orientation::Auto => {}
+        }
+    }
+
+    /// Gets the layout a split of something with the geometry should have,
+    /// according to the default orientation.
+    pub fn split_orientation(&self, geometry: Geometry) -> Layout {
+        match self.default_orientation {
+            Orientation::Horizontal => Layout::Horizontal,
+            Orientation::Vertical => Layout::Vertical,
+            Orientation::Auto => if geometry.size.w >= geometry.size.h {
+                Layout::Horizontal
+            } else {
+                Layout::Vertical
+            }
+        }
+    }
+
+    /// Splits the active container like `toggle_active_layout`, with the
+    /// layout picked by the default orientation.
+    pub fn split_active(&mut self) -> CommandResult {
+        let active_ix = self.active_container
+            .ok_or(TreeError::NoActiveContainer)?;
+        let geometry = self.tree[active_ix].get_geometry()
+            .expect("Active container had no geometry");
+        let layout = self.split_orientation(geometry);
+        self.toggle_active_layout(layout)
+    }
+
+    /// Wraps the view in a new container with the given layout,
+    /// keeping the focus on the view.
+    pub fn split_view(&mut self, view_ix: NodeIndex, layout: Layout) -> CommandResult {
+        let geometry = self.tree[view_ix].get_geometry()
+            .expect("View had no geometry");
+        let output_ix = self.tree.ancestor_of_type(view_ix, ContainerType::Output)?;
+        let output = match self.tree[output_ix].get_handle()? {
+            Handle::Output(handle) => handle,
+            _ => unreachable!()
+        };
+        let borders = Borders::new(geometry, output);
+        let mut container = Container::new_container(geometry, output, borders);
+        container.set_layout(layout).ok();
+        self.add_container(container, view_ix)?;
+        // add_container sets the active container to be the new container
+        if self.active_container == self.tree.parent_of(view_ix).ok() {
+            self.set_active_node(view_ix)?;
+        }
+        Ok(())
+    }
+
     // Updates the tree's layout recursively starting from the active container.
     // If the active container is a view, it starts at the parent container.
     pub fn layout_active_of(&mut self, c_type: ContainerType) {
@@ -527,7 +742,9 @@ impl LayoutTree {
     /// Sets the active container to the given layout.
     ///
     /// If the container is a view, it sets the layout of its parent to the
-    /// given layout.
+    /// given layout. Unlike `toggle_active_layout`, this accepts any layout.
+    /// When set to tabbed or stacked the children overlap and only
+    /// the focused one is visible.
     ///
     /// Automatically retiles the container whose layout was changed.
     pub fn set_active_layout(&mut self, new_layout: Layout) -> CommandResult {
@@ -537,19 +754,23 @@ impl LayoutTree {
             node_ix = self.tree.parent_of(node_ix)
                 .expect("View had no parent");
         }
-        // Remove the title if tabbed or stacked
-        if new_layout == Layout::Tabbed || new_layout == Layout::Stacked {
-            for child_ix in self.tree.grounded_children(node_ix) {
-                match self.tree[child_ix] {
-                    Container::View { ref mut borders, .. } => {
-                        borders.as_mut().map(|b| b.draw_title = false);
-                    },
-                    _ => {}
-                }
+        // Tabbed and stacked containers draw the titles of their children,
+        // so the views only draw their own when tiled side by side.
+        let draw_title = match new_layout {
+            Layout::Tabbed | Layout::Stacked => false,
+            Layout::Horizontal | Layout::Vertical => true
+        };
+        for child_ix in self.tree.grounded_children(node_ix) {
+            match self.tree[child_ix] {
+                Container::View { ref mut borders, .. } => {
+                    borders.as_mut().map(|b| b.draw_title = draw_title);
+                },
+                _ => {}
             }
         }
         self.tree[node_ix].set_layout(new_layout)
             .map_err(TreeError::Container)?;
+        self.emit_layout_changed(node_ix);
         self.validate();
         let workspace_ix = self.tree.ancestor_of_type(node_ix,
                                                       ContainerType::Workspace)?;
@@ -557,6 +778,77 @@ impl LayoutTree {
         Ok(())
     }
 
+    /// Moves all the tiled views in the workspace that share a class into a
+    /// tabbed container, one container per class.
+    ///
+    /// If a view has no class, its app id is used instead.
+    /// Classes with only one view are left alone.
+    pub fn group_by_class(&mut self, workspace: &str) -> CommandResult {
+        self.with_workspace(workspace, |this, workspace_ix| {
+            this.group_views_by(workspace_ix, |container| match *container {
+                Container::View { handle, .. } => {
+                    let class = handle.get_class();
+                    if class.is_empty() {
+                        handle.get_app_id()
+                    } else {
+                        class
+                    }
+                },
+                _ => String::new()
+            })
+        })?
+    }
+
+    /// Groups the tiled views in the workspace into tabbed containers,
+    /// based on the key returned for each view.
+    fn group_views_by<F>(&mut self, workspace_ix: NodeIndex, key: F) -> CommandResult
+        where F: Fn(&Container) -> String
+    {
+        let root_ix = self.tree.children_of(workspace_ix)[0];
+        let mut groups: Vec<(String, Vec<NodeIndex>)> = Vec::new();
+        for node_ix in self.tree.all_descendants_of(root_ix) {
+            let container = &self.tree[node_ix];
+            if container.get_type() != ContainerType::View || container.floating() {
+                continue
+            }
+            let key = key(container);
+            match groups.iter().position(|&(ref group_key, _)| *group_key == key) {
+                Some(index) => groups[index].1.push(node_ix),
+                None => groups.push((key, vec![node_ix]))
+            }
+        }
+        let (geometry, output) = match self.tree[root_ix] {
+            Container::Container { geometry, output_handle, .. } => (geometry, output_handle),
+            _ => unreachable!()
+        };
+        for (_, views) in groups.into_iter().filter(|&(_, ref views)| views.len() > 1) {
+            let mut group = Container::new_container(geometry, output,
+                                                     Borders::new(geometry, output));
+            group.set_layout(Layout::Tabbed)?;
+            let group_ix = self.tree.add_child(root_ix, group, false);
+            for view_ix in views {
+                let parent_ix = self.tree.parent_of(view_ix)?;
+                self.tree.move_node(view_ix, group_ix);
+                match self.tree[view_ix] {
+                    Container::View { ref mut borders, .. } => {
+                        borders.as_mut().map(|b| b.draw_title = false);
+                    },
+                    _ => unreachable!()
+                }
+                if self.tree.can_remove_empty_parent(parent_ix) {
+                    self.remove_view_or_container(parent_ix)?;
+                }
+            }
+        }
+        if let Some(active_ix) = self.active_container {
+            self.tree.set_ancestor_paths_active(active_ix);
+        }
+        self.layout(workspace_ix);
+        self.validate();
+        self.validate_path();
+        Ok(())
+    }
+
     /// Gets the active container and toggles it based on the following rules:
     /// * If horizontal, make it vertical
     /// * else, make it horizontal
@@ -575,7 +867,12 @@ impl LayoutTree {
             }
             let new_layout = match self.tree[node_ix].get_layout()? {
                 Layout::Horizontal => Layout::Vertical,
-                _ => Layout::Horizontal
+                Layout::Vertical => Layout::Horizontal,
+                Layout::Tabbed | Layout::Stacked => {
+                    let geometry = self.tree[node_ix].get_geometry()
+                        .expect("Container had no geometry");
+                    self.split_orientation(geometry)
+                }
             };
             match new_layout {
                 Layout::Horizontal | Layout::Vertical => {
@@ -596,6 +893,43 @@ impl LayoutTree {
         Ok(())
     }
 
+    /// Cycles the layout of the container behind the UUID through
+    /// horizontal, vertical, tabbed and stacked, back to horizontal.
+    /// If the UUID is for a view, its parent container is used instead.
+    ///
+    /// Automatically retiles the workspace of the container.
+    pub fn cycle_tiling_mode(&mut self, id: Uuid) -> CommandResult {
+        let mut node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        if self.tree[node_ix].get_type() == ContainerType::View {
+            node_ix = self.tree.parent_of(node_ix)?;
+        }
+        let new_layout = match self.tree[node_ix].get_layout()? {
+            Layout::Horizontal => Layout::Vertical,
+            Layout::Vertical => Layout::Tabbed,
+            Layout::Tabbed => Layout::Stacked,
+            Layout::Stacked => Layout::Horizontal
+        };
+        let draw_title = match new_layout {
+            Layout::Tabbed | Layout::Stacked => false,
+            Layout::Horizontal | Layout::Vertical => true
+        };
+        for child_ix in self.tree.grounded_children(node_ix) {
+            match self.tree[child_ix] {
+                Container::View { ref mut borders, .. } => {
+                    borders.as_mut().map(|b| b.draw_title = draw_title);
+                },
+                _ => {}
+            }
+        }
+        self.set_layout(node_ix, new_layout);
+        let workspace_ix = self.tree.ancestor_of_type(node_ix,
+                                                      ContainerType::Workspace)?;
+        self.layout(workspace_ix);
+        self.validate();
+        Ok(())
+    }
+
 
     /// Calculates how much to scale on average for each value given.
     /// If the value is 0 (i.e the width or height of the container is 0),
@@ -612,6 +946,34 @@ impl LayoutTree {
         return scale;
     }
 
+    /// Splits the area of a stacked container with `count` children.
+    ///
+    /// Returns the title bar of each child, stacked on top of each other
+    /// from the top of the area, and the area below all of them that
+    /// the focused child is shown in.
+    fn stacked_geometry(geometry: Geometry, title_size: u32, count: usize)
+                        -> (Vec<Geometry>, Geometry) {
+        let title_bars = (0..count).map(|index| Geometry {
+            origin: Point {
+                x: geometry.origin.x,
+                y: geometry.origin.y + (title_size * index as u32) as i32
+            },
+            size: Size { w: geometry.size.w, h: title_size }
+        }).collect();
+        let titles_height = cmp::min(title_size * count as u32, geometry.size.h);
+        let body = Geometry {
+            origin: Point {
+                x: geometry.origin.x,
+                y: geometry.origin.y + titles_height as i32
+            },
+            size: Size {
+                w: geometry.size.w,
+                h: geometry.size.h - titles_height
+            }
+        };
+        (title_bars, body)
+    }
+
     fn generic_tile<SizeF, RemainF, PointF>
         (&mut self,
          node_ix: NodeIndex, geometry: Geometry, children: &[NodeIndex],
@@ -669,6 +1031,7 @@ impl LayoutTree {
                 return;
             }
         }
+        self.emit_layout_changed(node_ix);
         if new_layout == Layout::Vertical || new_layout == Layout::Horizontal {
             for child_ix in self.tree.children_of(node_ix) {
                 self.normalize_container(child_ix).ok();
@@ -682,6 +1045,19 @@ impl LayoutTree {
         }
     }
 
+    /// Runs the layout changed hooks for the container.
+    fn emit_layout_changed(&mut self, node_ix: NodeIndex) {
+        let layout = match self.tree[node_ix].get_layout() {
+            Ok(layout) => layout,
+            Err(_) => return
+        };
+        let id = self.tree[node_ix].get_id();
+        let workspace = self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)
+            .map(|workspace_ix| self.tree[workspace_ix].name())
+            .unwrap_or_default();
+        self.hooks.layout_changed(id, workspace.as_str(), layout);
+    }
+
     /// Normalizes the geometry of a view to be the same size as it's siblings,
     /// based on the parent container's layout, at the 0 point of the parent container.
     /// Note this does not auto-tile, only modifies this one view.
@@ -763,6 +1139,200 @@ impl LayoutTree {
         Ok(())
     }
 
+    /// Resizes the tiled children of the container so that together they
+    /// fill it again, keeping their sizes relative to each other,
+    /// and then lays the container out.
+    ///
+    /// This is used after a child is removed, so its space is shared
+    /// between its siblings instead of being left to the last one.
+    pub fn rebalance_children(&mut self, parent_ix: NodeIndex) {
+        let layout = match self.tree[parent_ix] {
+            Container::Container { layout, .. } => layout,
+            _ => return
+        };
+        let parent_geometry = match self.tree[parent_ix].get_actual_geometry() {
+            Some(geometry) => geometry,
+            None => return
+        };
+        let children = self.tree.grounded_children(parent_ix);
+        let horizontal = match layout {
+            Layout::Horizontal => true,
+            Layout::Vertical => false,
+            Layout::Tabbed | Layout::Stacked => {
+                self.layout(parent_ix);
+                return
+            }
+        };
+        let length_of = |geometry: Geometry| if horizontal {
+            geometry.size.w
+        } else {
+            geometry.size.h
+        };
+        let total = length_of(parent_geometry);
+        let lengths: Vec<u32> = children.iter()
+            .map(|child_ix| self.tree[*child_ix].get_geometry()
+                 .map(|geometry| length_of(geometry)).unwrap_or(0))
+            .collect();
+        let sum = lengths.iter().fold(0u64, |acc, &length| acc + length as u64);
+        let mut offset = 0;
+        for (index, child_ix) in children.iter().enumerate() {
+            let length = if index == children.len() - 1 {
+                total - offset
+            } else if sum == 0 {
+                total / children.len() as u32
+            } else {
+                (lengths[index] as u64 * total as u64 / sum) as u32
+            };
+            let geometry = if horizontal {
+                Geometry {
+                    origin: Point {
+                        x: parent_geometry.origin.x + offset as i32,
+                        y: parent_geometry.origin.y
+                    },
+                    size: Size { w: length, h: parent_geometry.size.h }
+                }
+            } else {
+                Geometry {
+                    origin: Point {
+                        x: parent_geometry.origin.x,
+                        y: parent_geometry.origin.y + offset as i32
+                    },
+                    size: Size { w: parent_geometry.size.w, h: length }
+                }
+            };
+            self.tree[*child_ix].set_geometry(ResizeEdge::empty(), geometry);
+            offset += length;
+        }
+        self.layout(parent_ix);
+    }
+
+    /// Resizes the tiled children of the container behind the UUID so that
+    /// they all have the same size, undoing any manual resizing.
+    /// If the UUID is for a view, its parent container is used instead.
+    ///
+    /// If `recursive` is set, every container beneath it is equalized too.
+    pub fn equalize(&mut self, id: Uuid, recursive: bool) -> CommandResult {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        let container_ix = match self.tree[node_ix].get_type() {
+            ContainerType::Container => node_ix,
+            ContainerType::View => self.tree.parent_of(node_ix)?,
+            _ => return Err(TreeError::UuidWrongType(id, vec!(ContainerType::View,
+                                                              ContainerType::Container)))
+        };
+        let mut containers = vec![container_ix];
+        if recursive {
+            containers.extend(self.tree.all_descendants_of(container_ix).into_iter()
+                              .filter(|&child_ix| self.tree[child_ix].get_type()
+                                      == ContainerType::Container));
+        }
+        // Parents come before their children, so each container is
+        // equalized after it has been given its new size.
+        for container_ix in containers {
+            if self.tree[container_ix].floating() {
+                continue
+            }
+            let geometry = match self.tree[container_ix].get_actual_geometry() {
+                Some(geometry) => geometry,
+                None => continue
+            };
+            for child_ix in self.tree.grounded_children(container_ix) {
+                self.tree[child_ix].set_geometry(ResizeEdge::empty(), geometry);
+            }
+            self.rebalance_children(container_ix);
+        }
+        self.validate();
+        Ok(())
+    }
+
+    /// Resizes the tiled children of the container along its axis so that
+    /// once they are tiled in the given length none of them is smaller than
+    /// its minimum size, taking the space from siblings that have some spare.
+    ///
+    /// If they can't all fit they are left alone, so they overflow their
+    /// minimum sizes instead.
+    fn fit_min_sizes(&mut self, node_ix: NodeIndex, horizontal: bool, total: u32) {
+        let length_of = |size: Size| if horizontal { size.w } else { size.h };
+        let children = self.tree.grounded_children(node_ix);
+        let mins: Vec<u64> = children.iter()
+            .map(|&child_ix| length_of(self.min_size_of(child_ix)) as u64)
+            .collect();
+        // Only views that asked for a minimum size need any of this
+        if mins.iter().all(|&min| min <= length_of(MIN_SIZE) as u64) {
+            return
+        }
+        if mins.iter().sum::<u64>() > total as u64 {
+            warn!("Children of {:?} don't fit in {} at their minimum sizes",
+                  node_ix, total);
+            return
+        }
+        let lengths: Vec<u64> = children.iter()
+            .map(|&child_ix| self.tree[child_ix].get_geometry()
+                 .map(|geometry| length_of(geometry.size) as u64).unwrap_or(0))
+            .collect();
+        let sum = lengths.iter().sum::<u64>();
+        if sum == 0 {
+            return
+        }
+        // How long each child will be once it's tiled
+        let mut scaled: Vec<u64> = lengths.iter()
+            .map(|&length| length * total as u64 / sum)
+            .collect();
+        let deficit = scaled.iter().zip(&mins)
+            .fold(0, |acc, (&length, &min)| acc + min.saturating_sub(length));
+        if deficit == 0 {
+            return
+        }
+        let spare = scaled.iter().zip(&mins)
+            .fold(0, |acc, (&length, &min)| acc + length.saturating_sub(min));
+        if spare == 0 {
+            return
+        }
+        for (length, &min) in scaled.iter_mut().zip(&mins) {
+            if *length < min {
+                *length = min;
+            } else {
+                *length -= (*length - min) * deficit / spare;
+            }
+        }
+        for (&child_ix, &length) in children.iter().zip(&scaled) {
+            let mut geometry = match self.tree[child_ix].get_geometry() {
+                Some(geometry) => geometry,
+                None => continue
+            };
+            if horizontal {
+                geometry.size.w = length as u32;
+            } else {
+                geometry.size.h = length as u32;
+            }
+            self.tree[child_ix].set_geometry(ResizeEdge::empty(), geometry);
+        }
+    }
+
+    /// Gets the smallest rectangle that covers every output.
+    fn outputs_bounding_box(&self) -> Geometry {
+        let root_ix = self.tree.root_ix();
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (0, 0, 0, 0);
+        for (index, output_ix) in self.tree.children_of(root_ix).into_iter().enumerate() {
+            let geometry = self.tree[output_ix].get_actual_geometry()
+                .expect("Output did not have a geometry associated with it");
+            let (x, y) = (geometry.origin.x, geometry.origin.y);
+            let (right, bottom) = (x + geometry.size.w as i32, y + geometry.size.h as i32);
+            if index == 0 {
+                min_x = x; min_y = y; max_x = right; max_y = bottom;
+            } else {
+                min_x = cmp::min(min_x, x);
+                min_y = cmp::min(min_y, y);
+                max_x = cmp::max(max_x, right);
+                max_y = cmp::max(max_y, bottom);
+            }
+        }
+        Geometry {
+            origin: Point { x: min_x, y: min_y },
+            size: Size { w: (max_x - min_x) as u32, h: (max_y - min_y) as u32 }
+        }
+    }
+
     /// Tiles these containers above all the other containers in its workspace.
     ///
     /// If multiple containers are in the same workspace, each one will be drawn
@@ -774,8 +1344,12 @@ impl LayoutTree {
         for node_ix in containers {
             let output_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Output)
                 .expect("Container did not have an output as an ancestor");
-            let output_geometry = self.tree[output_ix].get_actual_geometry()
-                .expect("Output did not have a geometry associated with it");
+            let output_geometry = if self.global_fullscreen == Some(self.tree[node_ix].get_id()) {
+                self.outputs_bounding_box()
+            } else {
+                self.tree[output_ix].get_actual_geometry()
+                    .expect("Output did not have a geometry associated with it")
+            };
 
             // Sorry, this is an ugly borrow checker hack
             // Can't do self.layout() in Container::Container, borrowing mutably self mutably here.
@@ -813,13 +1387,114 @@ impl LayoutTree {
     /// This does not recurse if a container is found.
     ///
     /// If the `NodeIndex` doesn't point to a `Container`, an error is returned.
+    /// Sets the gaps between tiled views, overriding the configured gap size.
+    ///
+    /// The inner gap goes between sibling views, the outer gap is
+    /// between the tiled views and the edges of the workspace.
+    /// Floating and fullscreen views are not affected.
+    pub fn set_gaps(&mut self, inner: u32, outer: u32) {
+        self.inner_gap = Some(inner);
+        self.outer_gap = outer;
+        let root_ix = self.tree.root_ix();
+        self.layout(root_ix);
+    }
+
+    /// Sets whether a workspace with only one tiled view has no gaps.
+    pub fn set_smart_gaps(&mut self, smart_gaps: bool) {
+        self.smart_gaps = smart_gaps;
+        let root_ix = self.tree.root_ix();
+        self.layout(root_ix);
+    }
+
+    /// Gets the gap between tiled siblings for the workspace the node is in.
+    fn inner_gap_of(&self, node_ix: NodeIndex) -> u32 {
+        if self.smart_gaps_apply(node_ix) {
+            0
+        } else {
+            self.inner_gap.unwrap_or_else(Borders::gap_size)
+        }
+    }
+
+    /// Determines if smart gaps removes the gaps for the workspace
+    /// the node is in, which is when it only has one tiled view.
+    fn smart_gaps_apply(&self, node_ix: NodeIndex) -> bool {
+        self.smart_gaps && self.has_lone_tiled_view(node_ix)
+    }
+
+    /// Sets when the borders of tiled views are hidden.
+    pub fn set_hide_edge_borders(&mut self, mode: borders::HideEdgeBorders) {
+        self.hide_edge_borders = mode;
+        let root_ix = self.tree.root_ix();
+        self.layout(root_ix);
+    }
+
+    /// Determines if the borders of the view are hidden by the
+    /// `HideEdgeBorders` mode. Floating views always keep their borders.
+    pub fn hides_borders(&self, node_ix: NodeIndex) -> bool {
+        let container = &self.tree[node_ix];
+        if container.get_type() != ContainerType::View || container.floating() {
+            return false
+        }
+        match self.hide_edge_borders {
+            borders::HideEdgeBorders::None => false,
+            borders::HideEdgeBorders::Smart => self.has_lone_tiled_view(node_ix),
+            borders::HideEdgeBorders::Both => true
+        }
+    }
+
+    /// Drops the borders of the view if they are hidden,
+    /// otherwise gives it back its borders if it lost them.
+    fn update_hidden_borders(&mut self, node_ix: NodeIndex) {
+        let hide = self.hides_borders(node_ix);
+        let draw_title = match self.tree.parent_of(node_ix)
+            .map(|parent_ix| self.tree[parent_ix].get_layout()) {
+                Ok(Ok(Layout::Tabbed)) | Ok(Ok(Layout::Stacked)) => false,
+                _ => true
+            };
+        match self.tree[node_ix] {
+            Container::View { handle, ref mut borders, .. } => {
+                if hide {
+                    *borders = None;
+                } else if borders.is_none() {
+                    let geometry = handle.get_geometry()
+                        .expect("View had no geometry");
+                    *borders = Borders::new(geometry, handle.get_output())
+                        .map(|mut b| {
+                            b.title = Container::get_title(handle);
+                            b.draw_title = draw_title;
+                            b
+                        });
+                }
+            },
+            _ => unreachable!()
+        }
+    }
+
+    /// Determines if the workspace the node is in has exactly one tiled view.
+    fn has_lone_tiled_view(&self, node_ix: NodeIndex) -> bool {
+        let workspace_ix = if self.tree[node_ix].get_type() == ContainerType::Workspace {
+            node_ix
+        } else {
+            match self.tree.ancestor_of_type(node_ix, ContainerType::Workspace) {
+                Ok(workspace_ix) => workspace_ix,
+                Err(_) => return false
+            }
+        };
+        self.tree.all_descendants_of(workspace_ix).into_iter()
+            .filter(|&child_ix| {
+                let child = &self.tree[child_ix];
+                child.get_type() == ContainerType::View && !child.floating()
+            })
+            .count() == 1
+    }
+
     fn add_gaps(&mut self, node_ix: NodeIndex) -> CommandResult {
         let layout = match self.tree[node_ix] {
             Container::Container { layout, .. } => layout,
             _ => return Err(TreeError::UuidNotAssociatedWith(
                 ContainerType::Container))
         };
-        let gap = Borders::gap_size();
+        let gap = self.inner_gap_of(node_ix);
         if gap == 0 {
             return Ok(())
         }
@@ -884,9 +1559,10 @@ impl LayoutTree {
     fn update_container_geo_for_borders(&mut self, node_ix: NodeIndex,
                                         mut geometry: Geometry)
                                         -> Result<Geometry, TreeError> {
-        // Stacked containers need bigger title bars.
+        // Stacked containers need a title bar for each child.
         // This has to be done before borrowing self.tree to use the container
-        let child_count = self.tree.children_of(node_ix).len();
+        let child_count = self.tree.grounded_children(node_ix).len();
+        let gap = self.inner_gap_of(node_ix);
 
         let container = &mut self.tree[node_ix];
 
@@ -897,22 +1573,19 @@ impl LayoutTree {
                                    ref borders, .. } => {
                 *actual_geometry = geometry;
                 if let Some(borders) = borders.as_ref() {
-                    let title_count = match layout {
-                        Layout::Stacked =>
-                            // I don't understand why I have to use this
-                            // instead of just child_count, but seems to work...
-                            (2 * child_count).saturating_sub(1) as u32,
-                        _ => 1
-                    };
-                    let gap = Borders::gap_size();
                     let thickness = Borders::thickness() + gap;
                     let edge_thickness = thickness / 2;
                     let title_size = borders.title_bar_size();
-                    let real_title_size = (title_size / 2) * title_count;
                     geometry.origin.y += edge_thickness as i32;
-                    geometry.origin.y += real_title_size as i32;
                     geometry.size.h = geometry.size.h.saturating_sub(edge_thickness);
-                    geometry.size.h = geometry.size.h.saturating_sub(real_title_size);
+                    if layout == Layout::Stacked {
+                        geometry = LayoutTree::stacked_geometry(geometry, title_size,
+                                                                child_count).1;
+                    } else {
+                        let real_title_size = title_size / 2;
+                        geometry.origin.y += real_title_size as i32;
+                        geometry.size.h = geometry.size.h.saturating_sub(real_title_size);
+                    }
                 }
                 *apparent_geometry = geometry;
             },
@@ -928,13 +1601,18 @@ impl LayoutTree {
     /// Updates the geometry of the view, so that the borders are not
     /// hidden by other views. E.g this ensures that the borders are treated
     /// as part of the container for tiling/rendering purposes
+    ///
+    /// The focused view can have thicker borders, which are taken out of
+    /// its usable area as well.
     fn update_view_geo_for_borders(&mut self, node_ix: NodeIndex) -> CommandResult {
         let container = &mut self.tree[node_ix];
         let mut geometry = container.get_geometry()
             .expect("Container had no geometry");
         match *container {
             Container::View { handle, ref borders, .. } => {
-                let thickness = Borders::thickness();
+                let thickness = borders.as_ref()
+                    .map(|borders| borders.view_thickness())
+                    .unwrap_or(0);
                 if thickness == 0 {
                     return Ok(())
                 }
@@ -966,6 +1644,20 @@ impl LayoutTree {
         Ok(())
     }
 
+    /// Fits a tiled view within its borders again, for when the
+    /// thickness of its borders changed because it gained or lost focus.
+    ///
+    /// Floating views and other containers are left alone.
+    pub fn update_border_thickness(&mut self, node_ix: NodeIndex) -> CommandResult {
+        match self.tree.get(node_ix) {
+            Some(&Container::View { floating: false, .. }) => {},
+            _ => return Ok(())
+        }
+        self.update_view_geo_for_borders(node_ix)?;
+        self.tree[node_ix].draw_borders()?;
+        Ok(())
+    }
+
     /// Draws the borders recursively, down from the top to the bottom.
     pub fn draw_borders_rec(&mut self, mut children: Vec<NodeIndex>)
                         -> CommandResult {
@@ -1039,7 +1731,385 @@ impl LayoutTree {
 
 #[cfg(test)]
 mod test {
-    use super::super::super::LayoutTree;
+    use super::super::super::{LayoutTree, TreeError};
+    use super::super::super::core::tree::tests::basic_tree;
+    use super::{LayoutErr, Orientation};
+    use super::super::super::core::container::{Container, ContainerType, Layout};
+    use rustwlc::{Geometry, Point, ResizeEdge, Size, WlcView};
+    use super::borders::HideEdgeBorders;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn tile_container_goes_next_to_focused_view() {
+        let mut tree = basic_tree();
+        let first_ix = tree.active_container.unwrap();
+        let root_c_ix = tree.tree.parent_of(first_ix).unwrap();
+        let floating_id = tree.add_view(WlcView::dummy(1)).unwrap().get_id();
+        let last_id = tree.add_view(WlcView::dummy(2)).unwrap().get_id();
+        tree.float_container(floating_id).unwrap();
+        tree.set_active_node(first_ix).unwrap();
+        let floating_ix = tree.tree.lookup_id(floating_id).unwrap();
+        tree.set_active_node(floating_ix).unwrap();
+        tree.tile_container(floating_id).unwrap();
+        let floating_ix = tree.tree.lookup_id(floating_id).unwrap();
+        assert!(!tree.tree[floating_ix].floating());
+        let last_ix = tree.tree.lookup_id(last_id).unwrap();
+        assert_eq!(tree.tree.grounded_children(root_c_ix),
+                   vec![first_ix, floating_ix, last_ix]);
+        // It's tiled like its siblings again
+        let geometry = tree.tree[floating_ix].get_geometry().unwrap();
+        let first_geometry = tree.tree[first_ix].get_geometry().unwrap();
+        assert_eq!(geometry.size, first_geometry.size);
+        assert!(tree.tree[floating_ix].last_floating_geometry().is_some());
+        assert_eq!(tree.tile_container(floating_id),
+                   Err(TreeError::Layout(LayoutErr::AlreadyGrounded(floating_id))));
+        tree.validate();
+    }
+
+    #[test]
+    fn layout_changed_hook_fires_once() {
+        let mut tree = basic_tree();
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        {
+            let changes = changes.clone();
+            tree.hooks.add_layout_changed_hook(move |id, workspace, layout| {
+                changes.lock().unwrap().push((id, workspace.to_string(), layout));
+            });
+        }
+        let root_ix = tree.tree.parent_of(tree.active_container.unwrap()).unwrap();
+        tree.active_container = Some(root_ix);
+        tree.toggle_active_layout(Layout::Vertical).unwrap();
+        let changes = changes.lock().unwrap();
+        assert_eq!(*changes, vec![(tree.tree[root_ix].get_id(), "1".to_string(),
+                                   Layout::Vertical)]);
+    }
+
+    #[test]
+    fn group_views_by_key() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("3");
+        let ids: Vec<_> = (0..4)
+            .map(|_| tree.add_view(WlcView::root()).unwrap().get_id())
+            .collect();
+        let (first, second) = (ids[0], ids[2]);
+        let workspace_ix = tree.tree.workspace_ix_by_name("3").unwrap();
+        tree.group_views_by(workspace_ix, |container| {
+            let id = container.get_id();
+            if id == first || id == ids[1] { "a".into() } else { "b".into() }
+        }).unwrap();
+        let root_ix = tree.tree.children_of(workspace_ix)[0];
+        let groups = tree.tree.children_of(root_ix);
+        assert_eq!(groups.len(), 2);
+        for group_ix in &groups {
+            match tree.tree[*group_ix] {
+                Container::Container { layout, .. } => assert_eq!(layout, Layout::Tabbed),
+                _ => panic!("Expected a tabbed container")
+            }
+            let children = tree.tree.children_of(*group_ix);
+            assert_eq!(children.len(), 2);
+            for child_ix in children {
+                assert_eq!(tree.tree[child_ix].get_type(), ContainerType::View);
+            }
+        }
+        let first_group = tree.tree.parent_of(tree.tree.lookup_id(first).unwrap()).unwrap();
+        let second_group = tree.tree.parent_of(tree.tree.lookup_id(second).unwrap()).unwrap();
+        assert!(first_group != second_group);
+    }
+
+    #[test]
+    fn set_active_layout_any_layout() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let parent_ix = tree.tree.parent_of(tree.active_container.unwrap()).unwrap();
+        for &layout in &[Layout::Tabbed, Layout::Stacked,
+                         Layout::Vertical, Layout::Horizontal] {
+            tree.set_active_layout(layout).unwrap();
+            assert_eq!(tree.tree[parent_ix].get_layout(), Ok(layout));
+            // The focus stays on the same container
+            assert_eq!(tree.tree.parent_of(tree.active_container.unwrap()), Ok(parent_ix));
+        }
+        tree.set_active_layout(Layout::Tabbed).unwrap();
+        // Children overlap in a tabbed container
+        let children = tree.tree.grounded_children(parent_ix);
+        let geometries: Vec<_> = children.iter()
+            .map(|&child_ix| tree.tree[child_ix].get_geometry().unwrap())
+            .collect();
+        assert!(geometries.iter().all(|geometry| *geometry == geometries[0]));
+    }
+
+    #[test]
+    fn gaps_shrink_tiled_area() {
+        let mut tree = basic_tree();
+        let output_ix = tree.tree.ancestor_of_type(tree.active_container.unwrap(),
+                                                   ContainerType::Output).unwrap();
+        let output_geometry = tree.tree[output_ix].get_geometry().unwrap();
+        let ws_1_root = tree.tree.parent_of(tree.active_container.unwrap()).unwrap();
+        tree.set_gaps(10, 20);
+        let geometry = tree.tree[ws_1_root].get_actual_geometry().unwrap();
+        assert_eq!(geometry.origin.x, output_geometry.origin.x + 20);
+        assert_eq!(geometry.origin.y, output_geometry.origin.y + 20);
+        assert_eq!(geometry.size.w, output_geometry.size.w - 40);
+        assert_eq!(geometry.size.h, output_geometry.size.h - 40);
+        assert_eq!(tree.inner_gap_of(ws_1_root), 10);
+
+        // A lone view on a workspace gets no gaps with smart gaps
+        tree.set_smart_gaps(true);
+        let geometry = tree.tree[ws_1_root].get_actual_geometry().unwrap();
+        assert_eq!(geometry, output_geometry);
+        assert_eq!(tree.inner_gap_of(ws_1_root), 0);
+        // But workspace 2 has two views, so it keeps them
+        tree.switch_to_workspace("2");
+        let ws_2_root = tree.tree.children_of(
+            tree.tree.workspace_ix_by_name("2").unwrap())[0];
+        let geometry = tree.tree[ws_2_root].get_actual_geometry().unwrap();
+        assert_eq!(geometry.origin.x, output_geometry.origin.x + 20);
+        assert_eq!(tree.inner_gap_of(ws_2_root), 10);
+    }
+
+    #[test]
+    fn smart_hide_edge_borders() {
+        let mut tree = basic_tree();
+        let lone_ix = tree.active_container.unwrap();
+        assert!(!tree.hides_borders(lone_ix));
+        tree.set_hide_edge_borders(HideEdgeBorders::Smart);
+        assert!(tree.hides_borders(lone_ix));
+        // Workspace 2 has two views, so they keep their borders
+        let ws_2_ix = tree.tree.workspace_ix_by_name("2").unwrap();
+        let ws_2_views: Vec<_> = tree.tree.all_descendants_of(ws_2_ix).into_iter()
+            .filter(|&ix| tree.tree[ix].get_type() == ContainerType::View)
+            .collect();
+        assert_eq!(ws_2_views.len(), 2);
+        assert!(ws_2_views.iter().all(|&ix| !tree.hides_borders(ix)));
+        // A second view brings the borders back on both
+        tree.add_view(WlcView::root()).unwrap();
+        let new_ix = tree.active_container.unwrap();
+        assert!(!tree.hides_borders(lone_ix));
+        assert!(!tree.hides_borders(new_ix));
+        tree.set_hide_edge_borders(HideEdgeBorders::Both);
+        assert!(tree.hides_borders(lone_ix));
+        assert!(tree.hides_borders(new_ix));
+    }
+
+    #[test]
+    fn focus_change_keeps_tiled_geometry() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let first_ix = tree.active_container.unwrap();
+        let parent_ix = tree.tree.parent_of(first_ix).unwrap();
+        tree.layout(parent_ix);
+        let children = tree.tree.grounded_children(parent_ix);
+        let geometries: Vec<_> = children.iter()
+            .map(|&ix| tree.tree[ix].get_geometry().unwrap())
+            .collect();
+        for &child_ix in children.iter().rev() {
+            tree.set_active_node(child_ix).unwrap();
+            tree.update_border_thickness(child_ix).unwrap();
+        }
+        // Only the usable area of the view changes, not its slot in the layout
+        for (&child_ix, geometry) in children.iter().zip(geometries) {
+            assert_eq!(tree.tree[child_ix].get_geometry().unwrap(), geometry);
+        }
+    }
+
+    #[test]
+    fn removing_rebalances_siblings() {
+        let mut tree = basic_tree();
+        tree.add_view(WlcView::root()).unwrap();
+        tree.add_view(WlcView::root()).unwrap();
+        let parent_ix = tree.tree.parent_of(tree.active_container.unwrap()).unwrap();
+        tree.set_layout(parent_ix, Layout::Horizontal);
+        tree.layout(parent_ix);
+        let parent_w = tree.tree[parent_ix].get_geometry().unwrap().size.w as i32;
+        let children = tree.tree.grounded_children(parent_ix);
+        assert_eq!(children.len(), 3);
+        tree.remove_view_or_container(children[1]).unwrap();
+        let survivors = tree.tree.grounded_children(parent_ix);
+        assert_eq!(survivors.len(), 2);
+        for child_ix in survivors {
+            let width = tree.tree[child_ix].get_geometry().unwrap().size.w as i32;
+            assert!((width - parent_w / 2).abs() <= 1);
+        }
+    }
+
+    #[test]
+    fn equalize_evens_out_siblings() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        tree.add_view(WlcView::root()).unwrap();
+        let active_ix = tree.active_container.unwrap();
+        let parent_ix = tree.tree.parent_of(active_ix).unwrap();
+        tree.set_layout(parent_ix, Layout::Horizontal);
+        tree.layout(parent_ix);
+        let parent_geometry = tree.tree[parent_ix].get_geometry().unwrap();
+        let children = tree.tree.grounded_children(parent_ix);
+        assert_eq!(children.len(), 3);
+        tree.tree[children[0]].set_geometry(ResizeEdge::empty(), Geometry {
+            origin: parent_geometry.origin,
+            size: Size { w: parent_geometry.size.w - 20, h: parent_geometry.size.h }
+        });
+        tree.rebalance_children(parent_ix);
+        let width = |tree: &LayoutTree, child_ix| tree.tree[child_ix]
+            .get_geometry().unwrap().size.w as i32;
+        assert!(width(&tree, children[0]) > width(&tree, children[1]) + 1);
+        let root_c_ix = tree.tree.parent_of(parent_ix).unwrap();
+        let root_c_id = tree.tree[root_c_ix].get_id();
+        tree.equalize(root_c_id, true).unwrap();
+        let parent_w = tree.tree[parent_ix].get_geometry().unwrap().size.w as i32;
+        for child_ix in children {
+            assert!((width(&tree, child_ix) - parent_w / 3).abs() <= 1);
+        }
+        tree.validate();
+    }
+
+    #[test]
+    fn cycle_tiling_mode_goes_round() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let view_ix = tree.active_container.unwrap();
+        let view_id = tree.tree[view_ix].get_id();
+        let parent_ix = tree.tree.parent_of(view_ix).unwrap();
+        let start = tree.tree[parent_ix].get_layout().unwrap();
+        let mut seen = vec![start];
+        for _ in 0..4 {
+            tree.cycle_tiling_mode(view_id).unwrap();
+            seen.push(tree.tree[parent_ix].get_layout().unwrap());
+        }
+        assert_eq!(seen[4], start);
+        for layout in &[Layout::Horizontal, Layout::Vertical,
+                        Layout::Tabbed, Layout::Stacked] {
+            assert!(seen.contains(layout));
+        }
+        tree.validate();
+    }
+
+    #[test]
+    fn pending_split_wraps_next_view() {
+        let mut tree = basic_tree();
+        let id = tree.get_active_container().unwrap().get_id();
+        let root_ix = tree.tree.parent_of(tree.active_container.unwrap()).unwrap();
+        tree.set_split_direction(id, Layout::Vertical).unwrap();
+        // Nothing happens until a view is added
+        assert_eq!(tree.tree.parent_of(tree.active_container.unwrap()), Ok(root_ix));
+        let new_id = tree.add_view(WlcView::root()).unwrap().get_id();
+        let new_ix = tree.tree.lookup_id(new_id).unwrap();
+        let split_ix = tree.tree.parent_of(new_ix).unwrap();
+        assert!(split_ix != root_ix);
+        assert_eq!(tree.tree[split_ix].get_layout(), Ok(Layout::Vertical));
+        assert_eq!(tree.tree.parent_of(tree.tree.lookup_id(id).unwrap()), Ok(split_ix));
+        assert_eq!(tree.tree.children_of(root_ix), vec![split_ix]);
+        // It's only used once
+        tree.add_view(WlcView::root()).unwrap();
+        assert_eq!(tree.tree.children_of(split_ix).len(), 3);
+
+        // Moving the focus away forgets it
+        tree.switch_to_workspace("2");
+        let active_ix = tree.active_container.unwrap();
+        let parent_ix = tree.tree.parent_of(active_ix).unwrap();
+        let id = tree.tree[active_ix].get_id();
+        tree.set_split_direction(id, Layout::Horizontal).unwrap();
+        let other_ix = tree.tree.children_of(parent_ix).into_iter()
+            .find(|&child_ix| child_ix != active_ix).unwrap();
+        tree.set_active_node(other_ix).unwrap();
+        tree.add_view(WlcView::root()).unwrap();
+        assert_eq!(tree.tree.children_of(parent_ix).len(), 3);
+    }
+
+    #[test]
+    fn auto_orientation_follows_the_shape() {
+        let mut tree = basic_tree();
+        tree.set_default_orientation(Orientation::Auto);
+        let active_ix = tree.active_container.unwrap();
+        let parent_ix = tree.tree.parent_of(active_ix).unwrap();
+        let geometry = |w, h| Geometry {
+            origin: Point { x: 0, y: 0 },
+            size: Size { w: w, h: h }
+        };
+        tree.tree[active_ix].set_geometry(ResizeEdge::empty(), geometry(100, 300));
+        tree.split_active().unwrap();
+        assert_eq!(tree.tree[parent_ix].get_layout(), Ok(Layout::Vertical));
+        tree.tree[active_ix].set_geometry(ResizeEdge::empty(), geometry(300, 100));
+        tree.split_active().unwrap();
+        assert_eq!(tree.tree[parent_ix].get_layout(), Ok(Layout::Horizontal));
+        // New workspaces are oriented by the shape of their output
+        let output_ix = tree.active_ix_of(ContainerType::Output).unwrap();
+        let output_geometry = tree.tree[output_ix].get_geometry().unwrap();
+        let expected = if output_geometry.size.w >= output_geometry.size.h {
+            Layout::Horizontal
+        } else {
+            Layout::Vertical
+        };
+        let root_c_ix = tree.init_workspace("auto".into(), output_ix);
+        assert_eq!(tree.tree[root_c_ix].get_layout(), Ok(expected));
+        // A fixed orientation ignores the shape
+        tree.set_default_orientation(Orientation::Vertical);
+        tree.split_active().unwrap();
+        assert_eq!(tree.tree[parent_ix].get_layout(), Ok(Layout::Vertical));
+        let root_c_ix = tree.init_workspace("vertical".into(), output_ix);
+        assert_eq!(tree.tree[root_c_ix].get_layout(), Ok(Layout::Vertical));
+    }
+
+    #[test]
+    fn floating_geometry_is_remembered() {
+        let mut tree = basic_tree();
+        let id = tree.get_active_container().unwrap().get_id();
+        let workspace_ix = tree.tree.workspace_ix_by_name("1").unwrap();
+        let workspace_geometry = tree.tree[workspace_ix].get_geometry().unwrap();
+        // The first time it's centered at 75% of the workspace
+        tree.float_container(id).unwrap();
+        let geometry = tree.lookup(id).unwrap().get_geometry().unwrap();
+        assert_eq!(geometry.size.w, workspace_geometry.size.w * 3 / 4);
+        assert_eq!(geometry.size.h, workspace_geometry.size.h * 3 / 4);
+        assert_eq!(geometry.origin.x, workspace_geometry.origin.x +
+                   (workspace_geometry.size.w - geometry.size.w) as i32 / 2);
+
+        let moved = Geometry {
+            origin: Point { x: 10, y: 20 },
+            size: Size { w: 100, h: 200 }
+        };
+        tree.lookup_mut(id).unwrap().set_geometry(ResizeEdge::empty(), moved);
+        tree.ground_container(id).unwrap();
+        assert!(!tree.lookup(id).unwrap().floating());
+        tree.float_container(id).unwrap();
+        assert_eq!(tree.lookup(id).unwrap().get_geometry(), Some(moved));
+    }
+
+    #[test]
+    fn stacked_geometry_reserves_a_title_bar_per_child() {
+        let geometry = Geometry {
+            origin: Point { x: 10, y: 20 },
+            size: Size { w: 300, h: 400 }
+        };
+        let (title_bars, body) = LayoutTree::stacked_geometry(geometry, 15, 3);
+        assert_eq!(title_bars.len(), 3);
+        for (index, title_bar) in title_bars.iter().enumerate() {
+            assert_eq!(title_bar.origin, Point { x: 10, y: 20 + 15 * index as i32 });
+            assert_eq!(title_bar.size, Size { w: 300, h: 15 });
+        }
+        // The focused child starts below all three title bars
+        assert_eq!(body.origin, Point { x: 10, y: 20 + 15 * 3 });
+        assert_eq!(body.size, Size { w: 300, h: 400 - 15 * 3 });
+        // Never more than the whole area
+        let (_, body) = LayoutTree::stacked_geometry(geometry, 300, 3);
+        assert_eq!(body.size.h, 0);
+    }
+
+    #[test]
+    fn stacked_children_share_the_body() {
+        let mut tree = basic_tree();
+        tree.add_view(WlcView::root()).unwrap();
+        tree.add_view(WlcView::root()).unwrap();
+        let active_ix = tree.active_container.unwrap();
+        let parent_ix = tree.tree.parent_of(active_ix).unwrap();
+        tree.set_active_layout(Layout::Stacked).unwrap();
+        let children = tree.tree.grounded_children(parent_ix);
+        assert_eq!(children.len(), 3);
+        let parent_geometry = tree.tree[parent_ix].get_geometry().unwrap();
+        let focused = tree.tree[active_ix].get_geometry().unwrap();
+        assert!(focused.origin.y >= parent_geometry.origin.y);
+        for child_ix in children {
+            assert_eq!(tree.tree[child_ix].get_geometry().unwrap(), focused);
+        }
+    }
 
     #[test]
     /// Ensure that calculate_scale is fair to all it's children
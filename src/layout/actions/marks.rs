@@ -0,0 +1,125 @@
+use uuid::Uuid;
+
+use super::super::LayoutTree;
+use super::super::commands::CommandResult;
+use super::super::core::TreeError;
+use super::super::core::container::ContainerType;
+
+impl LayoutTree {
+    /// Marks the container behind the UUID with the given name.
+    ///
+    /// Marks are unique, so if another container already has this mark it is
+    /// moved to this container. If `toggle` is set and the container already
+    /// has the mark, the mark is removed instead.
+    pub fn mark_container(&mut self, id: Uuid, mark: String, toggle: bool) -> CommandResult {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        let has_mark = self.tree[node_ix].marks()
+            .ok_or(TreeError::UuidWrongType(id, vec!(ContainerType::View,
+                                                     ContainerType::Container)))?
+            .contains(&mark);
+        if has_mark {
+            if toggle {
+                self.tree[node_ix].marks_mut()
+                    .expect("Container could not be marked").remove(&mark);
+            }
+            return Ok(())
+        }
+        if let Some(old_id) = self.find_marked(&mark) {
+            let old_ix = self.tree.lookup_id(old_id)
+                .ok_or(TreeError::NodeNotFound(old_id))?;
+            self.tree[old_ix].marks_mut()
+                .expect("Marked container could not be marked").remove(&mark);
+        }
+        self.tree[node_ix].marks_mut()
+            .expect("Container could not be marked").insert(mark);
+        Ok(())
+    }
+
+    /// Finds the container that holds the mark, if there is one.
+    ///
+    /// Views in the scratchpad are included, so they keep their marks unique.
+    pub fn find_marked(&self, mark: &str) -> Option<Uuid> {
+        self.all_descendants_with_scratchpad().into_iter()
+            .find(|&node_ix| self.tree[node_ix].marks()
+                  .map(|marks| marks.contains(mark))
+                  .unwrap_or(false))
+            .map(|node_ix| self.tree[node_ix].get_id())
+    }
+
+    /// Focuses on the container that holds the mark.
+    pub fn focus_mark(&mut self, mark: &str) -> CommandResult {
+        let id = self.find_marked(mark)
+            .ok_or_else(|| TreeError::MarkNotFound(mark.into()))?;
+        self.set_active_container(id)
+            .or_else(|_| self.focus_on(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::core::tree::tests::basic_tree;
+    use super::super::super::core::TreeError;
+    use super::super::criteria::Criteria;
+
+    #[test]
+    fn marks_are_unique() {
+        let mut tree = basic_tree();
+        let ws_1_id = tree.get_active_container().unwrap().get_id();
+        tree.mark_container(ws_1_id, "a".into(), false).unwrap();
+        assert_eq!(tree.find_marked("a"), Some(ws_1_id));
+        tree.switch_to_workspace("2");
+        let ws_2_id = tree.get_active_container().unwrap().get_id();
+        // Marking another container moves the mark
+        tree.mark_container(ws_2_id, "a".into(), false).unwrap();
+        assert_eq!(tree.find_marked("a"), Some(ws_2_id));
+        assert!(tree.lookup(ws_1_id).unwrap().marks().unwrap().is_empty());
+        // Toggling it removes it
+        tree.mark_container(ws_2_id, "a".into(), true).unwrap();
+        assert_eq!(tree.find_marked("a"), None);
+        assert_eq!(tree.focus_mark("a"), Err(TreeError::MarkNotFound("a".into())));
+    }
+
+    #[test]
+    fn focus_mark_test() {
+        let mut tree = basic_tree();
+        let ws_1_id = tree.get_active_container().unwrap().get_id();
+        tree.mark_container(ws_1_id, "home".into(), false).unwrap();
+        tree.switch_to_workspace("2");
+        assert!(tree.get_active_container().unwrap().get_id() != ws_1_id);
+        tree.focus_mark("home").unwrap();
+        assert_eq!(tree.get_active_container().unwrap().get_id(), ws_1_id);
+    }
+
+    #[test]
+    fn removing_clears_marks() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let active_ix = tree.active_container.unwrap();
+        let id = tree.tree[active_ix].get_id();
+        tree.mark_container(id, "gone".into(), false).unwrap();
+        let container = tree.remove_view_or_container(active_ix).unwrap();
+        assert!(container.marks().unwrap().is_empty());
+        assert_eq!(tree.find_marked("gone"), None);
+    }
+
+    #[test]
+    fn marks_are_unique_with_the_scratchpad() {
+        let mut tree = basic_tree();
+        let ws_1_id = tree.get_active_container().unwrap().get_id();
+        tree.mark_container(ws_1_id, "a".into(), false).unwrap();
+        tree.move_to_scratchpad(ws_1_id).unwrap();
+        assert_eq!(tree.find_marked("a"), Some(ws_1_id));
+        let criteria = Criteria {
+            mark: Some("a".into()),
+            .. Criteria::default()
+        };
+        assert_eq!(tree.containers_matching(&criteria), vec![ws_1_id]);
+        // Marking another view takes the mark from the hidden one
+        tree.switch_to_workspace("2");
+        let ws_2_id = tree.get_active_container().unwrap().get_id();
+        tree.mark_container(ws_2_id, "a".into(), false).unwrap();
+        assert!(tree.lookup(ws_1_id).unwrap().marks().unwrap().is_empty());
+        assert_eq!(tree.containers_matching(&criteria), vec![ws_2_id]);
+    }
+}
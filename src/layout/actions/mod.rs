@@ -6,3 +6,13 @@ pub mod resize;
 pub mod pointer;
 pub mod background;
 pub mod borders;
+pub mod dispatch;
+pub mod criteria;
+pub mod assign;
+pub mod marks;
+pub mod scratchpad;
+pub mod snapshot;
+pub mod transient;
+pub mod stacking;
+pub mod transaction;
+pub mod opacity;
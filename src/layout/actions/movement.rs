@@ -1,4 +1,8 @@
-use rustwlc::{Point, ResizeEdge};
+use std::error::Error;
+use std::fmt;
+use std::ops::Deref;
+
+use rustwlc::{Geometry, Point, ResizeEdge, Size};
 use uuid::Uuid;
 use petgraph::graph::NodeIndex;
 
@@ -8,6 +12,10 @@ use super::super::core::{Direction, ShiftDirection, TreeError};
 use super::super::core::container::{Container, ContainerType, ContainerErr,
                                     Handle, Layout};
 
+/// The fractions of the workspace a floating view cycles through when it's
+/// snapped to the same edge again, see `snap_floating`.
+const SNAP_FRACTIONS: [(u32, u32); 3] = [(1, 2), (2, 3), (1, 3)];
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum MovementError {
     /// Attempted to move the node behind the UUID in the given direction,
@@ -17,7 +25,33 @@ pub enum MovementError {
     /// up back through the caller.
     Tree(Box<TreeError>),
     /// Expected the view to be floating, but it was not
-    NotFloating(NodeIndex)
+    NotFloating(NodeIndex),
+    /// The container behind the UUID is directly in the root container of
+    /// its workspace, so it can't be moved up any further.
+    AlreadyAtTop(Uuid)
+}
+
+impl fmt::Display for MovementError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MovementError::MoveOutsideSiblings(id, direction) =>
+                write!(f, "moving {} {} would leave its siblings", id, direction),
+            MovementError::Tree(ref err) => write!(f, "{}", err),
+            MovementError::NotFloating(node_ix) =>
+                write!(f, "node {} is not floating", node_ix.index()),
+            MovementError::AlreadyAtTop(id) =>
+                write!(f, "{} is already at the top of its workspace", id)
+        }
+    }
+}
+
+impl Error for MovementError {
+    fn source(&self) -> Option<&(Error + 'static)> {
+        match *self {
+            MovementError::Tree(ref err) => Some(&**err),
+            _ => None
+        }
+    }
 }
 
 impl From<ContainerErr> for MovementError {
@@ -57,6 +91,108 @@ impl LayoutTree {
         Ok(())
     }
 
+    /// Moves the container behind the UUID out of its parent, placing it
+    /// just after its old parent.
+    ///
+    /// If that leaves the old parent empty it is removed. Containers directly
+    /// in the root container of a workspace can't be promoted.
+    pub fn promote(&mut self, id: Uuid) -> CommandResult {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        match self.tree[node_ix].get_type() {
+            ContainerType::View | ContainerType::Container => {},
+            _ => return Err(TreeError::UuidWrongType(id, vec!(ContainerType::View,
+                                                              ContainerType::Container)))
+        }
+        if self.tree.is_root_container(node_ix) {
+            return Err(TreeError::InvalidOperationOnRootContainer(id))
+        }
+        if self.tree[node_ix].floating() {
+            return Err(TreeError::ContainerWasFloating(node_ix))
+        }
+        let parent_ix = self.tree.parent_of(node_ix)?;
+        if self.tree.is_root_container(parent_ix) {
+            return Err(TreeError::Movement(MovementError::AlreadyAtTop(id)))
+        }
+        let grandparent_ix = self.tree.parent_of(parent_ix)?;
+        let pos = *self.tree.get_edge_weight_between(grandparent_ix, parent_ix)
+            .expect("Parent had no edge weight").deref() + 1;
+        let was_on_path = self.tree.on_path(node_ix);
+        self.tree.move_into(node_ix, grandparent_ix)?;
+        self.tree.set_child_pos(node_ix, pos);
+        if !was_on_path {
+            // Moving it made it the focused child, point back at what was
+            if let Some(active_ix) = self.active_container {
+                self.tree.set_ancestor_paths_active(active_ix);
+            }
+        }
+        if self.tree.can_remove_empty_parent(parent_ix) {
+            self.remove_container(parent_ix)?;
+        }
+        let draw_title = match self.tree[grandparent_ix].get_layout()? {
+            Layout::Tabbed | Layout::Stacked => false,
+            Layout::Horizontal | Layout::Vertical => true
+        };
+        match self.tree[node_ix] {
+            Container::View { ref mut borders, .. } => {
+                borders.as_mut().map(|b| b.draw_title = draw_title);
+            },
+            _ => {}
+        }
+        self.layout(grandparent_ix);
+        self.validate();
+        Ok(())
+    }
+
+    /// Removes every container in the workspace that has only one child,
+    /// putting the child where the container was.
+    ///
+    /// The root container of the workspace is always kept, and whatever
+    /// was focused stays focused.
+    pub fn flatten(&mut self, workspace: &str) -> CommandResult {
+        let workspace_ix = self.lookup_workspace(workspace)?;
+        let mut active_ix = self.active_container;
+        loop {
+            let single_ix = self.tree.all_descendants_of(workspace_ix).into_iter()
+                .find(|&node_ix| self.tree[node_ix].get_type() == ContainerType::Container
+                      && !self.tree.is_root_container(node_ix)
+                      && self.tree.children_of(node_ix).len() == 1);
+            let container_ix = match single_ix {
+                Some(container_ix) => container_ix,
+                None => break
+            };
+            let child_ix = self.tree.children_of(container_ix)[0];
+            let parent_ix = self.tree.parent_of(container_ix)?;
+            let pos = *self.tree.get_edge_weight_between(parent_ix, container_ix)
+                .expect("Container had no edge weight").deref();
+            trace!("Flattening {:?} into {:?}", child_ix, parent_ix);
+            self.tree.move_into(child_ix, parent_ix)?;
+            self.tree.set_child_pos(child_ix, pos);
+            if active_ix == Some(container_ix) {
+                active_ix = Some(child_ix);
+            }
+            self.remove_node_no_refocus(container_ix)?;
+            let draw_title = match self.tree[parent_ix].get_layout()? {
+                Layout::Tabbed | Layout::Stacked => false,
+                Layout::Horizontal | Layout::Vertical => true
+            };
+            match self.tree[child_ix] {
+                Container::View { ref mut borders, .. } => {
+                    borders.as_mut().map(|b| b.draw_title = draw_title);
+                },
+                _ => {}
+            }
+        }
+        // Moving the children around changed the active path
+        if let Some(active_ix) = active_ix {
+            self.tree.set_ancestor_paths_active(active_ix);
+        }
+        self.active_container = active_ix;
+        self.layout(workspace_ix);
+        self.validate();
+        Ok(())
+    }
+
     /// Returns the new parent of the active container if the move succeeds,
     /// Otherwise it signals what error occurred in the tree.
     fn move_recurse(&mut self, node_to_move: NodeIndex, move_ancestor: Option<NodeIndex>,
@@ -290,13 +426,437 @@ impl LayoutTree {
         container.draw_borders()?;
         Ok(())
     }
+
+    /// Swaps the positions of the two containers behind the UUIDs.
+    ///
+    /// Both must be a view or a (non-root) container, and neither can be an
+    /// ancestor of the other. If either was the active container it stays
+    /// active in its new position, so if it ends up in another workspace
+    /// that workspace is switched to.
+    pub fn swap_containers(&mut self, a: Uuid, b: Uuid) -> CommandResult {
+        let a_ix = self.tree.lookup_id(a)
+            .ok_or(TreeError::NodeNotFound(a))?;
+        let b_ix = self.tree.lookup_id(b)
+            .ok_or(TreeError::NodeNotFound(b))?;
+        for &(node_ix, node_id) in &[(a_ix, a), (b_ix, b)] {
+            match self.tree[node_ix].get_type() {
+                ContainerType::View | ContainerType::Container => {},
+                _ => return Err(TreeError::UuidWrongType(node_id, vec!(
+                    ContainerType::View, ContainerType::Container)))
+            }
+            if self.tree.is_root_container(node_ix) {
+                return Err(TreeError::InvalidOperationOnRootContainer(node_id))
+            }
+        }
+        if a_ix == b_ix {
+            return Ok(())
+        }
+        if self.is_ancestor(a_ix, b_ix) || self.is_ancestor(b_ix, a_ix) {
+            return Err(TreeError::SwapWithDescendant(a, b))
+        }
+        let a_workspace_ix = self.tree
+            .ancestor_of_type(a_ix, ContainerType::Workspace)?;
+        let b_workspace_ix = self.tree
+            .ancestor_of_type(b_ix, ContainerType::Workspace)?;
+        let cross_workspace = a_workspace_ix != b_workspace_ix;
+        let active_ix = match self.active_container {
+            Some(active_ix) if active_ix == a_ix || active_ix == b_ix => Some(active_ix),
+            _ => None
+        };
+        if cross_workspace {
+            if let Some(active_ix) = active_ix {
+                // Follow the active container to the workspace it's moving to
+                let target_ix = if active_ix == a_ix { b_workspace_ix } else { a_workspace_ix };
+                let name = self.tree[target_ix].get_name()
+                    .expect("Workspace had no name").to_string();
+                self.switch_to_workspace(&name);
+            }
+        }
+        self.tree.swap_nodes(a_ix, b_ix)?;
+        if cross_workspace {
+            // The swapped containers take on the visibility of their new workspace
+            let a_visible = self.workspace_visible(b_workspace_ix)?;
+            let b_visible = self.workspace_visible(a_workspace_ix)?;
+            self.set_container_visibility(a_ix, a_visible);
+            self.set_container_visibility(b_ix, b_visible);
+        }
+        if let Some(active_ix) = active_ix {
+            self.tree.set_ancestor_paths_active(active_ix);
+            self.set_active_node(active_ix)?;
+        }
+        self.layout(a_workspace_ix);
+        if cross_workspace {
+            self.layout(b_workspace_ix);
+        }
+        self.validate();
+        self.validate_path();
+        Ok(())
+    }
+
+    /// Swaps the active container with the container at the given UUID.
+    ///
+    /// Focus stays on the active container in its new position.
+    pub fn swap_active_with(&mut self, id: Uuid) -> CommandResult {
+        let active_id = self.get_active_container()
+            .ok_or(TreeError::NoActiveContainer)?.get_id();
+        self.swap_containers(active_id, id)
+    }
+
+    /// Swaps the active container with the one that was focused before it,
+    /// so calling it again swaps them back.
+    ///
+    /// Containers in the focus history that have been removed since, or
+    /// that can't be swapped with the active one, are skipped.
+    pub fn swap_with_last_focused(&mut self) -> CommandResult {
+        let active_ix = self.active_container
+            .ok_or(TreeError::NoActiveContainer)?;
+        let active_id = self.tree[active_ix].get_id();
+        let previous_id = self.focus_history.iter().rev()
+            .filter_map(|&id| self.tree.lookup_id(id).map(|node_ix| (node_ix, id)))
+            .find(|&(node_ix, _)| {
+                let swappable = match self.tree[node_ix].get_type() {
+                    ContainerType::View | ContainerType::Container => true,
+                    _ => false
+                };
+                let in_scratchpad = self.tree
+                    .ancestor_of_type(node_ix, ContainerType::Workspace)
+                    .map(|workspace_ix| self.is_scratchpad(workspace_ix))
+                    .unwrap_or(true);
+                swappable && !in_scratchpad &&
+                    !self.tree.is_root_container(node_ix) &&
+                    !self.is_ancestor(node_ix, active_ix) &&
+                    !self.is_ancestor(active_ix, node_ix) &&
+                    node_ix != active_ix
+            })
+            .map(|(_, id)| id)
+            .ok_or(TreeError::NoPreviousFocus)?;
+        self.swap_containers(active_id, previous_id)
+    }
+
+    /// Moves the container behind the UUID in the given direction, like i3's
+    /// `move left/right/up/down`.
+    ///
+    /// It is swapped with its sibling in that direction, or inserted into
+    /// the neighbouring container if that parent is oriented differently.
+    /// If it is already at the edge of its workspace it is sent to the
+    /// output in that direction, if there is one.
+    /// If the container was focused it stays focused.
+    pub fn move_container_in_dir(&mut self, id: Uuid, dir: Direction) -> CommandResult {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        match self.tree[node_ix].get_type() {
+            ContainerType::View | ContainerType::Container => {},
+            _ => return Err(TreeError::UuidWrongType(id, vec!(ContainerType::View,
+                                                              ContainerType::Container)))
+        }
+        if self.tree.is_root_container(node_ix) {
+            return Err(TreeError::InvalidOperationOnRootContainer(id))
+        }
+        if self.tree[node_ix].floating() {
+            return Err(TreeError::ContainerWasFloating(node_ix))
+        }
+        let was_active = self.active_container == Some(node_ix);
+        let next_output = if self.at_workspace_edge(node_ix, dir) {
+            let output_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Output)?;
+            self.output_in_dir(output_ix, dir)
+        } else {
+            None
+        };
+        match next_output {
+            Some(output_ix) => {
+                let output = match self.tree[output_ix] {
+                    Container::Output { handle, .. } => handle,
+                    _ => unreachable!()
+                };
+                self.send_to_output(id, output)?;
+            },
+            None => self.move_container(id, dir)?
+        }
+        if was_active {
+            // NOTE refresh node_ix because it probably moved.
+            let node_ix = self.tree.lookup_id(id)
+                .ok_or(TreeError::NodeNotFound(id))?;
+            self.set_active_node(node_ix)?;
+        }
+        self.validate();
+        Ok(())
+    }
+
+    /// Snaps the floating view behind the UUID to an edge of its workspace,
+    /// filling half of it.
+    ///
+    /// Snapping it to the same edge again cycles it through two thirds and
+    /// one third of the workspace. If it's already snapped to a perpendicular
+    /// edge it keeps that size, so snapping left and then up puts it in the
+    /// top left quarter.
+    ///
+    /// The workspace area used does not include the outer gaps.
+    pub fn snap_floating(&mut self, id: Uuid, dir: Direction) -> CommandResult {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        if self.tree[node_ix].get_type() != ContainerType::View {
+            return Err(TreeError::UuidWrongType(id, vec!(ContainerType::View)))
+        }
+        if !self.tree[node_ix].floating() {
+            return Err(TreeError::ContainerWasNotFloating(node_ix))
+        }
+        let workspace_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)?;
+        let mut area = self.tree[workspace_ix].get_geometry()
+            .expect("Workspace had no geometry");
+        let gap = self.outer_gap;
+        area.origin.x += gap as i32;
+        area.origin.y += gap as i32;
+        area.size.w = area.size.w.saturating_sub(gap * 2);
+        area.size.h = area.size.h.saturating_sub(gap * 2);
+        let current = self.tree[node_ix].get_geometry()
+            .expect("View had no geometry");
+        let geometry = snap_geometry(area, current, dir);
+        let container = &mut self.tree[node_ix];
+        container.set_geometry(ResizeEdge::empty(), geometry);
+        container.resize_borders(geometry);
+        container.draw_borders()?;
+        Ok(())
+    }
+
+    /// Gets the container next to the one behind the UUID in the direction,
+    /// like `container_in_dir`, but if there is nothing else in that direction
+    /// in its workspace it looks at the next output over instead.
+    ///
+    /// On the other output, the view in its visible workspace closest to
+    /// the edge being crossed is returned, preferring views lined up with
+    /// the container. If there is no output in that direction either,
+    /// the error from `container_in_dir` is returned.
+    pub fn container_in_dir_cross_output(&self, id: Uuid, dir: Direction)
+                                         -> Result<Uuid, TreeError> {
+        let err = match self.container_in_dir(id, dir) {
+            Ok((_, sibling_id)) => return Ok(sibling_id),
+            Err(err) => err
+        };
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        let output_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Output)?;
+        let other_ix = match self.output_in_dir(output_ix, dir) {
+            Some(other_ix) => other_ix,
+            None => return Err(err)
+        };
+        let workspace_ix = match self.tree.follow_path_until(other_ix, ContainerType::Workspace) {
+            Ok(workspace_ix) => workspace_ix,
+            Err(_) => return Err(err)
+        };
+        let geometry = self.tree[node_ix].get_geometry()
+            .expect("Container had no geometry");
+        let center = |geometry: Geometry| (geometry.origin.x + geometry.size.w as i32 / 2,
+                                           geometry.origin.y + geometry.size.h as i32 / 2);
+        let (center_x, center_y) = center(geometry);
+        self.tree.all_descendants_of(workspace_ix).into_iter()
+            .filter(|&view_ix| self.tree[view_ix].get_type() == ContainerType::View)
+            .filter_map(|view_ix| {
+                let other = match self.tree[view_ix].get_geometry() {
+                    Some(other) => other,
+                    None => return None
+                };
+                let (other_x, other_y) = center(other);
+                // How far it is from the edge that was crossed,
+                // then how far it is from being lined up with the container.
+                let distance = match dir {
+                    Direction::Left => (-(other.origin.x + other.size.w as i32),
+                                        (other_y - center_y).abs()),
+                    Direction::Right => (other.origin.x, (other_y - center_y).abs()),
+                    Direction::Up => (-(other.origin.y + other.size.h as i32),
+                                      (other_x - center_x).abs()),
+                    Direction::Down => (other.origin.y, (other_x - center_x).abs())
+                };
+                Some((distance, view_ix))
+            })
+            .min()
+            .map(|(_, view_ix)| self.tree[view_ix].get_id())
+            .ok_or(err)
+    }
+
+    /// Determines if there is nothing else in the workspace in the given
+    /// direction from the node.
+    fn at_workspace_edge(&self, node_ix: NodeIndex, dir: Direction) -> bool {
+        let mut cur_ix = node_ix;
+        while !self.tree.is_root_container(cur_ix) {
+            let parent_ix = match self.tree.parent_of(cur_ix) {
+                Ok(parent_ix) => parent_ix,
+                Err(_) => return true
+            };
+            let along_parent = match (self.tree[parent_ix].get_layout(), dir) {
+                (Ok(Layout::Horizontal), Direction::Left) |
+                (Ok(Layout::Horizontal), Direction::Right) |
+                (Ok(Layout::Tabbed), Direction::Left) |
+                (Ok(Layout::Tabbed), Direction::Right) |
+                (Ok(Layout::Vertical), Direction::Up) |
+                (Ok(Layout::Vertical), Direction::Down) |
+                (Ok(Layout::Stacked), Direction::Up) |
+                (Ok(Layout::Stacked), Direction::Down) => true,
+                _ => false
+            };
+            if along_parent {
+                let siblings = self.tree.grounded_children(parent_ix);
+                let edge_ix = match dir {
+                    Direction::Left | Direction::Up => siblings.first(),
+                    Direction::Right | Direction::Down => siblings.last()
+                };
+                if edge_ix != Some(&cur_ix) {
+                    return false
+                }
+            }
+            cur_ix = parent_ix;
+        }
+        true
+    }
+
+    /// Gets the closest output in the given direction from the output.
+    fn output_in_dir(&self, output_ix: NodeIndex, dir: Direction) -> Option<NodeIndex> {
+        let geometry = self.tree[output_ix].get_geometry()
+            .expect("Output had no geometry");
+        let (x, y) = (geometry.origin.x, geometry.origin.y);
+        let (w, h) = (geometry.size.w as i32, geometry.size.h as i32);
+        let root_ix = self.tree.root_ix();
+        self.tree.children_of(root_ix).into_iter()
+            .filter(|&other_ix| other_ix != output_ix)
+            .filter_map(|other_ix| {
+                let other = self.tree[other_ix].get_geometry()
+                    .expect("Output had no geometry");
+                let (o_x, o_y) = (other.origin.x, other.origin.y);
+                let (o_w, o_h) = (other.size.w as i32, other.size.h as i32);
+                let overlaps_x = o_x < x + w && x < o_x + o_w;
+                let overlaps_y = o_y < y + h && y < o_y + o_h;
+                let distance = match dir {
+                    Direction::Left if overlaps_y && o_x + o_w <= x => x - (o_x + o_w),
+                    Direction::Right if overlaps_y && o_x >= x + w => o_x - (x + w),
+                    Direction::Up if overlaps_x && o_y + o_h <= y => y - (o_y + o_h),
+                    Direction::Down if overlaps_x && o_y >= y + h => o_y - (y + h),
+                    _ => return None
+                };
+                Some((distance, other_ix))
+            })
+            .min()
+            .map(|(_, other_ix)| other_ix)
+    }
+
+    /// Determines if the workspace is the one being shown on its output.
+    pub fn workspace_visible(&self, workspace_ix: NodeIndex) -> Result<bool, TreeError> {
+        let output_ix = self.tree.parent_of(workspace_ix)?;
+        Ok(self.tree.next_active_node(output_ix) == Some(workspace_ix))
+    }
+
+    /// Determines if `ancestor_ix` is a strict ancestor of `node_ix`.
+    fn is_ancestor(&self, ancestor_ix: NodeIndex, node_ix: NodeIndex) -> bool {
+        let mut cur_ix = node_ix;
+        while let Ok(parent_ix) = self.tree.parent_of(cur_ix) {
+            if parent_ix == ancestor_ix {
+                return true
+            }
+            cur_ix = parent_ix;
+        }
+        false
+    }
+
+    /// Determines if the container behind `child` is somewhere beneath the
+    /// container behind `ancestor`.
+    ///
+    /// A container is not a descendant of itself.
+    pub fn is_descendant_of(&self, child: Uuid, ancestor: Uuid) -> Result<bool, TreeError> {
+        let child_ix = self.tree.lookup_id(child)
+            .ok_or(TreeError::NodeNotFound(child))?;
+        let ancestor_ix = self.tree.lookup_id(ancestor)
+            .ok_or(TreeError::NodeNotFound(ancestor))?;
+        Ok(self.is_ancestor(ancestor_ix, child_ix))
+    }
+}
+
+/// Gets where a floating view at `current` goes when it's snapped to the
+/// edge of `area` in the direction.
+fn snap_geometry(area: Geometry, current: Geometry, dir: Direction) -> Geometry {
+    let is_snapped_length = |length: u32, total: u32| SNAP_FRACTIONS.iter()
+        .position(|&(num, den)| length == total * num / den);
+    // Snapping to the edge it's already snapped to moves on to the next size
+    let next_length = |length: u32, total: u32, aligned: bool| {
+        let (num, den) = match is_snapped_length(length, total) {
+            Some(index) if aligned => SNAP_FRACTIONS[(index + 1) % SNAP_FRACTIONS.len()],
+            _ => SNAP_FRACTIONS[0]
+        };
+        total * num / den
+    };
+    // Along the other axis it keeps its place if it's snapped there,
+    // otherwise it fills the whole area
+    let other_axis = |start: i32, length: u32, area_start: i32, total: u32| {
+        let aligned = start == area_start ||
+            start + length as i32 == area_start + total as i32;
+        if aligned && is_snapped_length(length, total).is_some() {
+            (start, length)
+        } else {
+            (area_start, total)
+        }
+    };
+    let right = |geometry: Geometry| geometry.origin.x + geometry.size.w as i32;
+    let bottom = |geometry: Geometry| geometry.origin.y + geometry.size.h as i32;
+    match dir {
+        Direction::Left | Direction::Right => {
+            let aligned = match dir {
+                Direction::Left => current.origin.x == area.origin.x,
+                _ => right(current) == right(area)
+            };
+            let w = next_length(current.size.w, area.size.w, aligned);
+            let x = match dir {
+                Direction::Left => area.origin.x,
+                _ => right(area) - w as i32
+            };
+            let (y, h) = other_axis(current.origin.y, current.size.h,
+                                    area.origin.y, area.size.h);
+            Geometry { origin: Point { x: x, y: y }, size: Size { w: w, h: h } }
+        },
+        Direction::Up | Direction::Down => {
+            let aligned = match dir {
+                Direction::Up => current.origin.y == area.origin.y,
+                _ => bottom(current) == bottom(area)
+            };
+            let h = next_length(current.size.h, area.size.h, aligned);
+            let y = match dir {
+                Direction::Up => area.origin.y,
+                _ => bottom(area) - h as i32
+            };
+            let (x, w) = other_axis(current.origin.x, current.size.w,
+                                    area.origin.x, area.size.w);
+            Geometry { origin: Point { x: x, y: y }, size: Size { w: w, h: h } }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::super::super::core::tree::tests::basic_tree;
-    use super::super::super::{Direction, Container, ContainerType, Layout};
+    use super::super::super::{Direction, Container, ContainerType, Layout, LayoutTree};
+    use super::super::super::core::TreeError;
+    use super::MovementError;
     use rustwlc::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn move_container_in_dir_keeps_focus() {
+        let mut tree = basic_tree();
+        tree.add_view(WlcView::root()).unwrap();
+        let active_uuid = tree.get_active_container().unwrap().get_id();
+        let active_parent = tree.tree.parent_of(tree.active_container.unwrap()).unwrap();
+        tree.move_container_in_dir(active_uuid, Direction::Left).unwrap();
+        assert_eq!(tree.tree.children_of(active_parent)[0], tree.active_container.unwrap());
+        assert_eq!(tree.get_active_container().unwrap().get_id(), active_uuid);
+        // There's no output to the left, so it can't go any further
+        assert!(tree.move_container_in_dir(active_uuid, Direction::Left).is_err());
+        assert_eq!(tree.get_active_container().unwrap().get_id(), active_uuid);
+
+        // Moving a view that isn't focused leaves the focus alone
+        let other_ix = tree.tree.children_of(active_parent)[1];
+        let other_id = tree.tree[other_ix].get_id();
+        tree.move_container_in_dir(other_id, Direction::Left).unwrap();
+        assert_eq!(tree.tree.children_of(active_parent)[0], other_ix);
+        assert_eq!(tree.get_active_container().unwrap().get_id(), active_uuid);
+        assert!(tree.move_container_in_dir(tree.tree[active_parent].get_id(),
+                                           Direction::Left).is_err());
+    }
 
     #[test]
     fn test_basic_move() {
@@ -444,4 +1004,235 @@ mod tests {
         assert_eq!(tree.tree[children[0]].get_type(), ContainerType::View);
         assert_eq!(tree.tree[children[1]].get_type(), ContainerType::View);
     }
+
+    #[test]
+    fn swap_active_with_keeps_focus() {
+        let mut tree = basic_tree();
+        tree.add_view(WlcView::root()).unwrap();
+        let active_ix = tree.active_container.unwrap();
+        let active_id = tree.tree[active_ix].get_id();
+        let parent_ix = tree.tree.parent_of(active_ix).unwrap();
+        let children = tree.tree.children_of(parent_ix);
+        assert_eq!(children[1], active_ix);
+        let other_id = tree.tree[children[0]].get_id();
+        tree.swap_active_with(other_id).unwrap();
+        let active_ix = tree.active_container.unwrap();
+        assert_eq!(tree.tree[active_ix].get_id(), active_id);
+        let children = tree.tree.children_of(parent_ix);
+        assert_eq!(children[0], active_ix);
+        assert_eq!(tree.tree[children[1]].get_id(), other_id);
+        // Can't swap with an ancestor of the active container
+        tree.switch_to_workspace("2");
+        let active_ix = tree.active_container.unwrap();
+        let active_id = tree.tree[active_ix].get_id();
+        let parent_ix = tree.tree.parent_of(active_ix).unwrap();
+        let parent_id = tree.tree[parent_ix].get_id();
+        assert_eq!(tree.swap_active_with(parent_id),
+                   Err(TreeError::SwapWithDescendant(active_id, parent_id)));
+    }
+
+    #[test]
+    fn swap_with_last_focused_bounces() {
+        let mut tree = basic_tree();
+        assert_eq!(tree.swap_with_last_focused(), Err(TreeError::NoPreviousFocus));
+        let first_ix = tree.active_container.unwrap();
+        let first_id = tree.tree[first_ix].get_id();
+        let parent_ix = tree.tree.parent_of(first_ix).unwrap();
+        tree.add_view(WlcView::root()).unwrap();
+        let second_id = tree.get_active_container().unwrap().get_id();
+        // A removed container in the history is skipped
+        tree.add_view(WlcView::dummy(7)).unwrap();
+        tree.remove_view(WlcView::dummy(7)).unwrap();
+        tree.focus_on(second_id).unwrap();
+        let ids = |tree: &LayoutTree| tree.tree.children_of(parent_ix).iter()
+            .map(|&child_ix| tree.tree[child_ix].get_id())
+            .collect::<Vec<_>>();
+        assert_eq!(ids(&tree), vec![first_id, second_id]);
+        tree.swap_with_last_focused().unwrap();
+        assert_eq!(ids(&tree), vec![second_id, first_id]);
+        assert_eq!(tree.get_active_container().unwrap().get_id(), second_id);
+        tree.swap_with_last_focused().unwrap();
+        assert_eq!(ids(&tree), vec![first_id, second_id]);
+        assert_eq!(tree.get_active_container().unwrap().get_id(), second_id);
+    }
+
+    #[test]
+    fn promote_flattens_nested_containers() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let active_ix = tree.active_container.unwrap();
+        let active_id = tree.tree[active_ix].get_id();
+        let parent_ix = tree.tree.parent_of(active_ix).unwrap();
+        let parent_id = tree.tree[parent_ix].get_id();
+        let other_id = tree.tree.children_of(parent_ix).into_iter()
+            .map(|child_ix| tree.tree[child_ix].get_id())
+            .find(|&id| id != active_id).unwrap();
+        let root_c_ix = tree.tree.parent_of(parent_ix).unwrap();
+        let ids = |tree: &LayoutTree| tree.tree.children_of(root_c_ix).iter()
+            .map(|&child_ix| tree.tree[child_ix].get_id())
+            .collect::<Vec<_>>();
+        tree.promote(active_id).unwrap();
+        assert_eq!(ids(&tree), vec![parent_id, active_id]);
+        assert_eq!(tree.get_active_container().unwrap().get_id(), active_id);
+        assert_eq!(tree.promote(active_id),
+                   Err(TreeError::Movement(MovementError::AlreadyAtTop(active_id))));
+        // The emptied parent is cleaned up, focus doesn't move
+        tree.promote(other_id).unwrap();
+        assert_eq!(ids(&tree), vec![other_id, active_id]);
+        assert!(tree.tree.lookup_id(parent_id).is_none());
+        assert_eq!(tree.get_active_container().unwrap().get_id(), active_id);
+    }
+
+    #[test]
+    fn flatten_collapses_single_child_chains() {
+        let mut tree = basic_tree();
+        let view_ix = tree.active_container.unwrap();
+        let view_id = tree.tree[view_ix].get_id();
+        let root_c_ix = tree.tree.parent_of(view_ix).unwrap();
+        // Wrap the view in three containers, each with only one child
+        let geometry = tree.tree[root_c_ix].get_geometry().unwrap();
+        let mut parent_ix = root_c_ix;
+        for _ in 0..3 {
+            let container = Container::new_container(geometry, WlcView::dummy(0).as_output(),
+                                                     None);
+            let container_ix = tree.tree.add_child(parent_ix, container, false);
+            parent_ix = container_ix;
+        }
+        tree.tree.move_node(view_ix, parent_ix);
+        assert_eq!(tree.tree.children_of(root_c_ix).len(), 1);
+        tree.flatten("1").unwrap();
+        assert_eq!(tree.tree.children_of(root_c_ix), vec![view_ix]);
+        assert_eq!(tree.get_active_container().unwrap().get_id(), view_id);
+        tree.validate_path();
+        let workspace_ix = tree.tree.workspace_ix_by_name("1").unwrap();
+        assert_eq!(tree.tree.children_of(workspace_ix), vec![root_c_ix]);
+
+        // Only the single children are moved up, in the same order
+        tree.switch_to_workspace("2");
+        let active_ix = tree.active_container.unwrap();
+        let nested_ix = tree.tree.parent_of(active_ix).unwrap();
+        let root_c_ix = tree.tree.parent_of(nested_ix).unwrap();
+        let ids = tree.tree.children_of(nested_ix);
+        tree.flatten("2").unwrap();
+        assert_eq!(tree.tree.children_of(nested_ix), ids);
+        assert_eq!(tree.tree.children_of(root_c_ix), vec![nested_ix]);
+        assert_eq!(tree.active_container, Some(active_ix));
+        tree.validate_path();
+        assert!(tree.flatten("nope").is_err());
+    }
+
+    #[test]
+    fn swap_containers_across_workspaces() {
+        let mut tree = basic_tree();
+        let ws_1_view = tree.active_container.unwrap();
+        let ws_1_id = tree.tree[ws_1_view].get_id();
+        let ws_1_parent = tree.tree.parent_of(ws_1_view).unwrap();
+        tree.switch_to_workspace("2");
+        let active_ix = tree.active_container.unwrap();
+        let active_id = tree.tree[active_ix].get_id();
+        let active_parent = tree.tree.parent_of(active_ix).unwrap();
+        let other_ix = tree.tree.children_of(active_parent).into_iter()
+            .find(|&ix| ix != active_ix).unwrap();
+        let other_id = tree.tree[other_ix].get_id();
+        tree.swap_containers(active_id, ws_1_id).unwrap();
+        // The active container followed its new position to workspace 1
+        assert_eq!(tree.current_workspace(), Ok("1"));
+        assert_eq!(tree.active_container, Some(active_ix));
+        assert_eq!(tree.tree.parent_of(active_ix).unwrap(), ws_1_parent);
+        assert_eq!(tree.tree.parent_of(ws_1_view).unwrap(), active_parent);
+        // Swapping two nodes that aren't active leaves the focus alone
+        tree.swap_containers(ws_1_id, other_id).unwrap();
+        assert_eq!(tree.active_container, Some(active_ix));
+        assert_eq!(tree.current_workspace(), Ok("1"));
+        // Can't swap a container with its own children, in either order
+        let parent_id = tree.tree[active_parent].get_id();
+        assert_eq!(tree.swap_containers(parent_id, ws_1_id),
+                   Err(TreeError::SwapWithDescendant(parent_id, ws_1_id)));
+        assert_eq!(tree.swap_containers(ws_1_id, parent_id),
+                   Err(TreeError::SwapWithDescendant(ws_1_id, parent_id)));
+        // Root containers can't be swapped
+        let root_id = tree.tree[ws_1_parent].get_id();
+        assert_eq!(tree.swap_containers(root_id, ws_1_id),
+                   Err(TreeError::InvalidOperationOnRootContainer(root_id)));
+        tree.validate();
+        tree.validate_path();
+    }
+
+    #[test]
+    fn snap_floating_halves_and_quarters() {
+        let mut tree = basic_tree();
+        let id = tree.get_active_container().unwrap().get_id();
+        let workspace_ix = tree.tree.workspace_ix_by_name("1").unwrap();
+        let area = tree.tree[workspace_ix].get_geometry().unwrap();
+        tree.float_container(id).unwrap();
+        let geometry = |tree: &::layout::LayoutTree| tree.lookup(id).unwrap()
+            .get_geometry().unwrap();
+
+        tree.snap_floating(id, Direction::Left).unwrap();
+        assert_eq!(geometry(&tree), Geometry {
+            origin: area.origin,
+            size: Size { w: area.size.w / 2, h: area.size.h }
+        });
+        // Snapping the same way again cycles through the widths
+        tree.snap_floating(id, Direction::Left).unwrap();
+        assert_eq!(geometry(&tree).size.w, area.size.w * 2 / 3);
+        tree.snap_floating(id, Direction::Left).unwrap();
+        assert_eq!(geometry(&tree).size.w, area.size.w / 3);
+        tree.snap_floating(id, Direction::Left).unwrap();
+        assert_eq!(geometry(&tree).size.w, area.size.w / 2);
+        // Snapping up from the left half gives the top left quarter
+        tree.snap_floating(id, Direction::Up).unwrap();
+        assert_eq!(geometry(&tree), Geometry {
+            origin: area.origin,
+            size: Size { w: area.size.w / 2, h: area.size.h / 2 }
+        });
+        tree.snap_floating(id, Direction::Right).unwrap();
+        assert_eq!(geometry(&tree).origin.x,
+                   area.origin.x + (area.size.w - area.size.w / 2) as i32);
+        assert_eq!(geometry(&tree).size.h, area.size.h / 2);
+
+        // Only floating views can be snapped
+        tree.switch_to_workspace("2");
+        let tiled_ix = tree.active_container.unwrap();
+        let tiled_id = tree.tree[tiled_ix].get_id();
+        assert_eq!(tree.snap_floating(tiled_id, Direction::Left),
+                   Err(TreeError::ContainerWasNotFloating(tiled_ix)));
+    }
+
+    #[test]
+    fn is_descendant_of_test() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let view_ix = tree.active_container.unwrap();
+        let view_id = tree.tree[view_ix].get_id();
+        let parent_id = tree.tree[tree.tree.parent_of(view_ix).unwrap()].get_id();
+        let root_id = tree.tree[tree.tree.root_ix()].get_id();
+        assert_eq!(tree.is_descendant_of(view_id, parent_id), Ok(true));
+        assert_eq!(tree.is_descendant_of(view_id, root_id), Ok(true));
+        assert_eq!(tree.is_descendant_of(parent_id, view_id), Ok(false));
+        assert_eq!(tree.is_descendant_of(view_id, view_id), Ok(false));
+        let ws_1_ix = tree.tree.workspace_ix_by_name("1").unwrap();
+        let ws_1_id = tree.tree[ws_1_ix].get_id();
+        assert_eq!(tree.is_descendant_of(view_id, ws_1_id), Ok(false));
+        let missing = Uuid::new_v4();
+        assert_eq!(tree.is_descendant_of(missing, root_id),
+                   Err(TreeError::NodeNotFound(missing)));
+        assert_eq!(tree.is_descendant_of(view_id, missing),
+                   Err(TreeError::NodeNotFound(missing)));
+    }
+
+    #[test]
+    fn container_in_dir_cross_output_within_workspace() {
+        let mut tree = basic_tree();
+        let first_id = tree.get_active_container().unwrap().get_id();
+        let second_id = tree.add_view(WlcView::root()).unwrap().get_id();
+        assert_eq!(tree.container_in_dir_cross_output(first_id, Direction::Right),
+                   Ok(second_id));
+        assert_eq!(tree.container_in_dir_cross_output(second_id, Direction::Left),
+                   Ok(first_id));
+        // No neighbour and no output that way, so it's the same error as before
+        assert_eq!(tree.container_in_dir_cross_output(first_id, Direction::Left),
+                   tree.container_in_dir(first_id, Direction::Left).map(|(_, id)| id));
+        assert!(tree.container_in_dir_cross_output(first_id, Direction::Left).is_err());
+    }
 }
@@ -0,0 +1,83 @@
+//! Tracking how opaque views should be, e.g to dim the unfocused ones.
+//!
+//! wlc can't draw views translucent, so nothing is dimmed on screen. The
+//! opacity is only tracked here and reported in the tree's JSON, for
+//! whatever draws the views.
+
+use petgraph::graph::NodeIndex;
+use uuid::Uuid;
+
+use super::super::{LayoutTree, TreeError};
+use super::super::commands::CommandResult;
+use super::super::core::container::ContainerType;
+
+impl LayoutTree {
+    /// Sets how opaque the view is, clamped to be between 0 and 1.
+    pub fn set_opacity(&mut self, id: Uuid, opacity: f32) -> CommandResult {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        self.tree[node_ix].set_opacity(opacity)
+            .map_err(|_| TreeError::UuidWrongType(id, vec![ContainerType::View]))
+    }
+
+    /// Sets the opacity unfocused views are reported with,
+    /// or turns that off with `None`.
+    pub fn set_inactive_opacity(&mut self, opacity: Option<f32>) {
+        self.inactive_opacity = opacity.map(|opacity| opacity.max(0.0).min(1.0));
+    }
+
+    /// Gets the opacity reported for the view at the node index.
+    ///
+    /// That's its own opacity, unless it's not focused and unfocused
+    /// views are dimmed further than that.
+    pub fn reported_opacity(&self, node_ix: NodeIndex) -> Option<f32> {
+        let opacity = self.tree[node_ix].opacity()?;
+        match self.inactive_opacity {
+            Some(inactive) if self.active_container != Some(node_ix) =>
+                Some(opacity.min(inactive)),
+            _ => Some(opacity)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::core::tree::tests::basic_tree;
+    use super::super::super::TreeError;
+    use super::super::super::core::container::ContainerType;
+    use rustwlc::WlcView;
+
+    #[test]
+    fn opacity_is_clamped_and_reported_dimmed_when_unfocused() {
+        let mut tree = basic_tree();
+        let first_ix = tree.active_container.unwrap();
+        let first_id = tree.tree[first_ix].get_id();
+        tree.set_opacity(first_id, 1.5).unwrap();
+        assert_eq!(tree.tree[first_ix].opacity(), Some(1.0));
+        tree.set_opacity(first_id, -1.0).unwrap();
+        assert_eq!(tree.tree[first_ix].opacity(), Some(0.0));
+        tree.set_opacity(first_id, 0.5).unwrap();
+        assert_eq!(tree.reported_opacity(first_ix), Some(0.5));
+        let parent_ix = tree.tree.parent_of(first_ix).unwrap();
+        let parent_id = tree.tree[parent_ix].get_id();
+        assert_eq!(tree.set_opacity(parent_id, 0.5),
+                   Err(TreeError::UuidWrongType(parent_id, vec![ContainerType::View])));
+
+        // Dimming is off unless asked for
+        let second_id = tree.add_view(WlcView::dummy(1)).unwrap().get_id();
+        let second_ix = tree.tree.lookup_id(second_id).unwrap();
+        assert_eq!(tree.reported_opacity(first_ix), Some(0.5));
+        assert_eq!(tree.reported_opacity(second_ix), Some(1.0));
+        tree.set_inactive_opacity(Some(0.9));
+        assert_eq!(tree.reported_opacity(second_ix), Some(1.0));
+        // It never makes a view more opaque than it is
+        assert_eq!(tree.reported_opacity(first_ix), Some(0.5));
+        tree.set_opacity(first_id, 1.0).unwrap();
+        assert_eq!(tree.reported_opacity(first_ix), Some(0.9));
+        tree.set_active_node(first_ix).unwrap();
+        assert_eq!(tree.reported_opacity(first_ix), Some(1.0));
+        assert_eq!(tree.reported_opacity(second_ix), Some(0.9));
+        tree.set_inactive_opacity(None);
+        assert_eq!(tree.reported_opacity(second_ix), Some(1.0));
+    }
+}
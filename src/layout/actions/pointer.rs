@@ -1,7 +1,8 @@
-use rustwlc::{input, Point, ResizeEdge, Geometry,
+use rustwlc::{input, Point, ResizeEdge, Geometry, WlcOutput, WlcView,
               RESIZE_TOPLEFT, RESIZE_TOPRIGHT, RESIZE_BOTTOMLEFT, RESIZE_BOTTOMRIGHT,};
 
-use super::super::{LayoutTree, TreeError};
+use super::super::{Container, LayoutTree, TreeError};
+use super::focus::FocusError;
 use super::super::commands::{CommandResult};
 use uuid::Uuid;
 
@@ -34,4 +35,133 @@ impl LayoutTree {
         }
         Ok(origin)
     }
+
+    /// Gets the output whose geometry the point is in.
+    ///
+    /// Returns `None` if the point is outside of every output.
+    pub fn output_at(&self, point: Point) -> Option<WlcOutput> {
+        let root_ix = self.tree.root_ix();
+        self.tree.children_of(root_ix).into_iter()
+            .filter_map(|output_ix| match self.tree[output_ix] {
+                Container::Output { handle, .. } => {
+                    let geometry = self.tree[output_ix].get_geometry()
+                        .expect("Output had no geometry");
+                    Some((handle, geometry))
+                },
+                _ => None
+            })
+            .find(|&(_, geometry)| {
+                point.x >= geometry.origin.x && point.y >= geometry.origin.y &&
+                    point.x < geometry.origin.x + geometry.size.w as i32 &&
+                    point.y < geometry.origin.y + geometry.size.h as i32
+            })
+            .map(|(handle, _)| handle)
+    }
+
+    /// Converts the point from global coordinates to ones relative to the
+    /// top left corner of the output.
+    ///
+    /// If the output is not in the tree the point is returned as it was.
+    pub fn to_output_local(&self, output: WlcOutput, point: Point) -> Point {
+        let root_ix = self.tree.root_ix();
+        let geometry = self.tree.descendant_with_handle(root_ix, output.into())
+            .and_then(|output_ix| self.tree[output_ix].get_geometry());
+        match geometry {
+            Some(geometry) => Point {
+                x: point.x - geometry.origin.x,
+                y: point.y - geometry.origin.y
+            },
+            None => point
+        }
+    }
+
+    /// Sets whether moving the pointer over a view focuses it.
+    pub fn set_focus_follows_mouse(&mut self, enabled: bool) {
+        self.focus_follows_mouse = enabled;
+        self.pointer_view = None;
+    }
+
+    /// Focuses on the view the pointer is over,
+    /// if focus follows the mouse. Otherwise this does nothing.
+    ///
+    /// Focus only changes when the pointer moves onto a different view,
+    /// so moving it around inside the same view doesn't steal the focus back.
+    /// Views that aren't in the tree (e.g backgrounds and bars) are ignored.
+    pub fn pointer_focus(&mut self, view: WlcView) -> CommandResult {
+        if !self.focus_follows_mouse || self.pointer_view == Some(view) {
+            return Ok(())
+        }
+        self.pointer_view = Some(view);
+        if view.is_root() {
+            return Ok(())
+        }
+        let node_ix = match self.tree.lookup_view(view) {
+            Some(node_ix) => node_ix,
+            None => return Ok(())
+        };
+        if self.active_container == Some(node_ix) {
+            return Ok(())
+        }
+        match self.set_active_node(node_ix) {
+            Err(TreeError::Focus(FocusError::BlockedByFullscreen(_, _))) |
+            Err(TreeError::Focus(FocusError::NotFocusable(_))) => Ok(()),
+            result => result
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustwlc::{Point, WlcView};
+    use super::super::super::Container;
+    use super::super::super::core::tree::tests::basic_tree;
+
+    #[test]
+    fn output_coordinates() {
+        let tree = basic_tree();
+        let root_ix = tree.tree.root_ix();
+        let output_ix = tree.tree.children_of(root_ix)[0];
+        let output = match tree.tree[output_ix] {
+            Container::Output { handle, .. } => handle,
+            _ => unreachable!()
+        };
+        let geometry = tree.tree[output_ix].get_geometry().unwrap();
+        let inside = Point { x: geometry.origin.x + 5, y: geometry.origin.y + 7 };
+        assert_eq!(tree.output_at(inside), Some(output));
+        assert_eq!(tree.to_output_local(output, inside), Point { x: 5, y: 7 });
+        let outside = Point {
+            x: geometry.origin.x + geometry.size.w as i32,
+            y: geometry.origin.y
+        };
+        assert_eq!(tree.output_at(outside), None);
+        assert_eq!(tree.output_at(Point { x: -1, y: -1 }), None);
+        // Unknown outputs leave the point alone
+        let unknown = WlcView::dummy(9).as_output();
+        assert_eq!(tree.to_output_local(unknown, inside), inside);
+    }
+
+    #[test]
+    fn pointer_focus_follows_mouse() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let first_id = tree.add_view(WlcView::dummy(1)).unwrap().get_id();
+        let second_id = tree.add_view(WlcView::dummy(2)).unwrap().get_id();
+        // Off by default
+        tree.pointer_focus(WlcView::dummy(1)).unwrap();
+        assert_eq!(tree.get_active_container().unwrap().get_id(), second_id);
+        tree.set_focus_follows_mouse(true);
+        tree.pointer_focus(WlcView::dummy(1)).unwrap();
+        assert_eq!(tree.get_active_container().unwrap().get_id(), first_id);
+        // Moving around in the same view doesn't take the focus back
+        tree.focus_on(second_id).unwrap();
+        tree.pointer_focus(WlcView::dummy(1)).unwrap();
+        assert_eq!(tree.get_active_container().unwrap().get_id(), second_id);
+        // But moving onto another one and back does
+        tree.pointer_focus(WlcView::dummy(2)).unwrap();
+        tree.pointer_focus(WlcView::dummy(1)).unwrap();
+        assert_eq!(tree.get_active_container().unwrap().get_id(), first_id);
+        // Views that aren't in the tree are ignored
+        tree.pointer_focus(WlcView::dummy(42)).unwrap();
+        assert_eq!(tree.get_active_container().unwrap().get_id(), first_id);
+    }
 }
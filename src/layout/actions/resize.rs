@@ -1,9 +1,14 @@
-use rustwlc::{Point, ResizeEdge, Geometry,
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+
+use petgraph::graph::NodeIndex;
+use rustwlc::{Point, ResizeEdge, Geometry, Size,
               RESIZE_LEFT, RESIZE_RIGHT, RESIZE_TOP, RESIZE_BOTTOM};
 
-use super::super::{Action, Direction, LayoutTree, TreeError};
+use super::super::{Action, ActionErr, Direction, LayoutTree, TreeError};
 use super::super::commands::{CommandResult};
-use super::super::core::container::{ContainerType, MIN_SIZE};
+use super::super::core::container::{Container, ContainerType, Layout, MIN_SIZE};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -11,7 +16,35 @@ pub enum ResizeErr {
     /// Expected the node associated with the UUID to be floating.
     ExpectedFloating(Uuid),
     /// Expected the node associated with the UUID to not be floating
-    ExpectedNotFloating(Uuid)
+    ExpectedNotFloating(Uuid),
+    /// There is no tiled container in the direction to take space from
+    NoNeighbor(Uuid, Direction)
+}
+
+impl fmt::Display for ResizeErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ResizeErr::ExpectedFloating(id) =>
+                write!(f, "container {} is not floating", id),
+            ResizeErr::ExpectedNotFloating(id) =>
+                write!(f, "container {} is floating", id),
+            ResizeErr::NoNeighbor(id, direction) =>
+                write!(f, "container {} has no tiled neighbor to the {}", id, direction)
+        }
+    }
+}
+
+impl Error for ResizeErr {}
+
+/// A pointer driven resize of a tiled container, see `begin_resize`.
+#[derive(Debug, Clone, Copy)]
+pub struct TiledResize {
+    /// The container being resized.
+    pub id: Uuid,
+    /// The edges of the container being dragged.
+    pub edge: ResizeEdge,
+    /// Where the pointer was the last time the containers were resized.
+    pub grab: Point
 }
 
 impl LayoutTree {
@@ -43,6 +76,20 @@ impl LayoutTree {
 
     pub fn resize_tiled(&mut self, id: Uuid, edge: ResizeEdge, pointer: Point,
                         action: &mut Action) -> Result<Point, TreeError> {
+        if self.resize_tiled_from(id, edge, pointer, &mut action.grab)? {
+            self.grab_at_corner(id, edge)
+        } else {
+            Ok(pointer)
+        }
+    }
+
+    /// Resizes the tiled container and its neighbours on the edge by how far
+    /// the pointer moved from the grab point, laying out the workspace again.
+    ///
+    /// Returns false if nothing was resized because a container would be
+    /// too small, otherwise the grab point is moved to the pointer.
+    fn resize_tiled_from(&mut self, id: Uuid, edge: ResizeEdge, pointer: Point,
+                         grab: &mut Point) -> Result<bool, TreeError> {
         // This is the vector of operations we will perform, we do all geometry sets atomically.
         let mut resizing_ops: Vec<(Uuid, (ResizeEdge, Geometry))> = Vec::with_capacity(4);
         let dirs_moving_in = Direction::from_edge(edge);
@@ -62,10 +109,13 @@ impl LayoutTree {
             }
             let geo = container.get_geometry()
                 .expect("Could not get geometry of the container");
-            if geometry_resize_too_small(geo, edge, pointer, action.grab) {
-                return Ok(pointer)
+            if geometry_resize_too_small(geo, edge, pointer, *grab) {
+                return Ok(false)
+            }
+            let new_geo = calculate_resize(geo, edge, pointer, *grab);
+            if self.shrinks_below_min(ancestor_id, geo, new_geo) {
+                return Ok(false)
             }
-            let new_geo = calculate_resize(geo, edge, pointer, action.grab);
             resizing_ops.push((ancestor_id, (edge, new_geo)));
         }
         let siblings: Vec<Uuid> = next_containers.into_iter()
@@ -75,7 +125,7 @@ impl LayoutTree {
             .map(|dir| dir.reverse()).collect();
         let reversed_edge = Direction::to_edge(reversed_dir.as_slice());
         for sibling in siblings {
-            let container = try!(self.lookup_mut(sibling));
+            let container = try!(self.lookup(sibling));
             if container.floating() {
                 return Err(TreeError::Resize(ResizeErr::ExpectedNotFloating(container.get_id())))
             }
@@ -86,16 +136,19 @@ impl LayoutTree {
             }
             let geo = container.get_geometry()
                 .expect("Could not get geometry of the container");
-            if geometry_resize_too_small(geo, reversed_edge, pointer, action.grab) {
-                return Ok(pointer)
+            if geometry_resize_too_small(geo, reversed_edge, pointer, *grab) {
+                return Ok(false)
             }
-            let new_geo = calculate_resize(geo, reversed_edge, pointer, action.grab);
+            let new_geo = calculate_resize(geo, reversed_edge, pointer, *grab);
             if new_geo.size.w <= MIN_SIZE.w || new_geo.size.h <= MIN_SIZE.h {
-                return Ok(pointer)
+                return Ok(false)
+            }
+            if self.shrinks_below_min(sibling, geo, new_geo) {
+                return Ok(false)
             }
             resizing_ops.push((sibling, (reversed_edge, new_geo)));
         }
-        action.grab = pointer;
+        *grab = pointer;
         for (id, (edge, geo)) in resizing_ops {
             let container = self.lookup_mut(id)
                 .expect("Id no longer points to node!");
@@ -104,7 +157,270 @@ impl LayoutTree {
         let node_ix = self.tree.lookup_id(id).unwrap();
         let workspace_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Workspace).unwrap();
         self.layout(workspace_ix);
-        self.grab_at_corner(id, edge)
+        Ok(true)
+    }
+}
+
+impl LayoutTree {
+    /// Grows the tiled container in the direction by a percentage of its
+    /// parent's size, taking the space from its neighbour in that direction.
+    /// A negative amount shrinks it, giving the space to the neighbour.
+    ///
+    /// This is the same as i3's `resize grow width 10 ppt`.
+    /// Neither container is made smaller than the minimum size.
+    pub fn resize_container(&mut self, id: Uuid, dir: Direction, amount_ppt: i32)
+                            -> CommandResult {
+        if self.lookup(id)?.floating() {
+            return Err(TreeError::Resize(ResizeErr::ExpectedNotFloating(id)))
+        }
+        let (ancestor_id, sibling_id) = self.container_in_dir(id, dir)
+            .map_err(|_| TreeError::Resize(ResizeErr::NoNeighbor(id, dir)))?;
+        let ancestor_ix = self.tree.lookup_id(ancestor_id)
+            .ok_or(TreeError::NodeNotFound(ancestor_id))?;
+        let sibling_ix = self.tree.lookup_id(sibling_id)
+            .ok_or(TreeError::NodeNotFound(sibling_id))?;
+        let parent_ix = self.tree.parent_of(ancestor_ix)?;
+        let horizontal = match dir {
+            Direction::Left | Direction::Right => true,
+            Direction::Up | Direction::Down => false
+        };
+        let length = |geometry: Geometry| if horizontal {
+            geometry.size.w as i32
+        } else {
+            geometry.size.h as i32
+        };
+        let min_length = |size: Size| if horizontal { size.w } else { size.h } as i32;
+        let ancestor_min = min_length(self.min_size_of(ancestor_ix));
+        let sibling_min = min_length(self.min_size_of(sibling_ix));
+        let parent_geometry = self.tree[parent_ix].get_geometry()
+            .expect("Parent had no geometry");
+        let ancestor_geometry = self.tree[ancestor_ix].get_geometry()
+            .expect("Container had no geometry");
+        let sibling_geometry = self.tree[sibling_ix].get_geometry()
+            .expect("Sibling had no geometry");
+        let mut delta = length(parent_geometry) * amount_ppt / 100;
+        delta = cmp::min(delta, length(sibling_geometry) - sibling_min);
+        delta = cmp::max(delta, ancestor_min - length(ancestor_geometry));
+        // Already at the minimum, so it can't go any further that way
+        if delta == 0 || delta.signum() != amount_ppt.signum() {
+            return Ok(())
+        }
+        let resized = |mut geometry: Geometry, delta: i32| {
+            if horizontal {
+                geometry.size.w = (geometry.size.w as i32 + delta) as u32;
+            } else {
+                geometry.size.h = (geometry.size.h as i32 + delta) as u32;
+            }
+            geometry
+        };
+        self.tree[ancestor_ix].set_geometry(ResizeEdge::empty(),
+                                            resized(ancestor_geometry, delta));
+        self.tree[sibling_ix].set_geometry(ResizeEdge::empty(),
+                                           resized(sibling_geometry, -delta));
+        self.layout(parent_ix);
+        Ok(())
+    }
+
+    /// Starts dragging the edges of the tiled container behind the UUID,
+    /// e.g when the user grabs the boundary between two splits.
+    ///
+    /// Follow this with `update_resize` as the pointer moves,
+    /// and `end_resize` when it's let go.
+    /// Only one resize can be in progress at a time.
+    pub fn begin_resize(&mut self, id: Uuid, edge: ResizeEdge) -> CommandResult {
+        if self.tiled_resize.is_some() {
+            return Err(TreeError::Action(ActionErr::ActionInProgress))
+        }
+        let geometry = {
+            let container = self.lookup(id)?;
+            if container.floating() {
+                return Err(TreeError::Resize(ResizeErr::ExpectedNotFloating(id)))
+            }
+            match container.get_type() {
+                ContainerType::View | ContainerType::Container => {},
+                _ => return Err(TreeError::UuidWrongType(id, vec!(ContainerType::View,
+                                                                  ContainerType::Container)))
+            }
+            container.get_geometry()
+                .expect("Could not get geometry of the container")
+        };
+        // Start from the edges being dragged, so the first update
+        // only moves them by as much as the pointer moved from there.
+        let mut grab = geometry.origin;
+        if edge.contains(RESIZE_RIGHT) {
+            grab.x += geometry.size.w as i32;
+        }
+        if edge.contains(RESIZE_BOTTOM) {
+            grab.y += geometry.size.h as i32;
+        }
+        self.tiled_resize = Some(TiledResize {
+            id: id,
+            edge: edge,
+            grab: grab
+        });
+        Ok(())
+    }
+
+    /// Moves the edges grabbed by `begin_resize` to the pointer,
+    /// resizing the container and its neighbours and tiling them again.
+    pub fn update_resize(&mut self, id: Uuid, pointer: Point) -> CommandResult {
+        let mut resize = match self.tiled_resize {
+            Some(resize) if resize.id == id => resize,
+            _ => return Err(TreeError::Action(ActionErr::ActionNotInProgress))
+        };
+        self.resize_tiled_from(id, resize.edge, pointer, &mut resize.grab)?;
+        self.tiled_resize = Some(resize);
+        Ok(())
+    }
+
+    /// Stops the resize started by `begin_resize`.
+    pub fn end_resize(&mut self) -> CommandResult {
+        self.tiled_resize.take()
+            .map(|_| ())
+            .ok_or(TreeError::Action(ActionErr::ActionNotInProgress))
+    }
+
+    /// Sets the smallest size the view behind the UUID can be tiled at,
+    /// e.g from the size hints of the client, and lays out its workspace.
+    pub fn set_min_size(&mut self, id: Uuid, size: Option<Size>) -> CommandResult {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        self.tree[node_ix].set_min_size(size)
+            .map_err(|_| TreeError::UuidWrongType(id, vec!(ContainerType::View)))?;
+        let workspace_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)?;
+        self.layout(workspace_ix);
+        Ok(())
+    }
+
+    /// Gets the smallest size the tiled container at the node index can be.
+    ///
+    /// For a view this is its minimum size, for a container it's the space
+    /// its children need. Nothing is smaller than `MIN_SIZE`.
+    pub fn min_size_of(&self, node_ix: NodeIndex) -> Size {
+        let size = match self.tree[node_ix] {
+            Container::View { .. } => self.tree[node_ix].min_size().unwrap_or(MIN_SIZE),
+            Container::Container { layout, .. } => {
+                let children = self.tree.grounded_children(node_ix).into_iter()
+                    .map(|child_ix| self.min_size_of(child_ix));
+                children.fold(Size { w: 0, h: 0 }, |acc, min| match layout {
+                    Layout::Horizontal => Size { w: acc.w + min.w, h: cmp::max(acc.h, min.h) },
+                    Layout::Vertical => Size { w: cmp::max(acc.w, min.w), h: acc.h + min.h },
+                    Layout::Tabbed | Layout::Stacked => Size {
+                        w: cmp::max(acc.w, min.w),
+                        h: cmp::max(acc.h, min.h)
+                    }
+                })
+            },
+            _ => MIN_SIZE
+        };
+        Size {
+            w: cmp::max(size.w, MIN_SIZE.w),
+            h: cmp::max(size.h, MIN_SIZE.h)
+        }
+    }
+
+    /// Moves the floating view behind the UUID so that its top left corner
+    /// is at the point, keeping it inside its output.
+    pub fn move_floating_to(&mut self, id: Uuid, origin: Point) -> CommandResult {
+        let node_ix = self.floating_view_ix(id)?;
+        let geometry = self.tree[node_ix].get_geometry()
+            .expect("View had no geometry");
+        self.set_floating_geometry(node_ix, Geometry { origin: origin, ..geometry })
+    }
+
+    /// Resizes the floating view behind the UUID, keeping its top left
+    /// corner where it is if it can.
+    ///
+    /// The view is kept above its minimum size, and no bigger than its output.
+    pub fn resize_floating_to(&mut self, id: Uuid, size: Size) -> CommandResult {
+        let node_ix = self.floating_view_ix(id)?;
+        let geometry = self.tree[node_ix].get_geometry()
+            .expect("View had no geometry");
+        self.set_floating_geometry(node_ix, Geometry { size: size, ..geometry })
+    }
+
+    /// Moves the floating view behind the UUID to the center of the part of
+    /// its output that isn't taken up by the bar.
+    ///
+    /// If it's bigger than that, it's placed so its top left corner can
+    /// still be seen.
+    pub fn center_floating(&mut self, id: Uuid) -> CommandResult {
+        let node_ix = self.floating_view_ix(id)?;
+        let output_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Output)?;
+        let bounds = self.tree[output_ix].get_geometry()
+            .expect("Output had no geometry");
+        let geometry = self.tree[node_ix].get_geometry()
+            .expect("View had no geometry");
+        let center = |start: i32, bound: u32, length: u32| {
+            start + cmp::max(bound as i32 - length as i32, 0) / 2
+        };
+        let geometry = Geometry {
+            origin: Point {
+                x: center(bounds.origin.x, bounds.size.w, geometry.size.w),
+                y: center(bounds.origin.y, bounds.size.h, geometry.size.h)
+            },
+            size: geometry.size
+        };
+        let container = &mut self.tree[node_ix];
+        container.set_geometry(ResizeEdge::empty(), geometry);
+        container.resize_borders(geometry);
+        container.draw_borders()?;
+        Ok(())
+    }
+
+    /// Gets the node index of the floating view behind the UUID.
+    fn floating_view_ix(&self, id: Uuid) -> Result<NodeIndex, TreeError> {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        if self.tree[node_ix].get_type() != ContainerType::View {
+            return Err(TreeError::UuidWrongType(id, vec!(ContainerType::View)))
+        }
+        if !self.tree[node_ix].floating() {
+            return Err(TreeError::Resize(ResizeErr::ExpectedFloating(id)))
+        }
+        Ok(node_ix)
+    }
+
+    /// Sets the geometry of the floating view at the node index, after
+    /// fitting it between its minimum size and the bounds of its output.
+    fn set_floating_geometry(&mut self, node_ix: NodeIndex, geometry: Geometry)
+                             -> CommandResult {
+        let output_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Output)?;
+        let bounds = self.tree[output_ix].get_geometry()
+            .expect("Output had no geometry");
+        let min = self.min_size_of(node_ix);
+        let size = Size {
+            w: cmp::max(cmp::min(geometry.size.w, bounds.size.w), min.w),
+            h: cmp::max(cmp::min(geometry.size.h, bounds.size.h), min.h)
+        };
+        let clamp = |pos: i32, length: u32, start: i32, bound: u32| {
+            let end = start + bound as i32 - length as i32;
+            cmp::max(cmp::min(pos, end), start)
+        };
+        let geometry = Geometry {
+            origin: Point {
+                x: clamp(geometry.origin.x, size.w, bounds.origin.x, bounds.size.w),
+                y: clamp(geometry.origin.y, size.h, bounds.origin.y, bounds.size.h)
+            },
+            size: size
+        };
+        let container = &mut self.tree[node_ix];
+        container.set_geometry(ResizeEdge::empty(), geometry);
+        container.resize_borders(geometry);
+        container.draw_borders()?;
+        Ok(())
+    }
+
+    /// Determines if resizing the container behind the UUID from the old
+    /// geometry to the new one makes it smaller than its minimum size.
+    fn shrinks_below_min(&self, id: Uuid, old: Geometry, new: Geometry) -> bool {
+        let node_ix = match self.tree.lookup_id(id) {
+            Some(node_ix) => node_ix,
+            None => return false
+        };
+        let min = self.min_size_of(node_ix);
+        (new.size.w < min.w && new.size.w < old.size.w) ||
+            (new.size.h < min.h && new.size.h < old.size.h)
     }
 }
 
@@ -182,3 +498,144 @@ fn geometry_resize_too_small(geo: Geometry, edge: ResizeEdge, cur_point: Point,
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::core::tree::tests::basic_tree;
+    use rustwlc::WlcView;
+
+    #[test]
+    fn resize_container_by_ppt() {
+        let mut tree = basic_tree();
+        let first_id = tree.get_active_container().unwrap().get_id();
+        let second_id = tree.add_view(WlcView::root()).unwrap().get_id();
+        let parent_ix = tree.tree.parent_of(tree.active_container.unwrap()).unwrap();
+        tree.layout(parent_ix);
+        let parent_w = tree.tree[parent_ix].get_geometry().unwrap().size.w as i32;
+        let width = |tree: &LayoutTree, id| tree.lookup(id).unwrap()
+            .get_geometry().unwrap().size.w as i32;
+        let (first_w, second_w) = (width(&tree, first_id), width(&tree, second_id));
+
+        tree.resize_container(first_id, Direction::Right, 10).unwrap();
+        assert!((width(&tree, first_id) - (first_w + parent_w / 10)).abs() <= 1);
+        assert!((width(&tree, second_id) - (second_w - parent_w / 10)).abs() <= 1);
+        // Can't take more than the neighbour has
+        tree.resize_container(first_id, Direction::Right, 1000).unwrap();
+        assert!(width(&tree, second_id) >= MIN_SIZE.w as i32 - 1);
+
+        assert_eq!(tree.resize_container(first_id, Direction::Left, 10),
+                   Err(TreeError::Resize(ResizeErr::NoNeighbor(first_id, Direction::Left))));
+        assert_eq!(tree.resize_container(first_id, Direction::Up, 10),
+                   Err(TreeError::Resize(ResizeErr::NoNeighbor(first_id, Direction::Up))));
+    }
+
+    #[test]
+    fn min_size_is_kept_when_tiling_and_resizing() {
+        let mut tree = basic_tree();
+        let first_id = tree.get_active_container().unwrap().get_id();
+        let second_id = tree.add_view(WlcView::root()).unwrap().get_id();
+        let parent_ix = tree.tree.parent_of(tree.active_container.unwrap()).unwrap();
+        tree.layout(parent_ix);
+        let parent_w = tree.tree[parent_ix].get_geometry().unwrap().size.w;
+        let width = |tree: &LayoutTree, id| tree.lookup(id).unwrap()
+            .get_geometry().unwrap().size.w as i32;
+        let min_w = parent_w * 3 / 4;
+        tree.set_min_size(first_id, Some(Size { w: min_w, h: 0 })).unwrap();
+        // It takes the space from its sibling when tiled
+        assert!(width(&tree, first_id) >= min_w as i32 - 1);
+        assert!((width(&tree, first_id) + width(&tree, second_id) - parent_w as i32).abs() <= 1);
+        // And it can't be shrunk below it
+        tree.resize_container(second_id, Direction::Left, 50).unwrap();
+        assert!(width(&tree, first_id) >= min_w as i32 - 1);
+        tree.resize_container(first_id, Direction::Right, -50).unwrap();
+        assert!(width(&tree, first_id) >= min_w as i32 - 1);
+        // Only views have a minimum size
+        let parent_id = tree.tree[parent_ix].get_id();
+        assert!(tree.set_min_size(parent_id, None).is_err());
+    }
+
+    #[test]
+    fn pointer_resize_of_tiled_split() {
+        let mut tree = basic_tree();
+        let first_id = tree.get_active_container().unwrap().get_id();
+        let second_id = tree.add_view(WlcView::root()).unwrap().get_id();
+        let parent_ix = tree.tree.parent_of(tree.active_container.unwrap()).unwrap();
+        tree.layout(parent_ix);
+        let geometry = |tree: &LayoutTree, id| tree.lookup(id).unwrap().get_geometry().unwrap();
+        let (first, second) = (geometry(&tree, first_id), geometry(&tree, second_id));
+
+        tree.begin_resize(first_id, RESIZE_RIGHT).unwrap();
+        assert_eq!(tree.begin_resize(first_id, RESIZE_RIGHT),
+                   Err(TreeError::Action(ActionErr::ActionInProgress)));
+        let edge_x = first.origin.x + first.size.w as i32;
+        tree.update_resize(first_id, Point { x: edge_x + 20, y: 0 }).unwrap();
+        tree.update_resize(first_id, Point { x: edge_x + 50, y: 0 }).unwrap();
+        let width = |tree: &LayoutTree, id| geometry(tree, id).size.w as i32;
+        assert!((width(&tree, first_id) - (first.size.w as i32 + 50)).abs() <= 1);
+        assert!((width(&tree, second_id) - (second.size.w as i32 - 50)).abs() <= 1);
+        tree.end_resize().unwrap();
+
+        assert_eq!(tree.update_resize(first_id, Point { x: edge_x, y: 0 }),
+                   Err(TreeError::Action(ActionErr::ActionNotInProgress)));
+        assert_eq!(tree.end_resize(), Err(TreeError::Action(ActionErr::ActionNotInProgress)));
+    }
+
+    #[test]
+    fn floating_geometry_is_kept_on_the_output() {
+        let mut tree = basic_tree();
+        let id = tree.get_active_container().unwrap().get_id();
+        assert_eq!(tree.move_floating_to(id, Point { x: 0, y: 0 }),
+                   Err(TreeError::Resize(ResizeErr::ExpectedFloating(id))));
+        tree.float_container(id).unwrap();
+        let node_ix = tree.tree.lookup_id(id).unwrap();
+        let output_ix = tree.tree.ancestor_of_type(node_ix, ContainerType::Output).unwrap();
+        let bounds = tree.tree[output_ix].get_geometry().unwrap();
+        let geometry = |tree: &LayoutTree| tree.lookup(id).unwrap().get_geometry().unwrap();
+
+        tree.resize_floating_to(id, Size { w: 1, h: 1 }).unwrap();
+        assert_eq!(geometry(&tree).size, MIN_SIZE);
+        tree.move_floating_to(id, Point { x: 10, y: 20 }).unwrap();
+        assert_eq!(geometry(&tree).origin, Point { x: bounds.origin.x.max(10),
+                                                   y: bounds.origin.y.max(20) });
+        // Dragged back onto the output
+        tree.move_floating_to(id, Point { x: -500, y: 100_000 }).unwrap();
+        assert_eq!(geometry(&tree).origin, Point {
+            x: bounds.origin.x,
+            y: bounds.origin.y + bounds.size.h as i32 - MIN_SIZE.h as i32
+        });
+        // No bigger than the output
+        tree.resize_floating_to(id, Size { w: 100_000, h: 100_000 }).unwrap();
+        assert_eq!(geometry(&tree), bounds);
+        tree.set_min_size(id, Some(Size { w: 150, h: 120 })).unwrap();
+        tree.resize_floating_to(id, Size { w: 1, h: 1 }).unwrap();
+        assert_eq!(geometry(&tree).size, Size { w: 150, h: 120 });
+    }
+
+    #[test]
+    fn center_floating_view() {
+        let mut tree = basic_tree();
+        let id = tree.get_active_container().unwrap().get_id();
+        assert_eq!(tree.center_floating(id),
+                   Err(TreeError::Resize(ResizeErr::ExpectedFloating(id))));
+        tree.float_container(id).unwrap();
+        let node_ix = tree.tree.lookup_id(id).unwrap();
+        let output_ix = tree.tree.ancestor_of_type(node_ix, ContainerType::Output).unwrap();
+        let bounds = tree.tree[output_ix].get_geometry().unwrap();
+        tree.resize_floating_to(id, Size { w: 100, h: 50 }).unwrap();
+        tree.move_floating_to(id, bounds.origin).unwrap();
+        tree.center_floating(id).unwrap();
+        let geometry = tree.lookup(id).unwrap().get_geometry().unwrap();
+        assert_eq!(geometry.size, Size { w: 100, h: 50 });
+        assert_eq!(geometry.origin, Point {
+            x: bounds.origin.x + (bounds.size.w as i32 - 100) / 2,
+            y: bounds.origin.y + (bounds.size.h as i32 - 50) / 2
+        });
+        // Too big to fit, the top left corner stays on the output
+        let mut too_big = geometry;
+        too_big.size = Size { w: bounds.size.w * 2, h: bounds.size.h * 2 };
+        tree.tree[node_ix].set_geometry(ResizeEdge::empty(), too_big);
+        tree.center_floating(id).unwrap();
+        assert_eq!(tree.lookup(id).unwrap().get_geometry().unwrap().origin, bounds.origin);
+    }
+}
@@ -0,0 +1,202 @@
+//! The scratchpad, a hidden workspace that views can be put away in
+//! and summoned back to any workspace.
+//!
+//! The scratchpad workspace is not attached to any output, so it is never
+//! shown by `switch_to_workspace`, is skipped by anything that walks the
+//! outputs, and is unaffected by outputs being added or removed.
+
+use rustwlc::{Geometry, Point, ResizeEdge, Size, WlcOutput};
+use uuid::Uuid;
+use petgraph::graph::NodeIndex;
+
+use super::super::LayoutTree;
+use super::super::commands::CommandResult;
+use super::super::core::TreeError;
+use super::super::core::container::{Container, ContainerType};
+
+/// The name of the reserved scratchpad workspace.
+pub const SCRATCHPAD_NAME: &'static str = "__scratchpad";
+
+impl LayoutTree {
+    /// Determines if the node is the scratchpad workspace.
+    pub fn is_scratchpad(&self, workspace_ix: NodeIndex) -> bool {
+        self.scratchpad == Some(workspace_ix)
+    }
+
+    /// Gets every node beneath the root, and every node in the scratchpad.
+    ///
+    /// The scratchpad isn't attached to the root, so anything that has to
+    /// find views wherever they are (e.g marks) should walk this instead.
+    pub fn all_descendants_with_scratchpad(&self) -> Vec<NodeIndex> {
+        let mut nodes = self.tree.all_descendants_of(self.tree.root_ix());
+        if let Some(scratchpad_ix) = self.scratchpad {
+            nodes.extend(self.tree.all_descendants_of(scratchpad_ix));
+        }
+        nodes
+    }
+
+    /// Gets the root container of the scratchpad, making the scratchpad
+    /// if it doesn't exist yet.
+    fn scratchpad_container(&mut self, geometry: Geometry, output: WlcOutput) -> NodeIndex {
+        let workspace_ix = match self.scratchpad {
+            Some(workspace_ix) => workspace_ix,
+            None => {
+                let workspace = Container::new_workspace(SCRATCHPAD_NAME.into(), geometry);
+                let workspace_ix = self.tree.add_detached(workspace);
+                let container = Container::new_container(geometry, output, None);
+                self.tree.add_child(workspace_ix, container, true);
+                self.scratchpad = Some(workspace_ix);
+                workspace_ix
+            }
+        };
+        self.tree.children_of(workspace_ix)[0]
+    }
+
+    /// Moves the view behind the UUID into the scratchpad, hiding it.
+    ///
+    /// If the view was floating, its geometry is remembered for when it is
+    /// summoned with `scratchpad_show`.
+    pub fn move_to_scratchpad(&mut self, id: Uuid) -> CommandResult {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        if self.tree[node_ix].get_type() != ContainerType::View {
+            return Err(TreeError::UuidWrongType(id, vec!(ContainerType::View)))
+        }
+        let workspace_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)?;
+        if self.is_scratchpad(workspace_ix) {
+            return Ok(())
+        }
+        let output_ix = self.tree.ancestor_of_type(workspace_ix, ContainerType::Output)?;
+        let output_geometry = self.tree[output_ix].get_geometry()
+            .expect("Output had no geometry");
+        let output = match self.tree[output_ix] {
+            Container::Output { handle, .. } => handle,
+            _ => unreachable!()
+        };
+        if self.tree[node_ix].fullscreen() {
+            self.set_fullscreen(id, false)?;
+        }
+        if self.tree[node_ix].floating() {
+            let geometry = self.tree[node_ix].get_geometry()
+                .expect("View had no geometry");
+            self.scratchpad_geometry.insert(id, geometry);
+        }
+        let parent_ix = self.tree.parent_of(node_ix)?;
+        let scratchpad_ix = self.scratchpad_container(output_geometry, output);
+        self.tree.move_into(node_ix, scratchpad_ix)?;
+        self.tree[node_ix].set_floating(true)
+            .expect("View could not be floated");
+        self.set_container_visibility(node_ix, false);
+        if self.tree.can_remove_empty_parent(parent_ix) {
+            self.remove_view_or_container(parent_ix)?;
+        }
+        if self.active_container == Some(node_ix) {
+            self.active_container = None;
+            let root_c_ix = self.tree.children_of(workspace_ix)[0];
+            self.focus_on_next_container(root_c_ix);
+        }
+        self.layout(workspace_ix);
+        self.validate();
+        Ok(())
+    }
+
+    /// Summons the view most recently put in the scratchpad, floating it
+    /// in the center of the active output and focusing on it.
+    ///
+    /// It keeps the size it had when it was last floating, otherwise
+    /// it takes up half of the output.
+    /// Does nothing if the scratchpad is empty.
+    pub fn scratchpad_show(&mut self) -> CommandResult {
+        let scratchpad_ix = match self.scratchpad {
+            Some(workspace_ix) => self.tree.children_of(workspace_ix)[0],
+            None => return Ok(())
+        };
+        let node_ix = match self.tree.children_of(scratchpad_ix).last() {
+            Some(&node_ix) => node_ix,
+            None => return Ok(())
+        };
+        let id = self.tree[node_ix].get_id();
+        let root_c_ix = self.root_container_ix()
+            .ok_or(TreeError::NoActiveContainer)?;
+        let output_ix = self.tree.ancestor_of_type(root_c_ix, ContainerType::Output)?;
+        let output_geometry = self.tree[output_ix].get_geometry()
+            .expect("Output had no geometry");
+        let size = self.scratchpad_geometry.remove(&id)
+            .map(|geometry| geometry.size)
+            .unwrap_or(Size {
+                w: output_geometry.size.w / 2,
+                h: output_geometry.size.h / 2
+            });
+        let geometry = Geometry {
+            origin: Point {
+                x: output_geometry.origin.x +
+                    (output_geometry.size.w as i32 - size.w as i32) / 2,
+                y: output_geometry.origin.y +
+                    (output_geometry.size.h as i32 - size.h as i32) / 2
+            },
+            size: size
+        };
+        self.tree.move_into(node_ix, root_c_ix)?;
+        {
+            let container = &mut self.tree[node_ix];
+            container.set_geometry(ResizeEdge::empty(), geometry);
+            container.resize_borders(geometry);
+            container.draw_borders()?;
+        }
+        self.set_container_visibility(node_ix, true);
+        self.set_active_node(node_ix)?;
+        let workspace_ix = self.tree.parent_of(root_c_ix)?;
+        self.layout(workspace_ix);
+        self.validate();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::core::tree::tests::basic_tree;
+
+    #[test]
+    fn scratchpad_test() {
+        let mut tree = basic_tree();
+        let id = tree.get_active_container().unwrap().get_id();
+        tree.move_to_scratchpad(id).unwrap();
+        // It's hidden away, but still in the tree
+        assert!(tree.lookup(id).is_ok());
+        assert!(tree.views_in_workspace("1").unwrap().is_empty());
+        assert!(tree.tree.workspace_ix_by_name(SCRATCHPAD_NAME).is_none());
+        assert!(tree.get_active_container().map(|c| c.get_id()) != Some(id));
+
+        tree.switch_to_workspace("2");
+        tree.scratchpad_show().unwrap();
+        assert_eq!(tree.views_in_workspace("2").unwrap().len(), 3);
+        assert_eq!(tree.get_active_container().unwrap().get_id(), id);
+        assert!(tree.lookup(id).unwrap().floating());
+        let output_geometry = tree.tree[tree.tree.ancestor_of_type(
+            tree.active_container.unwrap(), ContainerType::Output).unwrap()]
+            .get_geometry().unwrap();
+        let geometry = tree.lookup(id).unwrap().get_geometry().unwrap();
+        assert_eq!(geometry.size.w, output_geometry.size.w / 2);
+        assert_eq!(geometry.size.h, output_geometry.size.h / 2);
+        assert_eq!(geometry.origin.x, (output_geometry.size.w - geometry.size.w) as i32 / 2);
+
+        // Its floating geometry is remembered
+        let new_geometry = Geometry {
+            origin: Point { x: 0, y: 0 },
+            size: Size { w: 100, h: 50 }
+        };
+        tree.lookup_mut(id).unwrap().set_geometry(ResizeEdge::empty(), new_geometry);
+        tree.move_to_scratchpad(id).unwrap();
+        tree.switch_to_workspace("1");
+        tree.scratchpad_show().unwrap();
+        let geometry = tree.lookup(id).unwrap().get_geometry().unwrap();
+        assert_eq!(geometry.size, new_geometry.size);
+        assert_eq!(tree.current_workspace(), Ok("1"));
+        // Nothing left to show
+        tree.switch_to_workspace("2");
+        tree.scratchpad_show().unwrap();
+        assert_eq!(tree.current_workspace(), Ok("2"));
+        tree.validate();
+    }
+}
@@ -0,0 +1,371 @@
+//! Saving the arrangement of a workspace and restoring it later,
+//! like i3's `append_layout`.
+//!
+//! A snapshot only holds the containers and where the views were, not the
+//! views themselves. When it's restored, views that are added to the
+//! workspace afterwards take the places of the views that matched them.
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Deref;
+
+use petgraph::graph::NodeIndex;
+use rustc_serialize::json::{Json, ToJson};
+use rustwlc::WlcView;
+use uuid::Uuid;
+
+use super::super::LayoutTree;
+use super::super::commands::CommandResult;
+use super::super::core::TreeError;
+use super::super::core::container::{Container, ContainerType, Handle, Layout};
+use super::criteria::Criteria;
+use ::layout::core::borders::Borders;
+use ::render::Renderable;
+
+/// The arrangement of the containers in a workspace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LayoutSnapshot {
+    /// A container with its layout and children.
+    Container {
+        layout: Layout,
+        children: Vec<LayoutSnapshot>
+    },
+    /// A placeholder for a view, taken by the next view that matches.
+    View(Criteria)
+}
+
+/// A snapshot being restored into a workspace.
+///
+/// Empty containers can't be in the tree, so the containers of the snapshot
+/// are only made once a view is put in them.
+#[derive(Clone, Debug)]
+pub struct PendingLayout {
+    snapshot: LayoutSnapshot,
+    /// The containers that have been made, by their path in the snapshot.
+    containers: HashMap<Vec<usize>, Uuid>,
+    /// The placeholders that have been taken, by their path in the snapshot.
+    views: HashMap<Vec<usize>, WlcView>
+}
+
+impl LayoutSnapshot {
+    /// Gets the paths to the view placeholders, in tree order.
+    fn placeholders(&self) -> Vec<(Vec<usize>, &Criteria)> {
+        fn collect<'a>(snapshot: &'a LayoutSnapshot, path: &mut Vec<usize>,
+                       result: &mut Vec<(Vec<usize>, &'a Criteria)>) {
+            match *snapshot {
+                LayoutSnapshot::Container { ref children, .. } => {
+                    for (index, child) in children.iter().enumerate() {
+                        path.push(index);
+                        collect(child, path, result);
+                        path.pop();
+                    }
+                },
+                LayoutSnapshot::View(ref criteria) => result.push((path.clone(), criteria))
+            }
+        }
+        let mut result = Vec::new();
+        collect(self, &mut Vec::new(), &mut result);
+        result
+    }
+
+    /// Gets the part of the snapshot at the path.
+    fn at(&self, path: &[usize]) -> &LayoutSnapshot {
+        path.iter().fold(self, |snapshot, &index| match *snapshot {
+            LayoutSnapshot::Container { ref children, .. } => &children[index],
+            LayoutSnapshot::View(_) => panic!("Path went through a view placeholder")
+        })
+    }
+
+    /// Reads a snapshot back from the JSON made by `to_json`.
+    pub fn from_json(json: &Json) -> Option<LayoutSnapshot> {
+        let object = match json.as_object() {
+            Some(object) => object,
+            None => return None
+        };
+        match object.get("children") {
+            Some(children) => {
+                let layout = match object.get("layout").and_then(|layout| layout.as_string()) {
+                    Some("horizontal") => Layout::Horizontal,
+                    Some("vertical") => Layout::Vertical,
+                    Some("tabbed") => Layout::Tabbed,
+                    Some("stacked") => Layout::Stacked,
+                    _ => return None
+                };
+                children.as_array()
+                    .and_then(|children| children.iter()
+                              .map(LayoutSnapshot::from_json)
+                              .collect::<Option<Vec<_>>>())
+                    .map(|children| LayoutSnapshot::Container {
+                        layout: layout,
+                        children: children
+                    })
+            },
+            None => {
+                let string = |key: &str| object.get(key)
+                    .and_then(|value| value.as_string())
+                    .map(|value| value.to_string());
+                Some(LayoutSnapshot::View(Criteria {
+                    class: string("class"),
+                    app_id: string("app_id"),
+                    title: string("title"),
+                    .. Criteria::default()
+                }))
+            }
+        }
+    }
+}
+
+impl ToJson for LayoutSnapshot {
+    fn to_json(&self) -> Json {
+        let mut map = BTreeMap::new();
+        match *self {
+            LayoutSnapshot::Container { layout, ref children } => {
+                map.insert("layout".into(), layout.to_string().to_json());
+                map.insert("children".into(), children.to_json());
+            },
+            LayoutSnapshot::View(ref criteria) => {
+                if let Some(ref class) = criteria.class {
+                    map.insert("class".into(), class.to_json());
+                }
+                if let Some(ref app_id) = criteria.app_id {
+                    map.insert("app_id".into(), app_id.to_json());
+                }
+                if let Some(ref title) = criteria.title {
+                    map.insert("title".into(), title.to_json());
+                }
+            }
+        }
+        Json::Object(map)
+    }
+}
+
+impl LayoutTree {
+    /// Takes a snapshot of the tiled containers in the workspace
+    /// with the given name. Floating views are left out.
+    ///
+    /// Views are remembered by their class and app id.
+    pub fn save_workspace_layout(&self, name: &str) -> Result<LayoutSnapshot, TreeError> {
+        let workspace_ix = self.lookup_workspace(name)?;
+        let root_c_ix = self.tree.children_of(workspace_ix)[0];
+        Ok(self.snapshot_of(root_c_ix)
+           .expect("Root container could not be snapshotted"))
+    }
+
+    /// Restores a snapshot into the workspace with the given name.
+    ///
+    /// The root container takes the layout of the snapshot. Views added
+    /// to the workspace afterwards are put in the first placeholder they
+    /// match, making the containers around it as they are needed.
+    /// Restoring another snapshot replaces this one.
+    pub fn restore_workspace_layout(&mut self, name: &str, snapshot: LayoutSnapshot)
+                                    -> CommandResult {
+        let workspace_ix = self.lookup_workspace(name)?;
+        let snapshot = match snapshot {
+            LayoutSnapshot::View(_) => LayoutSnapshot::Container {
+                layout: Layout::Horizontal,
+                children: vec![snapshot]
+            },
+            snapshot => snapshot
+        };
+        let layout = match snapshot {
+            LayoutSnapshot::Container { layout, .. } => layout,
+            LayoutSnapshot::View(_) => unreachable!()
+        };
+        let root_c_ix = self.tree.children_of(workspace_ix)[0];
+        let mut containers = HashMap::new();
+        containers.insert(vec![], self.tree[root_c_ix].get_id());
+        let workspace_id = self.tree[workspace_ix].get_id();
+        self.pending_layouts.insert(workspace_id, PendingLayout {
+            snapshot: snapshot,
+            containers: containers,
+            views: HashMap::new()
+        });
+        self.set_layout(root_c_ix, layout);
+        self.layout(workspace_ix);
+        Ok(())
+    }
+
+    fn snapshot_of(&self, node_ix: NodeIndex) -> Option<LayoutSnapshot> {
+        match self.tree[node_ix] {
+            Container::Container { layout, .. } => {
+                let children: Vec<_> = self.tree.grounded_children(node_ix).into_iter()
+                    .filter_map(|child_ix| self.snapshot_of(child_ix))
+                    .collect();
+                if children.is_empty() && !self.tree.is_root_container(node_ix) {
+                    return None
+                }
+                Some(LayoutSnapshot::Container { layout: layout, children: children })
+            },
+            Container::View { handle, floating: false, .. } => {
+                let non_empty = |string: String| if string.is_empty() {
+                    None
+                } else {
+                    Some(string)
+                };
+                Some(LayoutSnapshot::View(Criteria {
+                    class: non_empty(handle.get_class()),
+                    app_id: non_empty(handle.get_app_id()),
+                    ..Criteria::default()
+                }))
+            },
+            _ => None
+        }
+    }
+
+    /// Finds the placeholder the view should go in, if a layout is being
+    /// restored in the active workspace and the view matches one.
+    ///
+    /// The containers leading to the placeholder are made, and the container
+    /// the view should be added to is returned with the position in it.
+    pub fn take_placeholder(&mut self, view: WlcView)
+                            -> Result<Option<(NodeIndex, u32)>, TreeError> {
+        let workspace_ix = match self.active_ix_of(ContainerType::Workspace) {
+            Some(workspace_ix) => workspace_ix,
+            None => return Ok(None)
+        };
+        let workspace_id = self.tree[workspace_ix].get_id();
+        let path = {
+            let pending = match self.pending_layouts.get(&workspace_id) {
+                Some(pending) => pending,
+                None => return Ok(None)
+            };
+            let candidate = Container::new_view(view, None);
+            let placeholder = pending.snapshot.placeholders().into_iter()
+                .find(|&(ref path, criteria)| !pending.views.contains_key(path) &&
+                      criteria.matches(&candidate));
+            match placeholder {
+                Some((path, _)) => path,
+                None => return Ok(None)
+            }
+        };
+        let mut parent_ix = self.tree.children_of(workspace_ix)[0];
+        for depth in 1..path.len() {
+            let container_path = &path[..depth];
+            let existing = self.pending_layouts[&workspace_id].containers.get(container_path)
+                .and_then(|&id| self.tree.lookup_id(id))
+                .and_then(|node_ix| if self.tree.parent_of(node_ix).ok() == Some(parent_ix) {
+                    Some(node_ix)
+                } else {
+                    None
+                });
+            parent_ix = match existing {
+                Some(node_ix) => node_ix,
+                None => {
+                    let layout = match *self.pending_layouts[&workspace_id].snapshot
+                        .at(container_path) {
+                        LayoutSnapshot::Container { layout, .. } => layout,
+                        LayoutSnapshot::View(_) => unreachable!()
+                    };
+                    let pos = self.placeholder_pos(workspace_id, parent_ix, container_path);
+                    let container_ix = self.add_placeholder_container(parent_ix, layout, pos)?;
+                    let id = self.tree[container_ix].get_id();
+                    self.pending_layouts.get_mut(&workspace_id)
+                        .expect("Pending layout went missing")
+                        .containers.insert(container_path.to_vec(), id);
+                    container_ix
+                }
+            };
+        }
+        let pos = self.placeholder_pos(workspace_id, parent_ix, &path);
+        let done = {
+            let pending = self.pending_layouts.get_mut(&workspace_id)
+                .expect("Pending layout went missing");
+            pending.views.insert(path, view);
+            pending.views.len() == pending.snapshot.placeholders().len()
+        };
+        if done {
+            self.pending_layouts.remove(&workspace_id);
+        }
+        Ok(Some((parent_ix, pos)))
+    }
+
+    /// Gets the position in the parent that the part of the snapshot at the
+    /// path should go, which is right after the closest sibling before it
+    /// that has already been put in the tree.
+    fn placeholder_pos(&self, workspace_id: Uuid, parent_ix: NodeIndex, path: &[usize]) -> u32 {
+        let pending = &self.pending_layouts[&workspace_id];
+        let (&index, prefix) = path.split_last()
+            .expect("Placeholder path was empty");
+        for sibling in (0..index).rev() {
+            let mut sibling_path = prefix.to_vec();
+            sibling_path.push(sibling);
+            let sibling_ix = pending.containers.get(&sibling_path)
+                .and_then(|&id| self.tree.lookup_id(id))
+                .or_else(|| pending.views.get(&sibling_path)
+                         .and_then(|&view| self.tree.lookup_view(view)));
+            if let Some(sibling_ix) = sibling_ix {
+                if let Some(weight) = self.tree.get_edge_weight_between(parent_ix, sibling_ix) {
+                    return *weight.deref() + 1
+                }
+            }
+        }
+        1
+    }
+
+    fn add_placeholder_container(&mut self, parent_ix: NodeIndex, layout: Layout, pos: u32)
+                                 -> Result<NodeIndex, TreeError> {
+        let geometry = self.tree[parent_ix].get_geometry()
+            .expect("Container had no geometry");
+        let output_ix = self.tree.ancestor_of_type(parent_ix, ContainerType::Output)?;
+        let output = match self.tree[output_ix].get_handle()? {
+            Handle::Output(handle) => handle,
+            _ => unreachable!()
+        };
+        let borders = Borders::new(geometry, output);
+        let mut container = Container::new_container(geometry, output, borders);
+        container.set_layout(layout)?;
+        let container_ix = self.tree.add_child(parent_ix, container, false);
+        self.tree.set_child_pos(container_ix, pos);
+        Ok(container_ix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustc_serialize::json::ToJson;
+    use rustwlc::WlcView;
+    use super::*;
+    use super::super::super::core::tree::tests::basic_tree;
+
+    #[test]
+    fn save_and_restore_workspace_layout() {
+        let mut tree = basic_tree();
+        let snapshot = tree.save_workspace_layout("2").unwrap();
+        let placeholder = LayoutSnapshot::View(Criteria::default());
+        assert_eq!(snapshot, LayoutSnapshot::Container {
+            layout: Layout::Horizontal,
+            children: vec![LayoutSnapshot::Container {
+                layout: Layout::Horizontal,
+                children: vec![placeholder.clone(), placeholder.clone()]
+            }]
+        });
+        assert_eq!(LayoutSnapshot::from_json(&snapshot.to_json()), Some(snapshot));
+
+        let snapshot = LayoutSnapshot::Container {
+            layout: Layout::Vertical,
+            children: vec![placeholder.clone(), LayoutSnapshot::Container {
+                layout: Layout::Tabbed,
+                children: vec![placeholder.clone()]
+            }]
+        };
+        tree.switch_to_workspace("3");
+        tree.restore_workspace_layout("3", snapshot).unwrap();
+        let work_ix = tree.tree.workspace_ix_by_name("3").unwrap();
+        let root_c_ix = tree.tree.children_of(work_ix)[0];
+        assert_eq!(tree.tree[root_c_ix].get_layout(), Ok(Layout::Vertical));
+        tree.add_view(WlcView::dummy(1)).unwrap();
+        tree.add_view(WlcView::dummy(2)).unwrap();
+        let children = tree.tree.children_of(root_c_ix);
+        assert_eq!(children.len(), 2);
+        assert_eq!(tree.tree.lookup_view(WlcView::dummy(1)), Some(children[0]));
+        assert_eq!(tree.tree[children[1]].get_layout(), Ok(Layout::Tabbed));
+        assert_eq!(tree.tree.children_of(children[1]),
+                   vec![tree.tree.lookup_view(WlcView::dummy(2)).unwrap()]);
+        // Every placeholder is taken, so views are added normally again
+        tree.add_view(WlcView::dummy(3)).unwrap();
+        let tabbed_ix = tree.tree.parent_of(tree.tree.lookup_view(WlcView::dummy(3)).unwrap());
+        assert_eq!(tabbed_ix, Ok(children[1]));
+        assert_eq!(tree.restore_workspace_layout("nope", placeholder),
+                   Err(TreeError::UuidNotAssociatedWith(ContainerType::Workspace)));
+        tree.validate();
+    }
+}
@@ -0,0 +1,127 @@
+//! The stacking order of floating views, kept per workspace.
+
+use petgraph::graph::NodeIndex;
+use uuid::Uuid;
+
+use super::super::{LayoutTree, TreeError};
+use super::super::commands::CommandResult;
+use super::super::core::container::{Container, ContainerType};
+
+impl LayoutTree {
+    /// Gets the floating views in the workspace, from the bottom of the
+    /// stack to the top.
+    ///
+    /// Views that were floated since the order was last changed are on top,
+    /// in tree order.
+    pub fn floating_stack(&self, workspace_ix: NodeIndex) -> Vec<Uuid> {
+        let workspace_id = self.tree[workspace_ix].get_id();
+        let floating: Vec<Uuid> = self.tree.all_descendants_of(workspace_ix).into_iter()
+            .filter(|&node_ix| match self.tree[node_ix] {
+                Container::View { floating, .. } => floating,
+                _ => false
+            })
+            .map(|node_ix| self.tree[node_ix].get_id())
+            .collect();
+        let mut stack: Vec<Uuid> = self.floating_order.get(&workspace_id)
+            .map(|order| order.iter()
+                 .filter(|id| floating.contains(id))
+                 .cloned()
+                 .collect())
+            .unwrap_or_else(Vec::new);
+        for id in floating {
+            if !stack.contains(&id) {
+                stack.push(id);
+            }
+        }
+        stack
+    }
+
+    /// Puts the floating view on top of the other floating views
+    /// in its workspace.
+    pub fn raise_floating(&mut self, id: Uuid) -> CommandResult {
+        self.restack_floating(id, true)
+    }
+
+    /// Puts the floating view beneath the other floating views
+    /// in its workspace. It stays above the tiled views.
+    pub fn lower_floating(&mut self, id: Uuid) -> CommandResult {
+        self.restack_floating(id, false)
+    }
+
+    /// Moves the floating view to the top or the bottom of the stack of
+    /// its workspace, and brings the views to the front in the new order.
+    fn restack_floating(&mut self, id: Uuid, raise: bool) -> CommandResult {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        match self.tree[node_ix] {
+            Container::View { floating: true, .. } => {},
+            Container::View { .. } =>
+                return Err(TreeError::ContainerWasNotFloating(node_ix)),
+            _ => return Err(TreeError::UuidWrongType(id, vec![ContainerType::View]))
+        }
+        let workspace_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)?;
+        let mut stack = self.floating_stack(workspace_ix);
+        stack.retain(|&other_id| other_id != id);
+        if raise {
+            stack.push(id);
+        } else {
+            stack.insert(0, id);
+        }
+        self.bring_stack_to_front(&stack);
+        let workspace_id = self.tree[workspace_ix].get_id();
+        self.floating_order.insert(workspace_id, stack);
+        Ok(())
+    }
+
+    /// Brings the views to the front, from the bottom of the stack to the top.
+    pub fn bring_stack_to_front(&self, stack: &[Uuid]) {
+        for &id in stack {
+            if let Some(node_ix) = self.tree.lookup_id(id) {
+                if let Container::View { handle, .. } = self.tree[node_ix] {
+                    handle.bring_to_front();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::core::tree::tests::basic_tree;
+    use super::super::super::core::container::ContainerType;
+    use super::super::super::TreeError;
+    use rustwlc::WlcView;
+
+    #[test]
+    fn raising_and_lowering_floating_views() {
+        let mut tree = basic_tree();
+        let tiled_id = tree.get_active_container().unwrap().get_id();
+        let first_id = tree.add_view(WlcView::dummy(1)).unwrap().get_id();
+        let second_id = tree.add_view(WlcView::dummy(2)).unwrap().get_id();
+        tree.float_container(first_id).unwrap();
+        tree.float_container(second_id).unwrap();
+        let workspace_ix = tree.tree.workspace_ix_by_name("1").unwrap();
+        assert_eq!(tree.floating_stack(workspace_ix), vec![first_id, second_id]);
+        tree.raise_floating(first_id).unwrap();
+        assert_eq!(tree.floating_stack(workspace_ix), vec![second_id, first_id]);
+        tree.lower_floating(first_id).unwrap();
+        assert_eq!(tree.floating_stack(workspace_ix), vec![first_id, second_id]);
+        // Focusing on a floating view raises it
+        let first_ix = tree.tree.lookup_id(first_id).unwrap();
+        tree.set_active_node(first_ix).unwrap();
+        assert_eq!(tree.floating_stack(workspace_ix), vec![second_id, first_id]);
+        // Views that stop floating leave the stack
+        tree.ground_container(second_id).unwrap();
+        assert_eq!(tree.floating_stack(workspace_ix), vec![first_id]);
+
+        let tiled_ix = tree.tree.lookup_id(tiled_id).unwrap();
+        assert_eq!(tree.raise_floating(tiled_id),
+                   Err(TreeError::ContainerWasNotFloating(tiled_ix)));
+        let root_c_ix = tree.tree.ancestor_of_type(tiled_ix, ContainerType::Container)
+            .unwrap();
+        let root_c_id = tree.tree[root_c_ix].get_id();
+        assert_eq!(tree.lower_floating(root_c_id),
+                   Err(TreeError::UuidWrongType(root_c_id, vec![ContainerType::View])));
+        tree.validate();
+    }
+}
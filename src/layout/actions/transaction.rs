@@ -0,0 +1,268 @@
+//! Running several commands on the tree as one.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+
+use petgraph::graph::NodeIndex;
+use rustwlc::{Geometry, WlcOutput, WlcView};
+use uuid::Uuid;
+
+use super::super::{Assignment, BackgroundMode, BorderColors, FocusWrapping, HideEdgeBorders,
+                   LayoutTree, Layout, Orientation, TiledResize};
+use super::super::commands::CommandResult;
+use super::super::core::InnerTree;
+use super::super::core::container::Container;
+use super::snapshot::PendingLayout;
+
+/// The parts of the tree that commands change,
+/// saved so that a failed transaction can be undone.
+struct SavedTree {
+    tree: InnerTree,
+    active_container: Option<NodeIndex>,
+    view_tags: HashMap<Uuid, Vec<String>>,
+    dynamic_workspaces: bool,
+    scratchpad: Option<NodeIndex>,
+    scratchpad_geometry: HashMap<Uuid, Geometry>,
+    inner_gap: Option<u32>,
+    outer_gap: u32,
+    smart_gaps: bool,
+    last_focused: HashMap<Uuid, Uuid>,
+    global_fullscreen: Option<Uuid>,
+    pending_split: Option<(Uuid, Layout)>,
+    prev_workspace: Option<String>,
+    focus_history: VecDeque<Uuid>,
+    tiled_resize: Option<TiledResize>,
+    hide_edge_borders: HideEdgeBorders,
+    default_layout: Layout,
+    default_orientation: Orientation,
+    pending_layouts: HashMap<Uuid, PendingLayout>,
+    default_background: Option<(PathBuf, BackgroundMode)>,
+    focus_follows_mouse: bool,
+    pointer_view: Option<WlcView>,
+    focus_wrapping: FocusWrapping,
+    assignments: Vec<Assignment>,
+    floating_order: HashMap<Uuid, Vec<Uuid>>,
+    headless_output: Option<WlcOutput>,
+    border_colors: BorderColors,
+    inactive_opacity: Option<f32>
+}
+
+impl SavedTree {
+    fn save(layout_tree: &LayoutTree) -> Self {
+        // Every field is named, so that adding one to the tree without
+        // deciding whether it's saved here doesn't compile. The ones that
+        // aren't saved are the hooks and the layout and transaction
+        // bookkeeping, which the transaction itself takes care of.
+        let LayoutTree {
+            ref tree,
+            active_container,
+            ref view_tags,
+            dynamic_workspaces,
+            hooks: _,
+            suspend_count: _,
+            pending_layout: _,
+            layout_depth: _,
+            scratchpad,
+            ref scratchpad_geometry,
+            inner_gap,
+            outer_gap,
+            smart_gaps,
+            ref last_focused,
+            global_fullscreen,
+            pending_split,
+            ref prev_workspace,
+            ref focus_history,
+            tiled_resize,
+            hide_edge_borders,
+            default_layout,
+            default_orientation,
+            ref pending_layouts,
+            ref default_background,
+            focus_follows_mouse,
+            pointer_view,
+            focus_wrapping,
+            ref assignments,
+            ref floating_order,
+            headless_output,
+            border_colors,
+            transaction_depth: _,
+            inactive_opacity
+        } = *layout_tree;
+        SavedTree {
+            tree: tree.clone(),
+            active_container: active_container,
+            view_tags: view_tags.clone(),
+            dynamic_workspaces: dynamic_workspaces,
+            scratchpad: scratchpad,
+            scratchpad_geometry: scratchpad_geometry.clone(),
+            inner_gap: inner_gap,
+            outer_gap: outer_gap,
+            smart_gaps: smart_gaps,
+            last_focused: last_focused.clone(),
+            global_fullscreen: global_fullscreen,
+            pending_split: pending_split,
+            prev_workspace: prev_workspace.clone(),
+            focus_history: focus_history.clone(),
+            tiled_resize: tiled_resize,
+            hide_edge_borders: hide_edge_borders,
+            default_layout: default_layout,
+            default_orientation: default_orientation,
+            pending_layouts: pending_layouts.clone(),
+            default_background: default_background.clone(),
+            focus_follows_mouse: focus_follows_mouse,
+            pointer_view: pointer_view,
+            focus_wrapping: focus_wrapping,
+            assignments: assignments.clone(),
+            floating_order: floating_order.clone(),
+            headless_output: headless_output,
+            border_colors: border_colors,
+            inactive_opacity: inactive_opacity
+        }
+    }
+
+    fn restore(self, tree: &mut LayoutTree) {
+        tree.tree = self.tree;
+        tree.active_container = self.active_container;
+        tree.view_tags = self.view_tags;
+        tree.dynamic_workspaces = self.dynamic_workspaces;
+        tree.scratchpad = self.scratchpad;
+        tree.scratchpad_geometry = self.scratchpad_geometry;
+        tree.inner_gap = self.inner_gap;
+        tree.outer_gap = self.outer_gap;
+        tree.smart_gaps = self.smart_gaps;
+        tree.last_focused = self.last_focused;
+        tree.global_fullscreen = self.global_fullscreen;
+        tree.pending_split = self.pending_split;
+        tree.prev_workspace = self.prev_workspace;
+        tree.focus_history = self.focus_history;
+        tree.tiled_resize = self.tiled_resize;
+        tree.hide_edge_borders = self.hide_edge_borders;
+        tree.default_layout = self.default_layout;
+        tree.default_orientation = self.default_orientation;
+        tree.pending_layouts = self.pending_layouts;
+        tree.default_background = self.default_background;
+        tree.focus_follows_mouse = self.focus_follows_mouse;
+        tree.pointer_view = self.pointer_view;
+        tree.focus_wrapping = self.focus_wrapping;
+        tree.assignments = self.assignments;
+        tree.floating_order = self.floating_order;
+        tree.headless_output = self.headless_output;
+        tree.border_colors = self.border_colors;
+        tree.inactive_opacity = self.inactive_opacity;
+    }
+}
+
+impl LayoutTree {
+    /// Runs the commands in the closure as one.
+    ///
+    /// The tree is only laid out and validated once, after all of them ran.
+    /// If the closure fails the tree is put back the way it was before it,
+    /// though hooks that ran in the meantime are not undone.
+    ///
+    /// Transactions can be nested, only the outermost one lays out the tree.
+    pub fn transaction<F>(&mut self, f: F) -> CommandResult
+        where F: FnOnce(&mut Self) -> CommandResult
+    {
+        let saved = SavedTree::save(self);
+        self.transaction_depth += 1;
+        self.suspend_layout();
+        let result = f(self);
+        self.transaction_depth -= 1;
+        if let Err(ref err) = result {
+            warn!("Transaction failed, rolling back: {:?}", err);
+            saved.restore(self);
+            self.restore_visibility();
+        }
+        // Laid out even if nothing asked for it, so views that were
+        // moved around are put back after a rollback too
+        self.pending_layout = true;
+        self.resume_layout();
+        if self.transaction_depth == 0 {
+            self.validate();
+        }
+        result
+    }
+
+    /// Whether commands are being run in a transaction,
+    /// in which case the tree isn't validated after each of them.
+    pub fn in_transaction(&self) -> bool {
+        self.transaction_depth > 0
+    }
+
+    /// Shows the views on the visible workspaces and hides the rest,
+    /// and focuses on the active view.
+    fn restore_visibility(&mut self) {
+        let root_ix = self.tree.root_ix();
+        for output_ix in self.tree.children_of(root_ix) {
+            for workspace_ix in self.tree.children_of(output_ix) {
+                let visible = self.tree.next_active_node(output_ix) == Some(workspace_ix);
+                self.set_container_visibility(workspace_ix, visible);
+            }
+        }
+        if let Some(active_ix) = self.active_container {
+            if let Container::View { handle, .. } = self.tree[active_ix] {
+                handle.focus();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::super::super::core::tree::tests::basic_tree;
+    use super::super::super::TreeError;
+    use rustwlc::WlcView;
+
+    #[test]
+    fn transactions_lay_out_once() {
+        let mut tree = basic_tree();
+        let reflows = Arc::new(Mutex::new(0));
+        {
+            let reflows = reflows.clone();
+            tree.hooks.add_reflow_hook(move |_| *reflows.lock().unwrap() += 1);
+        }
+        tree.transaction(|tree| {
+            tree.add_view(WlcView::dummy(1))?;
+            tree.add_view(WlcView::dummy(2))?;
+            assert!(tree.in_transaction());
+            Ok(())
+        }).unwrap();
+        assert!(!tree.in_transaction());
+        assert_eq!(*reflows.lock().unwrap(), 1);
+        assert!(tree.tree.lookup_view(WlcView::dummy(2)).is_some());
+        tree.validate();
+    }
+
+    #[test]
+    fn failed_transactions_roll_back() {
+        let mut tree = basic_tree();
+        let active_ix = tree.active_container;
+        let root_ix = tree.tree.root_ix();
+        let node_count = tree.tree.all_descendants_of(root_ix).len();
+        let result = tree.transaction(|tree| {
+            tree.add_view(WlcView::dummy(1))?;
+            tree.switch_to_workspace("3");
+            Err(TreeError::NoActiveContainer)
+        });
+        assert_eq!(result, Err(TreeError::NoActiveContainer));
+        assert_eq!(tree.active_container, active_ix);
+        assert_eq!(tree.current_workspace(), Ok("1"));
+        assert!(tree.tree.lookup_view(WlcView::dummy(1)).is_none());
+        assert!(tree.tree.workspace_ix_by_name("3").is_none());
+        assert_eq!(tree.tree.all_descendants_of(root_ix).len(), node_count);
+        tree.validate();
+
+        // The rest of the tree's state is put back too
+        let output = WlcView::root().as_output();
+        let result = tree.transaction(|tree| {
+            tree.remove_output(output)?;
+            assert!(tree.is_headless());
+            Err(TreeError::NoActiveContainer)
+        });
+        assert_eq!(result, Err(TreeError::NoActiveContainer));
+        assert!(!tree.is_headless());
+        tree.validate();
+    }
+}
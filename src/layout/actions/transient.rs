@@ -0,0 +1,146 @@
+//! Transient views, the dialogs that a client opens for one of its windows.
+//!
+//! A dialog whose parent is a tiled view floats centered over it, is raised
+//! with it when it's focused and follows it to other workspaces.
+
+use petgraph::graph::NodeIndex;
+use rustwlc::{Geometry, Point, ResizeEdge, WlcView};
+use uuid::Uuid;
+
+use super::super::LayoutTree;
+use super::super::core::TreeError;
+use super::super::core::container::{Container, ContainerType};
+use super::focus::FocusError;
+use ::layout::core::borders::Borders;
+
+impl LayoutTree {
+    /// Gets the tiled view that the view is a dialog of, if there is one.
+    pub fn transient_parent(&self, view: WlcView) -> Option<NodeIndex> {
+        let parent = view.get_parent();
+        if parent.is_root() {
+            return None
+        }
+        self.tree.lookup_view(parent)
+            .and_then(|parent_ix| match self.tree[parent_ix] {
+                Container::View { floating: false, .. } => Some(parent_ix),
+                _ => None
+            })
+    }
+
+    /// Adds the view as a dialog of the view at the node index, floating
+    /// it centered over its parent in the parent's workspace, and focuses it.
+    pub fn add_transient_view(&mut self, view: WlcView, parent_ix: NodeIndex)
+                              -> Result<&Container, TreeError> {
+        let parent_id = self.tree[parent_ix].get_id();
+        let parent_geometry = self.tree[parent_ix].get_geometry()
+            .ok_or(TreeError::NoGeometry(view))?;
+        let size = self.usable_geometry(view, parent_ix)?.size;
+        let geometry = Geometry {
+            origin: Point {
+                x: parent_geometry.origin.x +
+                    (parent_geometry.size.w as i32 - size.w as i32) / 2,
+                y: parent_geometry.origin.y +
+                    (parent_geometry.size.h as i32 - size.h as i32) / 2
+            },
+            size: size
+        };
+        let workspace_ix = self.tree.ancestor_of_type(parent_ix, ContainerType::Workspace)?;
+        let root_c_ix = self.tree.children_of(workspace_ix)[0];
+        let borders = Borders::new(geometry, view.get_output())
+            .map(|mut b| {
+                b.title = Container::get_title(view);
+                b
+            });
+        let view_ix = self.tree.add_child(root_c_ix,
+                                          Container::new_view(view, borders),
+                                          false);
+        {
+            let container = &mut self.tree[view_ix];
+            container.set_floating(true)
+                .expect("Could not float view we just made");
+            container.set_transient_for(Some(parent_id))
+                .expect("Could not make the view a dialog");
+            container.set_geometry(ResizeEdge::empty(), geometry);
+        }
+        self.validate();
+        match self.set_active_node(view_ix) {
+            Ok(_) => {},
+            Err(TreeError::Focus(FocusError::BlockedByFullscreen(_, _))) => {
+                info!("Blocked focus by fullscreen");
+            },
+            Err(err) => return Err(err)
+        }
+        Ok(&self.tree[view_ix])
+    }
+
+    /// Gets the dialogs of the view at the node index.
+    pub fn transients_of(&self, node_ix: NodeIndex) -> Vec<NodeIndex> {
+        let id = self.tree[node_ix].get_id();
+        let workspace_ix = match self.tree.ancestor_of_type(node_ix, ContainerType::Workspace) {
+            Ok(workspace_ix) => workspace_ix,
+            Err(_) => return vec![]
+        };
+        self.tree.all_descendants_of(workspace_ix).into_iter()
+            .filter(|&child_ix| self.tree[child_ix].transient_for() == Some(id))
+            .collect()
+    }
+
+    /// Gets the dialogs of the views at or beneath the node index,
+    /// that aren't beneath it themselves.
+    pub fn transients_beneath(&self, node_ix: NodeIndex) -> Vec<Uuid> {
+        let mut nodes = self.tree.all_descendants_of(node_ix);
+        nodes.push(node_ix);
+        nodes.iter()
+            .flat_map(|&child_ix| self.transients_of(child_ix))
+            .filter(|transient_ix| !nodes.contains(transient_ix))
+            .map(|transient_ix| self.tree[transient_ix].get_id())
+            .collect()
+    }
+
+    /// Brings the dialogs of the view at the node index in front of it.
+    pub fn raise_transients(&self, node_ix: NodeIndex) {
+        for transient_ix in self.transients_of(node_ix) {
+            if let Container::View { handle, .. } = self.tree[transient_ix] {
+                handle.bring_to_front();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::core::tree::tests::basic_tree;
+    use super::super::super::core::container::ContainerType;
+    use rustwlc::WlcView;
+
+    #[test]
+    fn dialogs_float_over_and_follow_their_parent() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let parent_ix = tree.active_container.unwrap();
+        let parent_id = tree.tree[parent_ix].get_id();
+        let dialog_id = tree.add_transient_view(WlcView::root(), parent_ix)
+            .unwrap().get_id();
+        let dialog_ix = tree.tree.lookup_id(dialog_id).unwrap();
+        assert!(tree.tree[dialog_ix].floating());
+        assert_eq!(tree.tree[dialog_ix].transient_for(), Some(parent_id));
+        assert_eq!(tree.transients_of(parent_ix), vec![dialog_ix]);
+        let parent_geometry = tree.tree[parent_ix].get_geometry().unwrap();
+        let geometry = tree.tree[dialog_ix].get_geometry().unwrap();
+        let center = |origin: i32, length: u32| origin + length as i32 / 2;
+        assert!((center(geometry.origin.x, geometry.size.w) -
+                 center(parent_geometry.origin.x, parent_geometry.size.w)).abs() <= 1);
+        assert!((center(geometry.origin.y, geometry.size.h) -
+                 center(parent_geometry.origin.y, parent_geometry.size.h)).abs() <= 1);
+        // It follows its parent to other workspaces
+        tree.send_to_workspace(parent_id, "3").unwrap();
+        let workspace_of = |tree: &::layout::LayoutTree, id| {
+            let node_ix = tree.tree.lookup_id(id).unwrap();
+            tree.tree.ancestor_of_type(node_ix, ContainerType::Workspace).unwrap()
+        };
+        let workspace_ix = tree.tree.workspace_ix_by_name("3").unwrap();
+        assert_eq!(workspace_of(&tree, parent_id), workspace_ix);
+        assert_eq!(workspace_of(&tree, dialog_id), workspace_ix);
+        tree.validate();
+    }
+}
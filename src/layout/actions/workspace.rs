@@ -1,9 +1,13 @@
-use rustwlc::WlcOutput;
+use rustwlc::{WlcView, WlcOutput};
 use petgraph::graph::NodeIndex;
 use uuid::Uuid;
 use super::super::{LayoutTree, TreeError, FocusError};
+use super::super::commands::CommandResult;
+use super::scratchpad::SCRATCHPAD_NAME;
+use super::layout::Orientation;
 use ::layout::core::container::{Container, ContainerType, Layout, Handle};
 use ::layout::core::borders::Borders;
+use ::render::Renderable;
 use ::debug_enabled;
 
 // TODO This module needs to be updated like the other modules...
@@ -30,9 +34,39 @@ impl LayoutTree {
     /// Initializes a workspace and gets the index of the root container
     pub fn init_workspace(&mut self, name: String, output_ix: NodeIndex)
                       -> NodeIndex {
+        let container_ix = self.make_workspace(name, output_ix);
+        self.tree.set_ancestor_paths_active(container_ix);
+        self.validate();
+        container_ix
+    }
+
+    /// Creates a named workspace on the output, without switching to it.
+    ///
+    /// Unlike `switch_to_workspace`, the active container and the visible
+    /// workspaces stay the same, so this can be used to set up workspaces
+    /// ahead of time. Workspace names are unique, so this fails if
+    /// one already has the name.
+    pub fn create_workspace(&mut self, name: String, output: WlcOutput) -> CommandResult {
+        if name.is_empty() || name == SCRATCHPAD_NAME {
+            return Err(TreeError::InvalidWorkspaceName(name))
+        }
+        if self.tree.workspace_ix_by_name(&name).is_some() {
+            return Err(TreeError::WorkspaceExists(name))
+        }
+        let root_ix = self.tree.root_ix();
+        let output_ix = self.tree.descendant_with_handle(root_ix, output.into())
+            .ok_or(TreeError::OutputNotFound(output))?;
+        self.make_workspace(name, output_ix);
+        self.validate();
+        Ok(())
+    }
+
+    /// Adds a workspace with its root container to the output,
+    /// and gets the index of the root container.
+    fn make_workspace(&mut self, name: String, output_ix: NodeIndex) -> NodeIndex {
         let geometry = self.tree.get(output_ix)
-            .expect("init_workspace: invalid output").get_geometry()
-            .expect("init_workspace: no geometry for output");
+            .expect("make_workspace: invalid output").get_geometry()
+            .expect("make_workspace: no geometry for output");
         let worksp = Container::new_workspace(name.to_string(), geometry);
         let output_handle = match self.tree[output_ix].get_handle() {
             Ok(Handle::Output(output)) => output,
@@ -47,11 +81,24 @@ impl LayoutTree {
                                                  output_handle,
                                                  borders);
         let container_ix = self.tree.add_child(worksp_ix, container, false);
-        self.tree.set_ancestor_paths_active(container_ix);
-        self.validate();
+        let layout = match self.default_layout {
+            Layout::Horizontal | Layout::Vertical
+                if self.default_orientation == Orientation::Auto =>
+                self.split_orientation(geometry),
+            layout => layout
+        };
+        self.tree[container_ix].set_layout(layout)
+            .expect("Root container could not have its layout set");
         container_ix
     }
 
+    /// Sets the layout the root container of new workspaces starts with.
+    ///
+    /// Workspaces that already exist keep their layout.
+    pub fn set_default_layout(&mut self, layout: Layout) {
+        self.default_layout = layout;
+    }
+
     /// Switch to the specified workspace
     pub fn switch_to_workspace(&mut self, name: &str) {
         let maybe_active_ix = self.active_container
@@ -93,6 +140,8 @@ impl LayoutTree {
         if old_worksp_ix == workspace_ix {
             return;
         }
+        self.prev_workspace = self.tree[old_worksp_ix].get_name()
+            .map(|name| name.to_string());
         {
             // Update the border colors
             let container = &mut self.tree[active_ix];
@@ -100,6 +149,8 @@ impl LayoutTree {
                 .expect("Could not clear old active border color");
             container.draw_borders().expect("Could not draw borders");
         }
+        self.gather_tagged_views(workspace_ix);
+        self.gather_sticky_views(old_worksp_ix, workspace_ix);
         let old_worksp_parent_ix = self.tree.parent_of(old_worksp_ix)
             .expect("Old workspace had no parent");
         let new_worksp_parent_ix = self.tree.parent_of(workspace_ix)
@@ -141,7 +192,8 @@ impl LayoutTree {
         }
         workspace_ix = self.tree.workspace_ix_by_name(name)
             .expect("Workspace we just made was deleted!");
-        let active_ix = self.tree.follow_path(workspace_ix);
+        let active_ix = self.last_focused_in(workspace_ix)
+            .unwrap_or_else(|| self.tree.follow_path(workspace_ix));
         match self.tree[active_ix].get_type() {
             ContainerType::View  => {
                 match self.tree[active_ix] {
@@ -192,31 +244,36 @@ impl LayoutTree {
     }
 
     /// Moves the active container to a new workspace.
-    pub fn send_active_to_workspace(&mut self, name: &str) {
-        if let Some(active_ix) = self.active_container {
-            let id = self.tree[active_ix].get_id();
-            self.send_to_workspace(id, name);
-        }
+    pub fn send_active_to_workspace(&mut self, name: &str) -> CommandResult {
+        let active_ix = self.active_container
+            .ok_or(TreeError::NoActiveContainer)?;
+        let id = self.tree[active_ix].get_id();
+        self.send_to_workspace(id, name)
     }
     /// Moves a container to a new workspace
-    pub fn send_to_workspace(&mut self, id: Uuid, name: &str) {
+    ///
+    /// Sending a container to the workspace it's already in does nothing.
+    /// The root container of a workspace can't be sent anywhere.
+    pub fn send_to_workspace(&mut self, id: Uuid, name: &str) -> CommandResult {
         let node_ix = self.tree.lookup_id(id);
         // Ensure focus
         // TODO Need to not make it default, but need to add tests to make
         // sure that doesn't cause a regression.
         if let Some(active_ix) = node_ix.or(self.active_container) {
-            let curr_work_ix = self.active_ix_of(ContainerType::Workspace)
-                .expect("send_active: Not currently in a workspace!");
+            let curr_work_ix = self.tree.ancestor_of_type(active_ix,
+                                                          ContainerType::Workspace)
+                .expect("send_to_workspace: Container was not in a workspace!");
             if active_ix == self.tree.children_of(curr_work_ix)[0] {
                 warn!("Tried to move the root container of a workspace, aborting move");
-                return;
+                let root_c_id = self.tree[active_ix].get_id();
+                return Err(TreeError::InvalidOperationOnRootContainer(root_c_id))
             }
             let next_work_ix = self.get_or_make_workspace(name);
 
             // Check if the workspaces are the same
             if next_work_ix == curr_work_ix {
                 trace!("Attempted to move a view to the same workspace {}!", name);
-                return;
+                return Ok(())
             }
             self.set_container_visibility(curr_work_ix, false);
             let new_output_ix = self.tree.parent_of(next_work_ix)
@@ -264,6 +321,8 @@ impl LayoutTree {
 
             // Save the parent of this view for focusing
             let maybe_active_parent = self.tree.parent_of(active_ix);
+            // Dialogs are moved along with their parents
+            let transients = self.transients_beneath(active_ix);
 
             // Get the root container of the next workspace
             let next_work_children = self.tree.children_of(next_work_ix);
@@ -299,8 +358,13 @@ impl LayoutTree {
                 self.container_visibilty_wrapper(new_output_ix, true);
             }
 
-            // If it's a fullscreen app, then update the fullscreen lists
-            self.transfer_fullscreen(curr_work_ix, next_work_ix, id);
+            // If it's a fullscreen app, or holds one, then update the fullscreen lists
+            let mut moved = self.tree.all_descendants_of(active_ix);
+            moved.push(active_ix);
+            for node_ix in moved {
+                let moved_id = self.tree[node_ix].get_id();
+                self.transfer_fullscreen(curr_work_ix, next_work_ix, moved_id);
+            }
 
             // Update the active container
             if let Ok(parent_ix) = maybe_active_parent {
@@ -326,11 +390,478 @@ impl LayoutTree {
             if !self.tree[active_ix].floating() {
                 self.normalize_container(active_ix).ok();
             }
+            for transient_id in transients {
+                self.send_to_workspace(transient_id, name)?;
+            }
+        } else {
+            return Err(TreeError::NodeNotFound(id))
         }
         let root_ix = self.tree.root_ix();
         self.layout(root_ix);
         self.validate();
         self.validate_path();
+        Ok(())
+    }
+
+    /// Sets whether workspaces are dynamic.
+    ///
+    /// When they are, removing the last view from a workspace that is not
+    /// the first one on its output removes the workspace. If it was being
+    /// viewed, the workspace before it is switched to.
+    pub fn set_dynamic_workspaces(&mut self, dynamic: bool) {
+        self.dynamic_workspaces = dynamic;
+    }
+
+    /// Removes the workspace if workspaces are dynamic and it no longer
+    /// has any views in it.
+    pub fn close_dynamic_workspace(&mut self, workspace_ix: NodeIndex) {
+        if !self.dynamic_workspaces {
+            return
+        }
+        if self.tree.node_type(workspace_ix) != Some(ContainerType::Workspace) {
+            return
+        }
+        if self.is_scratchpad(workspace_ix) {
+            return
+        }
+        if self.tree.descendant_of_type(workspace_ix, ContainerType::View).is_ok() {
+            return
+        }
+        let output_ix = self.tree.parent_of(workspace_ix)
+            .expect("Workspace had no output");
+        let siblings = self.tree.children_of(output_ix);
+        let index = siblings.iter().position(|ix| *ix == workspace_ix)
+            .expect("Workspace was not a child of its output");
+        // The first workspace on an output is always kept around
+        if index == 0 {
+            return
+        }
+        if self.active_ix_of(ContainerType::Workspace) == Some(workspace_ix) {
+            // Switching away from an empty workspace removes it
+            let prev_name = self.tree[siblings[index - 1]].name();
+            trace!("Closing dynamic workspace, switching to {}", prev_name);
+            self.switch_to_workspace(prev_name.as_str());
+        } else if let Err(err) = self.remove_workspace(workspace_ix) {
+            warn!("Could not remove empty dynamic workspace {:?}: {:?}",
+                  workspace_ix, err);
+        }
+    }
+
+    /// Adds all the views to the workspace with the given name, laying out
+    /// the tree only once after they have all been added.
+    ///
+    /// The workspace is made if it doesn't exist yet.
+    /// Returns the ids of the added views, in the same order.
+    pub fn add_views_batch(&mut self, views: Vec<WlcView>, workspace: &str)
+                           -> Result<Vec<Uuid>, TreeError> {
+        let workspace_ix = self.get_or_make_workspace(workspace);
+        let root_ix = self.tree.children_of(workspace_ix)[0];
+        let draw_title = match self.tree[root_ix].get_layout()? {
+            Layout::Tabbed | Layout::Stacked => false,
+            Layout::Horizontal | Layout::Vertical => true
+        };
+        // Find every geometry up front, so nothing is added if one is missing
+        let geometries = views.iter()
+            .map(|&view| self.usable_geometry(view, root_ix))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut ids = Vec::with_capacity(views.len());
+        self.suspend_layout();
+        for (view, geometry) in views.into_iter().zip(geometries) {
+            let borders = Borders::new(geometry, view.get_output())
+                .map(|mut b| {
+                    b.title = Container::get_title(view);
+                    b.draw_title = draw_title;
+                    b
+                });
+            let view_ix = self.tree.add_child(root_ix,
+                                              Container::new_view(view, borders),
+                                              false);
+            ids.push(self.tree[view_ix].get_id());
+        }
+        self.layout(root_ix);
+        self.resume_layout();
+        self.validate();
+        Ok(ids)
+    }
+
+    /// Resolves the workspace with the given name and runs `f` with its index.
+    ///
+    /// If no workspace has that name, `f` is not run and an error is returned.
+    pub fn with_workspace<F, R>(&mut self, name: &str, f: F) -> Result<R, TreeError>
+        where F: FnOnce(&mut LayoutTree, NodeIndex) -> R
+    {
+        let workspace_ix = self.lookup_workspace(name)?;
+        Ok(f(self, workspace_ix))
+    }
+
+    /// Renames the workspace, keeping whatever was focused focused.
+    ///
+    /// Workspace names are unique, so this fails if another workspace
+    /// already has the new name. Empty names are not allowed.
+    pub fn rename_workspace(&mut self, old: &str, new: String) -> CommandResult {
+        if new.is_empty() || new == SCRATCHPAD_NAME {
+            return Err(TreeError::InvalidWorkspaceName(new))
+        }
+        if old == new {
+            return self.lookup_workspace(old).map(|_| ())
+        }
+        if self.tree.workspace_ix_by_name(&new).is_some() {
+            return Err(TreeError::WorkspaceExists(new))
+        }
+        self.with_workspace(old, |this, workspace_ix| {
+            this.tree[workspace_ix].set_name(new.clone());
+            // Views tagged with the workspace follow the new name
+            for tags in this.view_tags.values_mut() {
+                for tag in tags.iter_mut() {
+                    if tag.as_str() == old {
+                        *tag = new.clone();
+                    }
+                }
+            }
+            if this.prev_workspace.as_ref().map(|name| name.as_str()) == Some(old) {
+                this.prev_workspace = Some(new.clone());
+            }
+        })
+    }
+
+    /// Switches back to the workspace that was active before the current one.
+    ///
+    /// Calling this again switches back again, so it goes back and forth
+    /// between the two. Does nothing if no other workspace has been active.
+    pub fn switch_to_prev_workspace(&mut self) -> CommandResult {
+        if let Some(name) = self.prev_workspace.clone() {
+            self.switch_to_workspace(&name);
+        }
+        Ok(())
+    }
+
+    /// Sets whether the view wants the user's attention, which makes
+    /// its workspace urgent too.
+    ///
+    /// The focused view is never made urgent, since the user is already
+    /// looking at it.
+    pub fn set_urgent(&mut self, view: WlcView, urgent: bool) -> CommandResult {
+        let node_ix = self.tree.lookup_view(view)
+            .ok_or(TreeError::ViewNotFound(view))?;
+        if urgent && self.active_container == Some(node_ix) {
+            return Ok(())
+        }
+        self.tree[node_ix].set_urgent(urgent)
+            .expect("View could not be made urgent");
+        // Draw it in the urgent color, or back in the normal one
+        if !self.tree.on_path(node_ix) {
+            let container = &mut self.tree[node_ix];
+            container.clear_border_color()?;
+            self.border_colors.apply(container, false);
+            container.draw_borders()?;
+        }
+        Ok(())
+    }
+
+    /// Determines if the container behind the UUID wants the user's attention.
+    ///
+    /// Workspaces and containers are urgent if any view in them is.
+    pub fn is_urgent(&self, id: Uuid) -> Result<bool, TreeError> {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        Ok(self.tree[node_ix].urgent() ||
+           self.tree.all_descendants_of(node_ix).into_iter()
+           .any(|child_ix| self.tree[child_ix].urgent()))
+    }
+
+    /// Gets the names of the workspaces that have an urgent view in them,
+    /// so the bar can point them out.
+    pub fn urgent_workspaces(&self) -> Vec<String> {
+        let root_ix = self.tree.root_ix();
+        self.tree.all_descendants_of(root_ix).into_iter()
+            .filter(|&node_ix| self.tree[node_ix].get_type() == ContainerType::Workspace)
+            .filter(|&node_ix| self.is_urgent(self.tree[node_ix].get_id()).unwrap_or(false))
+            .filter_map(|node_ix| self.tree[node_ix].get_name().map(|name| name.to_string()))
+            .collect()
+    }
+
+    /// Gets the ids of all the views in the workspace with the given name,
+    /// including floating views.
+    pub fn views_in_workspace(&self, name: &str) -> Result<Vec<Uuid>, TreeError> {
+        let workspace_ix = self.lookup_workspace(name)?;
+        Ok(self.tree.all_descendants_of(workspace_ix).into_iter()
+           .filter(|ix| self.tree[*ix].get_type() == ContainerType::View)
+           .map(|ix| self.tree[ix].get_id())
+           .collect())
+    }
+
+    /// Closes every view in the workspace with the given name and removes
+    /// them from the tree, leaving only its empty root container.
+    ///
+    /// The workspace itself is kept, even if workspaces are dynamic.
+    pub fn close_workspace_windows(&mut self, name: &str) -> CommandResult {
+        let workspace_ix = self.lookup_workspace(name)?;
+        let views: Vec<NodeIndex> = self.tree.all_descendants_of(workspace_ix).into_iter()
+            .filter(|&node_ix| self.tree[node_ix].get_type() == ContainerType::View)
+            .collect();
+        let was_active = self.active_container
+            .map(|active_ix| self.tree.ancestor_of_type(active_ix, ContainerType::Workspace)
+                 .ok() == Some(workspace_ix))
+            .unwrap_or(false);
+        for view_ix in views {
+            match self.tree[view_ix] {
+                Container::View { handle, .. } => handle.close(),
+                _ => unreachable!()
+            }
+            self.remove_node_no_refocus(view_ix)?;
+        }
+        let root_c_ix = self.tree.children_of(workspace_ix)[0];
+        if was_active {
+            self.active_container = None;
+            self.focus_on_next_container(root_c_ix);
+        }
+        self.layout(workspace_ix);
+        self.validate();
+        Ok(())
+    }
+
+    /// Associates a view with several workspaces (tags), so that it is
+    /// shown on each of them when they are viewed.
+    ///
+    /// A view can only live in one workspace at a time, so when one of its
+    /// tags is switched to the view is moved into that workspace.
+    pub fn add_view_to_tags(&mut self, id: Uuid, tags: Vec<String>) -> CommandResult {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        if self.tree[node_ix].get_type() != ContainerType::View {
+            return Err(TreeError::UuidWrongType(id, vec![ContainerType::View]))
+        }
+        self.view_tags.insert(id, tags);
+        Ok(())
+    }
+
+    /// Gets the ids of the views on the tag, in tree order.
+    ///
+    /// Those are the views in the workspace with the tag's name,
+    /// and the views tagged with it wherever they are.
+    pub fn views_on_tag(&self, name: &str) -> Vec<Uuid> {
+        let root_ix = self.tree.root_ix();
+        let workspace_ix = self.tree.workspace_ix_by_name(name);
+        self.tree.all_descendants_of(root_ix).into_iter()
+            .filter(|&node_ix| self.tree[node_ix].get_type() == ContainerType::View)
+            .filter(|&node_ix| {
+                let id = self.tree[node_ix].get_id();
+                let tagged = self.view_tags.get(&id)
+                    .map(|tags| tags.iter().any(|tag| tag == name))
+                    .unwrap_or(false);
+                tagged || (workspace_ix.is_some() &&
+                           self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)
+                           .ok() == workspace_ix)
+            })
+            .map(|node_ix| self.tree[node_ix].get_id())
+            .collect()
+    }
+
+    /// Makes the floating view behind the UUID follow the user between
+    /// workspaces, or stop following them if it already was.
+    pub fn toggle_sticky(&mut self, id: Uuid) -> CommandResult {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        if self.tree[node_ix].get_type() != ContainerType::View {
+            return Err(TreeError::UuidWrongType(id, vec!(ContainerType::View)))
+        }
+        if !self.tree[node_ix].floating() {
+            return Err(TreeError::ContainerWasNotFloating(node_ix))
+        }
+        let sticky = self.tree[node_ix].sticky();
+        self.tree[node_ix].set_sticky(!sticky)
+            .expect("View could not be made sticky");
+        Ok(())
+    }
+
+    /// Moves the sticky views from the old workspace into the new one,
+    /// if they are shown on the same output.
+    fn gather_sticky_views(&mut self, old_workspace_ix: NodeIndex, workspace_ix: NodeIndex) {
+        if self.tree.parent_of(old_workspace_ix).ok() != self.tree.parent_of(workspace_ix).ok() {
+            return
+        }
+        let old_root_ix = self.tree.children_of(old_workspace_ix)[0];
+        let root_ix = self.tree.children_of(workspace_ix)[0];
+        for node_ix in self.tree.floating_children(old_root_ix) {
+            if self.tree[node_ix].sticky() {
+                trace!("Moving sticky view {:?} to {:?}", node_ix, workspace_ix);
+                self.tree.move_node(node_ix, root_ix);
+            }
+        }
+    }
+
+    /// Shows the views on all of the given tags at once, like selecting
+    /// several tags in awesome.
+    ///
+    /// The first tag is switched to like a workspace, and every view tagged
+    /// with any of the tags is gathered into it. Tagged views in it that
+    /// have none of the tags are sent to the workspace of their first tag.
+    /// Does nothing if no tags are given.
+    pub fn show_tags(&mut self, tags: &[String]) -> CommandResult {
+        let name = match tags.first() {
+            Some(name) => name,
+            None => return Ok(())
+        };
+        self.switch_to_workspace(name);
+        let workspace_ix = self.lookup_workspace(name)?;
+        for tag in tags {
+            self.gather_views_tagged(workspace_ix, tag);
+        }
+        let hidden: Vec<(Uuid, String)> = self.tree.all_descendants_of(workspace_ix)
+            .into_iter()
+            .filter(|&node_ix| self.tree[node_ix].get_type() == ContainerType::View)
+            .filter_map(|node_ix| {
+                let id = self.tree[node_ix].get_id();
+                let view_tags = self.view_tags.get(&id)?;
+                if view_tags.iter().any(|tag| tags.contains(tag)) {
+                    return None
+                }
+                view_tags.first().map(|tag| (id, tag.clone()))
+            })
+            .collect();
+        for (id, tag) in hidden {
+            trace!("Hiding view {:?} on unselected tag {}", id, tag);
+            self.send_to_workspace(id, &tag)?;
+        }
+        self.layout(workspace_ix);
+        self.validate();
+        Ok(())
+    }
+
+    /// Moves every view tagged with the name of the workspace into it.
+    fn gather_tagged_views(&mut self, workspace_ix: NodeIndex) {
+        let name = self.tree[workspace_ix].name();
+        self.gather_views_tagged(workspace_ix, &name);
+    }
+
+    /// Moves every view tagged with the name into the workspace.
+    fn gather_views_tagged(&mut self, workspace_ix: NodeIndex, name: &str) {
+        let root_ix = self.tree.children_of(workspace_ix)[0];
+        let ids: Vec<Uuid> = self.view_tags.iter()
+            .filter(|&(_, tags)| tags.iter().any(|tag| tag == name))
+            .map(|(id, _)| *id)
+            .collect();
+        for id in ids {
+            let node_ix = match self.tree.lookup_id(id) {
+                Some(node_ix) => node_ix,
+                None => {
+                    self.view_tags.remove(&id);
+                    continue
+                }
+            };
+            if self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)
+                == Ok(workspace_ix) {
+                continue
+            }
+            let parent_ix = self.tree.parent_of(node_ix)
+                .expect("Tagged view had no parent");
+            trace!("Moving tagged view {:?} to workspace {}", id, name);
+            self.tree.move_node(node_ix, root_ix);
+            if self.tree.can_remove_empty_parent(parent_ix) {
+                if let Err(err) = self.remove_view_or_container(parent_ix) {
+                    error!("Could not remove empty parent {:?}: {:?}", parent_ix, err);
+                }
+            }
+        }
+    }
+
+    /// Gets the container that was last focused in the workspace.
+    ///
+    /// If it has since been removed or moved to another workspace,
+    /// the root container of the workspace is returned instead.
+    /// If nothing has been focused there yet, returns `None`.
+    fn last_focused_in(&self, workspace_ix: NodeIndex) -> Option<NodeIndex> {
+        let workspace_id = self.tree[workspace_ix].get_id();
+        let last_id = match self.last_focused.get(&workspace_id) {
+            Some(last_id) => *last_id,
+            None => return None
+        };
+        match self.tree.lookup_id(last_id) {
+            Some(node_ix) if self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)
+                == Ok(workspace_ix) => Some(node_ix),
+            _ => Some(self.tree.children_of(workspace_ix)[0])
+        }
+    }
+
+    /// Moves a container to the active workspace of the given output.
+    ///
+    /// Both the source and destination workspaces are laid out again,
+    /// and if the move left a non-root container empty it is removed.
+    pub fn send_to_output(&mut self, id: Uuid, output: WlcOutput) -> CommandResult {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        match self.tree[node_ix].get_type() {
+            ContainerType::View | ContainerType::Container => {},
+            _ => return Err(TreeError::UuidWrongType(id, vec![ContainerType::View,
+                                                              ContainerType::Container]))
+        }
+        if self.tree.is_root_container(node_ix) {
+            return Err(TreeError::InvalidOperationOnRootContainer(id))
+        }
+        let root_ix = self.tree.root_ix();
+        let output_ix = self.tree.descendant_with_handle(root_ix, output.into())
+            .ok_or(TreeError::OutputNotFound(output))?;
+        let workspace_ix = self.tree.follow_path_until(output_ix, ContainerType::Workspace)
+            .map_err(|_| TreeError::UuidNotAssociatedWith(ContainerType::Workspace))?;
+        let name = self.tree[workspace_ix].get_name()
+            .expect("Workspace had no name").to_string();
+        self.send_to_workspace(id, name.as_str())
+    }
+
+    /// Moves a container to the visible workspace of the given output,
+    /// like `send_to_output`.
+    ///
+    /// If the output has no workspace yet, its default one is made first,
+    /// named after the output the same way `add_output` does.
+    pub fn move_container_to_output(&mut self, id: Uuid, output: WlcOutput) -> CommandResult {
+        let root_ix = self.tree.root_ix();
+        let output_ix = self.tree.descendant_with_handle(root_ix, output.into())
+            .ok_or(TreeError::OutputNotFound(output))?;
+        if self.tree.follow_path_until(output_ix, ContainerType::Workspace).is_err() {
+            let name = output.0.to_string();
+            if self.tree.workspace_ix_by_name(&name).is_some() {
+                return Err(TreeError::WorkspaceExists(name))
+            }
+            self.init_workspace(name, output_ix);
+        }
+        self.send_to_output(id, output)
+    }
+
+    /// Moves a container to the workspace with the name on the container's
+    /// own output, making the workspace there if it doesn't exist yet.
+    ///
+    /// Workspace names are global, so if the workspace is on another output
+    /// nothing is moved. If the container was active, the workspace is
+    /// switched to so that it stays active.
+    pub fn move_to_workspace_on_active_output(&mut self, id: Uuid, workspace: &str)
+                                              -> CommandResult {
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        let output_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Output)?;
+        match self.tree.workspace_ix_by_name(workspace) {
+            Some(workspace_ix) => {
+                if self.tree.parent_of(workspace_ix)? != output_ix {
+                    return Err(TreeError::WorkspaceOnOtherOutput(workspace.into()))
+                }
+            },
+            None => {
+                let output = match self.tree[output_ix].get_handle()? {
+                    Handle::Output(output) => output,
+                    _ => unreachable!()
+                };
+                self.create_workspace(workspace.into(), output)?;
+            }
+        }
+        let was_active = self.active_container == Some(node_ix);
+        self.send_to_workspace(id, workspace)?;
+        if was_active {
+            self.switch_to_workspace(workspace);
+            let node_ix = self.tree.lookup_id(id)
+                .ok_or(TreeError::NodeNotFound(id))?;
+            self.set_active_node(node_ix)?;
+        }
+        self.validate();
+        Ok(())
     }
 
     /// Transfers a fullscreen app from this workspace to another.
@@ -394,7 +925,12 @@ impl LayoutTree {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use rustwlc::WlcView;
+    use ::layout::core::container::{Container, ContainerType, Handle, Layout};
     use ::layout::core::tree::tests::basic_tree;
+    use ::layout::{LayoutTree, TreeError};
 
     #[test]
     pub fn switch_empty_workspaces() {
@@ -405,4 +941,443 @@ mod tests {
         tree.switch_to_workspace("4");
         tree.switch_to_workspace("2");
     }
+
+    #[test]
+    fn switching_back_restores_last_focused() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let first_ix = tree.active_container.unwrap();
+        let parent_ix = tree.tree.parent_of(first_ix).unwrap();
+        let second_ix = *tree.tree.children_of(parent_ix).iter()
+            .find(|&&child_ix| child_ix != first_ix).unwrap();
+        tree.set_active_node(second_ix).unwrap();
+        tree.switch_to_workspace("1");
+        // Point the active path somewhere else, it shouldn't matter
+        tree.tree.set_ancestor_paths_active(first_ix);
+        tree.switch_to_workspace("2");
+        assert_eq!(tree.active_container, Some(second_ix));
+
+        // If it's gone, something in the workspace is focused instead
+        tree.switch_to_workspace("1");
+        tree.remove_view_or_container(second_ix).unwrap();
+        tree.switch_to_workspace("2");
+        let active_ix = tree.active_container.unwrap();
+        assert_eq!(tree.tree[active_ix].get_type(), ContainerType::View);
+        assert_eq!(tree.tree.workspace_ix_by_name("2"),
+                   tree.tree.ancestor_of_type(active_ix, ContainerType::Workspace).ok());
+    }
+
+    #[test]
+    fn rename_workspace_keeps_focus() {
+        let mut tree = basic_tree();
+        let active_ix = tree.active_container;
+        let id = tree.get_active_container().unwrap().get_id();
+        tree.add_view_to_tags(id, vec!["1".into(), "2".into()]).unwrap();
+        tree.rename_workspace("1", "web".into()).unwrap();
+        assert!(tree.tree.workspace_ix_by_name("1").is_none());
+        assert!(tree.views_in_workspace("web").unwrap().contains(&id));
+        assert_eq!(tree.current_workspace(), Ok("web"));
+        assert_eq!(tree.active_container, active_ix);
+        assert_eq!(tree.view_tags.get(&id), Some(&vec!["web".to_string(), "2".to_string()]));
+
+        assert_eq!(tree.rename_workspace("web", "2".into()),
+                   Err(TreeError::WorkspaceExists("2".into())));
+        assert_eq!(tree.rename_workspace("web", "".into()),
+                   Err(TreeError::InvalidWorkspaceName("".into())));
+        assert_eq!(tree.rename_workspace("1", "3".into()),
+                   Err(TreeError::UuidNotAssociatedWith(ContainerType::Workspace)));
+        tree.validate();
+    }
+
+    #[test]
+    fn sticky_views_follow_workspace_switches() {
+        let mut tree = basic_tree();
+        let id = tree.get_active_container().unwrap().get_id();
+        let node_ix = tree.tree.lookup_id(id).unwrap();
+        assert_eq!(tree.toggle_sticky(id), Err(TreeError::ContainerWasNotFloating(node_ix)));
+        tree.float_container(id).unwrap();
+        tree.toggle_sticky(id).unwrap();
+        assert!(tree.lookup(id).unwrap().sticky());
+        tree.switch_to_workspace("2");
+        assert!(tree.views_in_workspace("2").unwrap().contains(&id));
+        tree.switch_to_workspace("3");
+        assert!(tree.views_in_workspace("3").unwrap().contains(&id));
+        // Grounding it makes it stay put
+        tree.ground_container(id).unwrap();
+        assert!(!tree.lookup(id).unwrap().sticky());
+        tree.switch_to_workspace("2");
+        assert!(!tree.views_in_workspace("2").unwrap().contains(&id));
+        tree.validate();
+    }
+
+    #[test]
+    fn view_follows_its_tags() {
+        let mut tree = basic_tree();
+        let id = tree.get_active_container().unwrap().get_id();
+        tree.add_view_to_tags(id, vec!["1".into(), "2".into()]).unwrap();
+        tree.switch_to_workspace("2");
+        assert!(tree.views_in_workspace("2").unwrap().contains(&id));
+        tree.switch_to_workspace("1");
+        assert!(tree.views_in_workspace("1").unwrap().contains(&id));
+        assert!(!tree.views_in_workspace("2").unwrap().contains(&id));
+        tree.switch_to_workspace("2");
+        assert!(tree.views_in_workspace("2").unwrap().contains(&id));
+        // Not tagged with 3, so it stays behind
+        tree.switch_to_workspace("3");
+        assert!(tree.views_in_workspace("2").unwrap().contains(&id));
+        // Can only tag views
+        let root_ix = tree.tree.root_ix();
+        let output_id = tree.tree[tree.tree.children_of(root_ix)[0]].get_id();
+        assert!(tree.add_view_to_tags(output_id, vec!["1".into()]).is_err());
+    }
+
+    #[test]
+    fn views_on_tag_test() {
+        let mut tree = basic_tree();
+        let id = tree.get_active_container().unwrap().get_id();
+        assert_eq!(tree.views_on_tag("1"), vec![id]);
+        assert_eq!(tree.views_on_tag("2").len(), 2);
+        assert!(tree.views_on_tag("web").is_empty());
+        // Tagged views are on the tag wherever they are
+        tree.add_view_to_tags(id, vec!["1".into(), "web".into()]).unwrap();
+        assert_eq!(tree.views_on_tag("web"), vec![id]);
+        tree.send_to_workspace(id, "2").unwrap();
+        assert!(tree.views_on_tag("2").contains(&id));
+        assert_eq!(tree.views_on_tag("web"), vec![id]);
+        let node_ix = tree.tree.lookup_id(id).unwrap();
+        tree.remove_view_or_container(node_ix).unwrap();
+        assert!(tree.views_on_tag("web").is_empty());
+    }
+
+    #[test]
+    fn dynamic_workspace_closes_when_empty() {
+        let mut tree = basic_tree();
+        tree.set_dynamic_workspaces(true);
+        tree.switch_to_workspace("3");
+        tree.add_view(WlcView::root()).unwrap();
+        assert!(tree.tree.workspace_ix_by_name("3").is_some());
+        tree.remove_active().unwrap();
+        assert!(tree.tree.workspace_ix_by_name("3").is_none());
+        assert_eq!(tree.current_workspace(), Ok("2"));
+        // The first workspace is never removed
+        tree.switch_to_workspace("1");
+        tree.remove_active().unwrap();
+        assert!(tree.tree.workspace_ix_by_name("1").is_some());
+    }
+
+    #[test]
+    fn static_workspace_stays_when_empty() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("3");
+        tree.add_view(WlcView::root()).unwrap();
+        tree.remove_active().unwrap();
+        assert!(tree.tree.workspace_ix_by_name("3").is_some());
+        assert_eq!(tree.current_workspace(), Ok("3"));
+    }
+
+    #[test]
+    fn add_views_batch_reflows_once() {
+        let mut tree = basic_tree();
+        let reflows = Arc::new(AtomicUsize::new(0));
+        {
+            let reflows = reflows.clone();
+            tree.hooks.add_reflow_hook(move |_| {
+                reflows.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        // Views without a geometry of their own fall back to the workspace's
+        let views = vec![WlcView::root(), WlcView::root(), WlcView::dummy(7)];
+        let ids = tree.add_views_batch(views, "3").unwrap();
+        assert_eq!(ids.len(), 3);
+        let in_workspace = tree.views_in_workspace("3").unwrap();
+        for id in &ids {
+            assert!(in_workspace.contains(id));
+        }
+        assert_eq!(reflows.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn with_workspace_unknown_name() {
+        let mut tree = basic_tree();
+        let result = tree.with_workspace("not a workspace", |_, _| ());
+        assert_eq!(result,
+                   Err(TreeError::UuidNotAssociatedWith(ContainerType::Workspace)));
+        // Listing views only needs a shared borrow
+        let shared: &LayoutTree = &tree;
+        assert_eq!(shared.lookup_workspace("2"),
+                   Ok(shared.tree.workspace_ix_by_name("2").unwrap()));
+        assert_eq!(shared.views_in_workspace("2").unwrap().len(), 2);
+        assert!(shared.views_in_workspace("3").is_err());
+    }
+
+    #[test]
+    fn send_to_output_reflows_both_workspaces() {
+        let mut tree = basic_tree();
+        let new_output = WlcView::dummy(5).as_output();
+        tree.add_output(new_output).unwrap();
+        tree.switch_to_workspace("2");
+        let source_work_ix = tree.tree.workspace_ix_by_name("2").unwrap();
+        let source_root_ix = tree.tree.children_of(source_work_ix)[0];
+        let sub_container_ix = tree.tree.children_of(source_root_ix)[0];
+        assert_eq!(tree.tree[sub_container_ix].get_type(), ContainerType::Container);
+        // A view next to the sub container, that stays behind
+        let source_root_id = tree.tree[source_root_ix].get_id();
+        tree.set_active_container(source_root_id).unwrap();
+        let kept_id = tree.add_view(WlcView::root()).unwrap().get_id();
+        let moved_ids: Vec<_> = tree.tree.children_of(sub_container_ix).iter()
+            .map(|ix| tree.tree[*ix].get_id())
+            .collect();
+        assert_eq!(moved_ids.len(), 2);
+        for id in &moved_ids {
+            tree.send_to_output(*id, new_output).unwrap();
+        }
+        // The emptied non-root container was cleaned up
+        let source_work_ix = tree.tree.workspace_ix_by_name("2").unwrap();
+        let source_root_ix = tree.tree.children_of(source_work_ix)[0];
+        let source_children = tree.tree.children_of(source_root_ix);
+        assert_eq!(source_children.len(), 1);
+        assert_eq!(tree.tree[source_children[0]].get_id(), kept_id);
+        // and the view left behind fills the source workspace
+        let source_geo = tree.tree[source_root_ix].get_geometry().unwrap();
+        assert_eq!(tree.tree[source_children[0]].get_geometry().unwrap().size,
+                   source_geo.size);
+        // Both views are now tiled side by side in the destination workspace
+        let dest_work_ix = tree.tree.workspace_ix_by_name("5").unwrap();
+        let dest_root_ix = tree.tree.children_of(dest_work_ix)[0];
+        let dest_children = tree.tree.children_of(dest_root_ix);
+        assert_eq!(dest_children.len(), 2);
+        let dest_geo = tree.tree[dest_root_ix].get_geometry().unwrap();
+        for (child_ix, id) in dest_children.iter().zip(moved_ids.iter()) {
+            assert_eq!(tree.tree[*child_ix].get_id(), *id);
+            let geo = tree.tree[*child_ix].get_geometry().unwrap();
+            assert!((geo.size.w as i32 - dest_geo.size.w as i32 / 2).abs() <= 1);
+            assert_eq!(geo.size.h, dest_geo.size.h);
+        }
+        tree.validate();
+        tree.validate_path();
+    }
+
+    #[test]
+    fn send_to_output_rejects_root_container() {
+        let mut tree = basic_tree();
+        let new_output = WlcView::dummy(5).as_output();
+        tree.add_output(new_output).unwrap();
+        let root_id = tree.tree[tree.active_container.unwrap()].get_id();
+        assert!(tree.send_to_output(root_id, new_output).is_err());
+    }
+
+    #[test]
+    fn move_container_to_output_makes_workspace() {
+        let mut tree = basic_tree();
+        let id = tree.get_active_container().unwrap().get_id();
+        // An output that has no workspaces on it yet
+        let new_output = WlcView::dummy(6).as_output();
+        let root_ix = tree.tree.root_ix();
+        tree.tree.add_child(root_ix, Container::new_output(new_output), false);
+        tree.move_container_to_output(id, new_output).unwrap();
+        let work_ix = tree.tree.workspace_ix_by_name(&new_output.0.to_string()).unwrap();
+        let output_ix = tree.tree.ancestor_of_type(work_ix, ContainerType::Output).unwrap();
+        assert_eq!(tree.tree[output_ix].get_handle(), Ok(Handle::Output(new_output)));
+        let node_ix = tree.tree.lookup_id(id).unwrap();
+        assert_eq!(tree.tree.ancestor_of_type(node_ix, ContainerType::Workspace), Ok(work_ix));
+
+        let unknown = WlcView::dummy(7).as_output();
+        assert_eq!(tree.move_container_to_output(id, unknown),
+                   Err(TreeError::OutputNotFound(unknown)));
+        tree.validate();
+    }
+
+    #[test]
+    fn create_workspace_does_not_switch() {
+        let mut tree = basic_tree();
+        let active_ix = tree.active_container;
+        let output_ix = tree.tree.follow_path_until(tree.tree.root_ix(),
+                                                    ContainerType::Output).unwrap();
+        let output = match tree.tree[output_ix].get_handle() {
+            Ok(Handle::Output(output)) => output,
+            _ => panic!("Output had no output handle")
+        };
+        tree.create_workspace("5".into(), output).unwrap();
+        let work_ix = tree.tree.workspace_ix_by_name("5").unwrap();
+        assert_eq!(tree.tree.ancestor_of_type(work_ix, ContainerType::Output),
+                   Ok(output_ix));
+        assert_eq!(tree.tree.children_of(work_ix).len(), 1);
+        assert_eq!(tree.active_container, active_ix);
+        assert_eq!(tree.current_workspace(), Ok("1"));
+
+        assert_eq!(tree.create_workspace("2".into(), output),
+                   Err(TreeError::WorkspaceExists("2".into())));
+        assert_eq!(tree.create_workspace("".into(), output),
+                   Err(TreeError::InvalidWorkspaceName("".into())));
+        let unknown = WlcView::dummy(7).as_output();
+        assert_eq!(tree.create_workspace("6".into(), unknown),
+                   Err(TreeError::OutputNotFound(unknown)));
+        // Switching to it later uses the one that was made
+        tree.switch_to_workspace("5");
+        assert_eq!(tree.tree.workspace_ix_by_name("5"), Some(work_ix));
+        tree.validate();
+    }
+
+    #[test]
+    fn move_to_workspace_on_active_output_keeps_output() {
+        let mut tree = basic_tree();
+        let id = tree.add_view(WlcView::dummy(1)).unwrap().get_id();
+        let node_ix = tree.tree.lookup_id(id).unwrap();
+        let output_ix = tree.tree.ancestor_of_type(node_ix, ContainerType::Output).unwrap();
+        tree.move_to_workspace_on_active_output(id, "5").unwrap();
+        let work_ix = tree.tree.workspace_ix_by_name("5").unwrap();
+        assert_eq!(tree.tree.parent_of(work_ix), Ok(output_ix));
+        let node_ix = tree.tree.lookup_id(id).unwrap();
+        assert_eq!(tree.tree.ancestor_of_type(node_ix, ContainerType::Workspace),
+                   Ok(work_ix));
+        // It was active, so it still is
+        assert_eq!(tree.active_container, Some(node_ix));
+        assert_eq!(tree.current_workspace(), Ok("5"));
+
+        // A workspace on another output is left alone
+        let new_output = WlcView::dummy(6).as_output();
+        tree.add_output(new_output).unwrap();
+        let other_name = new_output.0.to_string();
+        assert!(tree.tree.workspace_ix_by_name(&other_name).is_some());
+        assert_eq!(tree.move_to_workspace_on_active_output(id, &other_name),
+                   Err(TreeError::WorkspaceOnOtherOutput(other_name.clone())));
+        assert_eq!(tree.tree.ancestor_of_type(node_ix, ContainerType::Workspace),
+                   Ok(work_ix));
+        tree.validate();
+    }
+
+    #[test]
+    fn switch_to_prev_workspace_back_and_forth() {
+        let mut tree = basic_tree();
+        // Nothing to go back to yet
+        tree.switch_to_prev_workspace().unwrap();
+        assert_eq!(tree.current_workspace(), Ok("1"));
+        tree.switch_to_workspace("2");
+        tree.switch_to_prev_workspace().unwrap();
+        assert_eq!(tree.current_workspace(), Ok("1"));
+        tree.switch_to_prev_workspace().unwrap();
+        assert_eq!(tree.current_workspace(), Ok("2"));
+        // Making a new workspace counts too
+        tree.switch_to_workspace("3");
+        tree.switch_to_prev_workspace().unwrap();
+        assert_eq!(tree.current_workspace(), Ok("2"));
+        tree.validate();
+    }
+
+    #[test]
+    fn switch_to_prev_workspace_forgets_removed_workspaces() {
+        let mut tree = basic_tree();
+        tree.set_dynamic_workspaces(true);
+        tree.switch_to_workspace("3");
+        tree.add_view(WlcView::root()).unwrap();
+        tree.remove_active().unwrap();
+        assert!(tree.tree.workspace_ix_by_name("3").is_none());
+        assert!(tree.prev_workspace != Some("3".into()));
+        // Going back doesn't bring the removed workspace back
+        tree.switch_to_prev_workspace().unwrap();
+        assert!(tree.tree.workspace_ix_by_name("3").is_none());
+        tree.validate();
+    }
+
+    #[test]
+    fn urgent_views_flag_their_workspace() {
+        let mut tree = basic_tree();
+        let view = WlcView::root();
+        let node_ix = tree.tree.lookup_view(view).unwrap();
+        let work_ix = tree.tree.ancestor_of_type(node_ix, ContainerType::Workspace).unwrap();
+        let work_name = tree.tree[work_ix].get_name().unwrap().to_string();
+        let work_id = tree.tree[work_ix].get_id();
+        // Move to an empty workspace, so the view isn't focused
+        tree.switch_to_workspace("3");
+        tree.set_urgent(view, true).unwrap();
+        assert!(tree.tree[node_ix].urgent());
+        assert!(tree.is_urgent(work_id).unwrap());
+        assert_eq!(tree.urgent_workspaces(), vec![work_name]);
+        // Focusing on it clears it
+        tree.set_active_node(node_ix).unwrap();
+        assert!(!tree.is_urgent(work_id).unwrap());
+        assert!(tree.urgent_workspaces().is_empty());
+        // The focused view can't be made urgent
+        tree.set_urgent(view, true).unwrap();
+        assert!(!tree.tree[node_ix].urgent());
+        assert_eq!(tree.set_urgent(WlcView::dummy(42), true),
+                   Err(TreeError::ViewNotFound(WlcView::dummy(42))));
+    }
+
+    #[test]
+    fn close_workspace_windows_keeps_workspace() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        tree.close_workspace_windows("2").unwrap();
+        assert!(tree.views_in_workspace("2").unwrap().is_empty());
+        let work_ix = tree.tree.workspace_ix_by_name("2").unwrap();
+        let children = tree.tree.children_of(work_ix);
+        assert_eq!(children.len(), 1);
+        assert!(tree.tree.children_of(children[0]).is_empty());
+        assert_eq!(tree.current_workspace(), Ok("2"));
+        // Other workspaces are untouched
+        assert_eq!(tree.views_in_workspace("1").unwrap().len(), 1);
+        assert_eq!(tree.close_workspace_windows("nope"),
+                   Err(TreeError::UuidNotAssociatedWith(ContainerType::Workspace)));
+        tree.validate();
+    }
+
+    #[test]
+    fn sending_clears_fullscreen_of_old_workspace() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let view_ix = tree.active_container.unwrap();
+        let view_id = tree.tree[view_ix].get_id();
+        tree.set_fullscreen(view_id, true).unwrap();
+        let ws_2_ix = tree.tree.workspace_ix_by_name("2").unwrap();
+        assert!(tree.tree[ws_2_ix].fullscreen_c().unwrap().contains(&view_id));
+        // Sending the container holding it moves it too
+        let container_ix = tree.tree.parent_of(view_ix).unwrap();
+        let container_id = tree.tree[container_ix].get_id();
+        tree.send_to_workspace(container_id, "3").unwrap();
+        assert!(!tree.tree[ws_2_ix].fullscreen_c().unwrap().contains(&view_id));
+        let ws_3_ix = tree.tree.workspace_ix_by_name("3").unwrap();
+        assert!(tree.tree[ws_3_ix].fullscreen_c().unwrap().contains(&view_id));
+        // And sending it back moves it back
+        tree.send_to_workspace(view_id, "2").unwrap();
+        assert!(tree.tree[ws_2_ix].fullscreen_c().unwrap().contains(&view_id));
+        assert!(tree.tree[ws_3_ix].fullscreen_c().unwrap().is_empty());
+        tree.validate();
+    }
+
+    #[test]
+    fn default_layout_applies_to_new_workspaces() {
+        let mut tree = basic_tree();
+        tree.set_default_layout(Layout::Vertical);
+        let output_ix = tree.active_ix_of(ContainerType::Output).unwrap();
+        let root_c_ix = tree.init_workspace("fresh".into(), output_ix);
+        assert_eq!(tree.tree[root_c_ix].get_layout(), Ok(Layout::Vertical));
+        // Existing workspaces keep theirs
+        let ws_1_ix = tree.tree.workspace_ix_by_name("1").unwrap();
+        let ws_1_root = tree.tree.children_of(ws_1_ix)[0];
+        assert_eq!(tree.tree[ws_1_root].get_layout(), Ok(Layout::Horizontal));
+    }
+
+    #[test]
+    fn show_tags_gathers_selected_views() {
+        let mut tree = basic_tree();
+        let first_id = tree.get_active_container().unwrap().get_id();
+        tree.add_view_to_tags(first_id, vec!["1".into(), "web".into()]).unwrap();
+        tree.switch_to_workspace("2");
+        let second_id = tree.get_active_container().unwrap().get_id();
+        tree.add_view_to_tags(second_id, vec!["web".into()]).unwrap();
+        tree.show_tags(&["web".into()]).unwrap();
+        assert_eq!(tree.current_workspace(), Ok("web"));
+        let views = tree.views_in_workspace("web").unwrap();
+        assert!(views.contains(&first_id) && views.contains(&second_id));
+        // Views without a selected tag go back to their first tag
+        tree.show_tags(&["1".into()]).unwrap();
+        assert_eq!(tree.views_in_workspace("1").unwrap(), vec![first_id]);
+        assert_eq!(tree.views_in_workspace("web").unwrap(), vec![second_id]);
+        tree.show_tags(&["1".into(), "web".into()]).unwrap();
+        let views = tree.views_in_workspace("1").unwrap();
+        assert!(views.contains(&first_id) && views.contains(&second_id));
+        assert_eq!(tree.show_tags(&[]), Ok(()));
+        assert_eq!(tree.current_workspace(), Ok("1"));
+    }
 }
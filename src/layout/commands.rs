@@ -2,10 +2,13 @@
 
 use std::fs::File;
 use std::io::Read;
+use std::path::PathBuf;
 
 use super::{try_lock_tree, lock_tree, try_lock_action};
 use super::{Action, ActionErr, Bar, Container, ContainerType,
-            Direction, Handle, Layout, TreeError, ResizeErr, IncompleteBackground};
+            Direction, Handle, Layout, TreeError, ResizeErr, IncompleteBackground,
+            BackgroundMode, FocusError, FocusWrapping, ActiveContext, ViewInfo,
+            Struts, Criteria};
 use super::core::borders::Borders;
 use ::render::Renderable;
 use super::Tree;
@@ -243,6 +246,35 @@ impl Tree {
             .map(|active_ix| self.0.tree[active_ix].get_id())
     }
 
+    /// Gets the active container along with where it is in the tree,
+    /// if there is an active container
+    pub fn active_container_context(&self) -> Option<ActiveContext> {
+        self.0.active_container_context()
+    }
+
+    /// Lists every view in the tree, in a stable order
+    pub fn all_views(&self) -> Vec<ViewInfo> {
+        self.0.all_views()
+    }
+
+    /// Focuses on the first view that matches the criteria,
+    /// switching workspaces if needed
+    pub fn focus_by_criteria(&mut self, criteria: Criteria) -> CommandResult {
+        debug!("Layout.FocusByCriteria({:?})", criteria);
+        self.0.focus_by_criteria(criteria)
+    }
+
+    /// Associates the view with several workspaces (tags)
+    pub fn add_view_to_tags(&mut self, id: Uuid, tags: Vec<String>) -> CommandResult {
+        debug!("Layout.AddViewToTags({}, {:?})", id, tags);
+        self.0.add_view_to_tags(id, tags)
+    }
+
+    /// Gets the views on the tag, including the ones tagged with it
+    pub fn views_on_tag(&self, name: &str) -> Vec<Uuid> {
+        self.0.views_on_tag(name)
+    }
+
     pub fn lookup_handle(&self, handle: Handle) -> Result<Uuid, TreeError> {
         match handle {
             Handle::View(view) =>
@@ -281,17 +313,45 @@ impl Tree {
     pub fn toggle_float(&mut self) -> CommandResult {
         debug!("Layout.ToggleFloat()");
         if let Some(uuid) = self.active_id() {
-            let is_floating: Result<bool, _> = self.0.lookup(uuid)
-                .and_then(|container| Ok(container.floating()));
+            let is_floating: Result<(bool, ContainerType), _> = self.0.lookup(uuid)
+                .and_then(|container| Ok((container.floating(), container.get_type())));
             try!(match is_floating {
-                Ok(true) => self.ground_container(uuid),
-                Ok(false) => self.float_container(uuid),
+                Ok((true, ContainerType::View)) => self.tile_container(uuid),
+                Ok((true, _)) => self.ground_container(uuid),
+                Ok((false, _)) => self.float_container(uuid),
                 Err(err) => return Err(err)
             });
         }
         Ok(())
     }
 
+    /// Sets how opaque the view should be, between 0 and 1.
+    ///
+    /// This is only tracked and reported in the tree, views aren't drawn
+    /// translucent.
+    pub fn set_opacity(&mut self, id: Uuid, opacity: f32) -> CommandResult {
+        debug!("Layout.SetOpacity({}, {})", id, opacity);
+        self.0.set_opacity(id, opacity)
+    }
+
+    /// Sets the opacity unfocused views are reported with, or turns it off.
+    pub fn set_inactive_opacity(&mut self, opacity: Option<f32>) {
+        debug!("Layout.SetInactiveOpacity({:?})", opacity);
+        self.0.set_inactive_opacity(opacity)
+    }
+
+    /// Puts the floating view on top of the others in its workspace.
+    pub fn raise_floating(&mut self, id: Uuid) -> CommandResult {
+        debug!("Layout.RaiseFloating({})", id);
+        self.0.raise_floating(id)
+    }
+
+    /// Puts the floating view beneath the others in its workspace.
+    pub fn lower_floating(&mut self, id: Uuid) -> CommandResult {
+        debug!("Layout.LowerFloating({})", id);
+        self.0.lower_floating(id)
+    }
+
     /// Toggles between horizontal and vertical layout.
     ///
     /// If on neither, defaults to horizontal.
@@ -345,6 +405,12 @@ impl Tree {
         self.0.add_output(output)
     }
 
+    /// Removes an Output from the tree, moving its workspaces to another.
+    pub fn remove_output(&mut self, output: WlcOutput) -> CommandResult {
+        debug!("Layout.RemoveOutput({:?})", output);
+        self.0.remove_output(output)
+    }
+
     /// Gets a list of UUIDs for all the outputs, in the order they were added.
     pub fn outputs(&self) -> Vec<Uuid> {
         let root_ix = self.0.tree.root_ix();
@@ -362,6 +428,42 @@ impl Tree {
         Ok(output.get_resolution().expect("Output had no resolution"))
     }
 
+    /// Lays out the output, e.g after its resolution changed.
+    pub fn layout_output(&mut self, output: WlcOutput) -> CommandResult {
+        let root_ix = self.0.tree.root_ix();
+        let output_ix = self.0.tree.descendant_with_handle(root_ix, output.into())
+            .ok_or(TreeError::OutputNotFound(output))?;
+        self.0.layout(output_ix);
+        Ok(())
+    }
+
+    /// Reserves space on the edges of the output for an external panel or
+    /// dock. Tiled views are laid out again so they don't overlap it,
+    /// floating views are left where they are.
+    pub fn set_struts(&mut self, output: WlcOutput,
+                      top: u32, bottom: u32, left: u32, right: u32) -> CommandResult {
+        debug!("Layout.SetStruts({:?}, {}, {}, {}, {})", output, top, bottom, left, right);
+        self.0.set_struts(output, Struts {
+            top: top,
+            bottom: bottom,
+            left: left,
+            right: right
+        })
+    }
+
+    /// Sets how the background of the output fills it.
+    pub fn set_background_mode(&mut self, output: WlcOutput, mode: BackgroundMode)
+                               -> CommandResult {
+        debug!("Layout.SetBackgroundMode({:?}, {:?})", output, mode);
+        self.0.set_background_mode(output, mode)
+    }
+
+    /// Sets the image drawn on outputs when they are added.
+    pub fn set_default_background(&mut self, path: PathBuf, mode: BackgroundMode) {
+        debug!("Layout.SetDefaultBackground({:?}, {:?})", path, mode);
+        self.0.set_default_background(path, mode)
+    }
+
     /// Binds a view to be the background for the given outputs.
     ///
     /// If there was a previous background, it is removed and deallocated.
@@ -418,6 +520,12 @@ impl Tree {
         Ok(())
     }
 
+    /// Shows the views on all of the tags at once, switching to the first.
+    pub fn show_tags(&mut self, tags: &[String]) -> CommandResult {
+        debug!("Layout.ShowTags({:?})", tags);
+        self.0.show_tags(tags)
+    }
+
     /// Gets the current workspace we are focused on
     pub fn current_workspace(&self) -> Result<&str, TreeError> {
         self.0.current_workspace()
@@ -441,6 +549,12 @@ impl Tree {
         self.0.ground_container(id)
     }
 
+    /// Tiles the floating view next to the last focused tiled view.
+    pub fn tile_container(&mut self, id: Uuid) -> CommandResult {
+        debug!("Layout.TileContainer(\"{}\")", id);
+        self.0.tile_container(id)
+    }
+
     /// Adds a view to the workspace of the active container
     pub fn add_view(&mut self, view: WlcView) -> CommandResult {
         let pid = view.get_pid();
@@ -546,29 +660,63 @@ impl Tree {
         }
     }
 
+    /// Updates the title in the borders of the view to its current title.
     pub fn update_title(&mut self, view: WlcView) -> CommandResult {
-        let id = try!(self.lookup_handle(view.into())
-                      .map_err(|_|TreeError::ViewNotFound(view)));
-        {
-            let container = try!(self.0.lookup_mut(id));
-            container.set_name(Container::get_title(view));
-            container.draw_borders()?;
-        }
-        // Update the parent container using draw_borders_rec
-        let node_ix = self.0.tree.lookup_id(id)
-            .ok_or(TreeError::ViewNotFound(view))?;
-        self.0.draw_borders_rec(vec![node_ix])?;
-        Ok(())
+        self.0.update_view_title(view, Container::get_title(view))
     }
 
     /// Sets the view to be the new active container.
     /// Will fail if the container is floating.
     pub fn set_active_view(&mut self, view: WlcView) -> CommandResult {
-        try!(self.0.set_active_view(view.clone()));
+        match self.0.set_active_view(view.clone()) {
+            // It never takes the focus, leave it with what has it
+            Err(TreeError::Focus(FocusError::NotFocusable(_))) => return Ok(()),
+            result => try!(result)
+        }
         view.focus();
         Ok(())
     }
 
+    /// Registers a callback that is run whenever the active container changes.
+    pub fn on_focus_change<F>(&mut self, hook: F)
+        where F: FnMut(Option<Uuid>, Uuid) + Send + 'static
+    {
+        self.0.on_focus_change(hook)
+    }
+
+    /// Gets the output the point is on, if it's on one.
+    pub fn output_at(&self, point: Point) -> Option<WlcOutput> {
+        self.0.output_at(point)
+    }
+
+    /// Converts the point to be relative to the output.
+    pub fn to_output_local(&self, output: WlcOutput, point: Point) -> Point {
+        self.0.to_output_local(output, point)
+    }
+
+    /// Sets whether the view behind the UUID can take the keyboard focus.
+    pub fn set_focusable(&mut self, id: Uuid, focusable: bool) -> CommandResult {
+        debug!("Layout.SetFocusable({:?}, {})", id, focusable);
+        self.0.set_focusable(id, focusable)
+    }
+
+    /// Focuses on the view the pointer moved over, if focus follows the mouse.
+    pub fn pointer_focus(&mut self, view: WlcView) -> CommandResult {
+        self.0.pointer_focus(view)
+    }
+
+    /// Sets whether moving the pointer over a view focuses it.
+    pub fn set_focus_follows_mouse(&mut self, enabled: bool) {
+        debug!("Layout.SetFocusFollowsMouse({})", enabled);
+        self.0.set_focus_follows_mouse(enabled)
+    }
+
+    /// Sets what moving the focus does at the edge of a container.
+    pub fn set_focus_wrapping(&mut self, wrapping: FocusWrapping) {
+        debug!("Layout.SetFocusWrapping({:?})", wrapping);
+        self.0.set_focus_wrapping(wrapping)
+    }
+
     /// Resets the focus to be whatever the active path points to.
     /// This is useful when the `active_container` is `None`, e.g when
     /// closing the lock screen.
@@ -597,36 +745,7 @@ impl Tree {
     /// and the flag is not set (it's only tracked for Views and Containers).
     pub fn set_fullscreen(&mut self, id: Uuid, toggle: bool) -> CommandResult {
         debug!("Layout.FullScreen(\"{}\", {})", id, toggle);
-        {
-            let container = try!(self.0.lookup_mut(id));
-            try!(container.set_fullscreen(toggle)
-                 .map_err(|_| TreeError::UuidWrongType(id, vec![ContainerType::View,
-                                                                ContainerType::Container])));
-        }
-        // Now update the workspace so that it knows which children are fullscreen
-        {
-            /*
-            TODO This needs proper error handling!
-            Correct way is to have active_ix_of to return a proper `Result<NodeIndex, TreeError>`
-            however that would break too much code and needs a branch to itself.
-
-            For now, in order to have the IPC not break Way Cooler,
-            this will return an incorrect error.
-            */
-            if let Some(workspace_ix) = self.0.active_ix_of(ContainerType::Workspace) {
-                let workspace = &mut self.0.tree[workspace_ix];
-                workspace.update_fullscreen_c(id, toggle).map_err(
-                    |_| TreeError::UuidWrongType(workspace.get_id(),
-                                                 vec![ContainerType::Workspace]))?;
-            } else {
-                // WRONG ID! See TODO above
-                return Err(TreeError::UuidWrongType(id, vec![ContainerType::Workspace]))
-            }
-        }
-        // TODO Only do this if in path, or otherwise just tile the workspace the container is in
-        // MOST of the time, this isn't a useless operation (read: when keybinding is used),
-        // but still the IPC shouldn't do a useless tile if it can help it.
-        self.layout_active_of(ContainerType::Workspace)
+        self.0.set_fullscreen(id, toggle)
     }
 
     pub fn is_fullscreen(&self, id: Uuid) -> Result<bool, TreeError> {
@@ -662,8 +781,7 @@ impl Tree {
     /// Moves the active container to a workspace
     pub fn send_active_to_workspace(&mut self, workspace_name: &str) -> CommandResult {
         debug!("Layout.SendActiveToWorkspace(\"{}\")", workspace_name);
-        self.0.send_active_to_workspace(workspace_name);
-        Ok(())
+        self.0.send_active_to_workspace(workspace_name)
     }
 
     /// Resizes the container, as if it was dragged at the edge to a certain point
@@ -705,8 +823,7 @@ impl Tree {
             Err(::layout::GraphError::LookupFailed(id))?
         }
         debug!("Layout.SendToWorkspace(\"{}\", \"{}\")", id, workspace_name);
-        self.0.send_to_workspace(id, workspace_name);
-        Ok(())
+        self.0.send_to_workspace(id, workspace_name)
     }
 
     pub fn set_pointer_pos(&mut self, point: Point) -> CommandResult {
@@ -734,11 +851,9 @@ impl Tree {
         } else {
             Err(TreeError::OutputNotFound(output))
         };
-        result.and_then(|_| {
-            self.0.layout_active_of(ContainerType::Output);
-            Ok(())
-        })
-   }
+        // The bar takes space away from the output, so tile it again
+        result.and_then(|_| self.layout_output(output))
+    }
 
     /// Updates the geometry of the view from an external request
     /// (such a request can come from the view itself)
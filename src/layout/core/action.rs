@@ -10,7 +10,6 @@ pub struct Action {
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ActionErr {
     /// Tried to start an action, but an action was already in progress
-    #[allow(dead_code)]
     ActionInProgress,
     /// There is not already an action in progress, but was expected to be.
     ActionNotInProgress,
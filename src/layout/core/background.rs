@@ -1,20 +1,92 @@
 //! Background for an output
 
-use rustwlc::WlcView;
+use rustwlc::{Geometry, Point, Size, WlcView};
 use wayland_sys::server::wl_client;
 
+/// The program spawned to draw the default background of new outputs.
+/// It's given the path of the image to draw.
+pub const BACKGROUND_PROGRAM: &'static str = "wc-bg";
+
+/// How the background image fills the output.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum BackgroundMode {
+    /// Scaled to cover the whole output, cropping whatever doesn't fit.
+    Fill,
+    /// Scaled to fit inside the output, keeping its aspect ratio.
+    Fit,
+    /// Stretched to the size of the output, ignoring its aspect ratio.
+    Stretch,
+    /// Kept at its own size in the middle of the output, letterboxed
+    /// if it is smaller than the output.
+    Center,
+    /// Repeated across the output. The client draws the repetitions,
+    /// so it is given the whole output.
+    Tile
+}
+
+impl Default for BackgroundMode {
+    fn default() -> Self {
+        BackgroundMode::Stretch
+    }
+}
+
+impl BackgroundMode {
+    /// Computes the geometry of a background image of the given size
+    /// on an output with the given geometry.
+    pub fn geometry(self, output: Geometry, image: Size) -> Geometry {
+        if image.w == 0 || image.h == 0 {
+            return output
+        }
+        let size = match self {
+            BackgroundMode::Stretch | BackgroundMode::Tile => return output,
+            BackgroundMode::Center => image,
+            BackgroundMode::Fill | BackgroundMode::Fit => {
+                let scale_w = output.size.w as f64 / image.w as f64;
+                let scale_h = output.size.h as f64 / image.h as f64;
+                let scale = if self == BackgroundMode::Fill {
+                    scale_w.max(scale_h)
+                } else {
+                    scale_w.min(scale_h)
+                };
+                Size {
+                    w: (image.w as f64 * scale).round() as u32,
+                    h: (image.h as f64 * scale).round() as u32
+                }
+            }
+        };
+        Geometry {
+            origin: Point {
+                x: output.origin.x + (output.size.w as i32 - size.w as i32) / 2,
+                y: output.origin.y + (output.size.h as i32 - size.h as i32) / 2
+            },
+            size: size
+        }
+    }
+}
+
 /// A background is not complete until you call the "complete" method on it.
 /// This will need to be executed via the view_created callback, because before that
 /// we haven't properly set it.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct IncompleteBackground {
-    client: usize
+    client: usize,
+    pub mode: BackgroundMode
 }
 
 /// A background for an output.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct Background {
-    pub handle: WlcView
+    pub handle: WlcView,
+    pub mode: BackgroundMode,
+    /// The size of the image, as first requested by the client.
+    pub size: Size
+}
+
+impl Background {
+    /// Gets the geometry the background should have on the output.
+    pub fn geometry(&self, output: Geometry) -> Geometry {
+        self.mode.geometry(output, self.size)
+    }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -35,9 +107,24 @@ impl Into<MaybeBackground> for Background {
     }
 }
 
+impl MaybeBackground {
+    /// Sets how the background fills the output, even if it hasn't loaded yet.
+    pub fn set_mode(&mut self, mode: BackgroundMode) {
+        match *self {
+            MaybeBackground::Incomplete(ref mut background) =>
+                background.mode = mode,
+            MaybeBackground::Complete(ref mut background) =>
+                background.mode = mode
+        }
+    }
+}
+
 impl IncompleteBackground {
     pub fn new(client: *mut wl_client) -> Self {
-        IncompleteBackground { client: client as _ }
+        IncompleteBackground {
+            client: client as _,
+            mode: BackgroundMode::default()
+        }
     }
 
     /// Builds the background if the client matches
@@ -45,10 +132,48 @@ impl IncompleteBackground {
     pub fn build(self, client: *mut wl_client, handle: WlcView)
                  -> MaybeBackground {
         if self.client as *mut wl_client == client {
-            Background { handle }.into()
+            let size = handle.get_geometry()
+                .map(|geometry| geometry.size)
+                .unwrap_or(Size { w: 0, h: 0 });
+            Background { handle, mode: self.mode, size }.into()
         } else {
             self.into()
         }
 
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output() -> Geometry {
+        Geometry {
+            origin: Point { x: 0, y: 0 },
+            size: Size { w: 800, h: 600 }
+        }
+    }
+
+    #[test]
+    fn background_mode_geometry() {
+        let image = Size { w: 400, h: 400 };
+        assert_eq!(BackgroundMode::Stretch.geometry(output(), image), output());
+        assert_eq!(BackgroundMode::Tile.geometry(output(), image), output());
+        // Fill covers the whole output, cropping the top and bottom
+        assert_eq!(BackgroundMode::Fill.geometry(output(), image), Geometry {
+            origin: Point { x: 0, y: -100 },
+            size: Size { w: 800, h: 800 }
+        });
+        // Fit shows all of it, with bars on the sides
+        assert_eq!(BackgroundMode::Fit.geometry(output(), image), Geometry {
+            origin: Point { x: 100, y: 0 },
+            size: Size { w: 600, h: 600 }
+        });
+        assert_eq!(BackgroundMode::Center.geometry(output(), image), Geometry {
+            origin: Point { x: 200, y: 100 },
+            size: image
+        });
+        // Don't know how big it is, so just give it everything
+        assert_eq!(BackgroundMode::Fit.geometry(output(), Size { w: 0, h: 0 }), output());
+    }
+}
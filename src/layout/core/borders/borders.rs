@@ -48,7 +48,10 @@ pub struct Borders {
     /// If unspecified, the default is used.
     title_font_color: Option<Color>,
     /// Specifies if we should draw the title or not
-    pub draw_title: bool
+    pub draw_title: bool,
+    /// Whether these are the borders of the focused view,
+    /// which can be thicker than the rest.
+    active: bool
 }
 
 impl Renderable for Borders {
@@ -86,7 +89,8 @@ impl Renderable for Borders {
             color: None,
             title_color: None,
             title_font_color: None,
-            draw_title: true
+            draw_title: true,
+            active: false
         })
     }
 
@@ -113,7 +117,7 @@ impl Renderable for Borders {
     /// bad performance.
     fn reallocate_buffer(mut self, mut geometry: Geometry) -> Option<Self>{
         // Add the thickness to the geometry.
-        let thickness = Borders::thickness();
+        let thickness = self.view_thickness();
 
         let title_count = match (self.layout, self.children.as_ref()) {
             (Some(Layout::Stacked), Some(children)) =>
@@ -212,6 +216,37 @@ impl Borders {
             .unwrap_or(0u32)
     }
 
+    /// Gets the thickness of the borders of the focused view.
+    ///
+    /// Defaults to the normal thickness if not set.
+    pub fn active_thickness() -> u32 {
+        let lock = registry::clients_read();
+        let client = lock.client(Uuid::nil()).unwrap();
+        let handle = registry::ReadHandle::new(&client);
+        handle.read("windows".into()).ok()
+            .and_then(|windows| windows.get("borders".into()))
+            .and_then(|borders| borders.as_object()
+                      .and_then(|borders| borders.get("active_size"))
+                      .and_then(|size| size.as_f64()))
+            .map(|num| num as u32)
+            .unwrap_or_else(Borders::thickness)
+    }
+
+    /// Gets the thickness of these borders, which depends on whether
+    /// they belong to the focused view.
+    pub fn view_thickness(&self) -> u32 {
+        if self.active {
+            Borders::active_thickness()
+        } else {
+            Borders::thickness()
+        }
+    }
+
+    /// Marks these borders as belonging to the focused view or not.
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
     /// Gets the size of the title bar.
     ///
     /// If the view doesn't want to display it, returns 0.
@@ -100,7 +100,7 @@ impl ViewDraw {
         if x < 0.0 {
             w += x;
         }
-        let mut title_x = Borders::thickness() as f64;
+        let mut title_x = self.base.inner().view_thickness() as f64;
         let mut title_y = title_size - 5.0;
         x = 0.0;
         let mut y = 0.0;
@@ -209,7 +209,7 @@ impl ViewDraw {
 impl Drawable<Borders> for ViewDraw {
     fn draw(mut self, view_g: Geometry) -> Result<Borders, DrawErr<Borders>> {
         let mut border_g = view_g;
-        let thickness = Borders::thickness();
+        let thickness = self.base.inner().view_thickness();
         let edge_thickness = thickness / 2;
         let output_res = self.inner().get_output().get_resolution()
             .expect("Could not get focused output's resolution");
@@ -1,6 +1,7 @@
 //! Container types
 
 use std::fmt;
+use std::collections::HashSet;
 
 use uuid::Uuid;
 use rustwlc::handle::{WlcView, WlcOutput};
@@ -35,6 +36,17 @@ impl From<WlcOutput> for Handle {
     }
 }
 
+/// How a container covers the screen when it is fullscreen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FullscreenMode {
+    /// Not fullscreen.
+    None,
+    /// Covers the output its workspace is on, other outputs can still be used.
+    Workspace,
+    /// Covers every output. Only one container can be globally fullscreen.
+    Global
+}
+
 /// Types of containers
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ContainerType {
@@ -92,6 +104,48 @@ impl fmt::Display for Layout {
     }
 }
 
+/// Space reserved on the edges of an output, e.g for panels or docks.
+///
+/// Tiled containers are never placed in this space.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Struts {
+    pub top: u32,
+    pub bottom: u32,
+    pub left: u32,
+    pub right: u32
+}
+
+/// How the contents of an output are rotated and flipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputTransform {
+    Normal,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    Flipped,
+    Flipped90,
+    Flipped180,
+    Flipped270
+}
+
+impl OutputTransform {
+    /// Determines if the output is on its side, so that its width and
+    /// height are swapped.
+    pub fn swaps_dimensions(self) -> bool {
+        match self {
+            OutputTransform::Rotate90 | OutputTransform::Rotate270 |
+            OutputTransform::Flipped90 | OutputTransform::Flipped270 => true,
+            _ => false
+        }
+    }
+}
+
+impl Default for OutputTransform {
+    fn default() -> Self {
+        OutputTransform::Normal
+    }
+}
+
 /// Represents an item in the container tree.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Container {
@@ -105,6 +159,10 @@ pub enum Container {
         background: Option<MaybeBackground>,
         /// Optional bar for the output
         bar: Option<Bar>,
+        /// Space reserved on the edges of the output
+        struts: Struts,
+        /// How the output is rotated
+        transform: OutputTransform,
         /// UUID associated with container, client program can use container
         id: Uuid,
     },
@@ -146,6 +204,9 @@ pub enum Container {
         id: Uuid,
         /// The border drawn to the screen
         borders: Option<Borders>,
+        /// Names the user has marked this container with.
+        /// A mark is held by at most one container in the tree.
+        marks: HashSet<String>,
     },
     /// View or window
     View {
@@ -161,6 +222,31 @@ pub enum Container {
         id: Uuid,
         /// The border drawn to the screen
         borders: Option<Borders>,
+        /// Names the user has marked this view with.
+        /// A mark is held by at most one container in the tree.
+        marks: HashSet<String>,
+        /// Whether this view follows the user between workspaces.
+        /// Only floating views can be sticky.
+        sticky: bool,
+        /// Where the view was the last time it stopped floating,
+        /// so floating it again puts it back there.
+        last_floating_geometry: Option<Geometry>,
+        /// Whether the view wants the user's attention.
+        urgent: bool,
+        /// The view this one is a dialog of, if its parent is a tiled view.
+        /// It floats over its parent and follows it between workspaces.
+        transient_for: Option<Uuid>,
+        /// The smallest size the client can be drawn at, if it has one.
+        /// Tiling takes space from its siblings rather than go below it.
+        min_size: Option<Size>,
+        /// The application id of the client, read when the view was added.
+        app_id: String,
+        /// How opaque the view is, in thousandths so that containers
+        /// can still be compared exactly. See `Container::opacity`.
+        opacity: u16,
+        /// Whether the view can take the keyboard focus.
+        /// Overlays like notifications can't, so they never steal input.
+        focusable: bool
     }
 }
 
@@ -175,6 +261,8 @@ impl Container {
             handle: handle,
             background: None,
             bar: None,
+            struts: Struts::default(),
+            transform: OutputTransform::default(),
             id: Uuid::new_v4()
         }
     }
@@ -203,7 +291,8 @@ impl Container {
             apparent_geometry: geometry,
             geometry,
             id: Uuid::new_v4(),
-            borders
+            borders,
+            marks: HashSet::new()
         }
     }
 
@@ -216,7 +305,16 @@ impl Container {
             floating: false,
             effective_geometry: geometry,
             id: Uuid::new_v4(),
-            borders: borders
+            borders: borders,
+            marks: HashSet::new(),
+            sticky: false,
+            last_floating_geometry: None,
+            urgent: false,
+            transient_for: None,
+            min_size: None,
+            app_id: handle.get_app_id(),
+            opacity: 1000,
+            focusable: true
         }
     }
 
@@ -265,15 +363,20 @@ impl Container {
 
     /// Gets the geometry of the container, if the container has one.
     /// Root: Returns None
-    /// Workspace/Output: Size is the size of the screen, origin is just 0,0
+    /// Workspace/Output: Size is the size of the screen, minus any bar or
+    /// struts. Origin is 0,0 moved past those. If the output is rotated on
+    /// its side the width and height of the screen are swapped.
     /// Container/View: Size is the size of the container,
     /// origin is the coordinates relative to the parent container.
     pub fn get_geometry(&self) -> Option<Geometry> {
         match *self {
             Container::Root(_)  => None,
-            Container::Output { ref handle, ref bar, .. } => {
+            Container::Output { ref handle, ref bar, struts, transform, .. } => {
                 let mut resolution = handle.get_resolution()
                     .expect("Couldn't get output resolution");
+                if transform.swaps_dimensions() {
+                    resolution = Size { w: resolution.h, h: resolution.w };
+                }
                 let mut origin = Point { x: 0, y: 0 };
                 if let Some(handle) = bar.as_ref().map(|bar| **bar) {
                     let bar_g = handle.get_geometry()
@@ -285,6 +388,10 @@ impl Container {
                     origin.y += h as i32;
                     resolution.h = resolution.h.saturating_sub(h)
                 }
+                origin.x += struts.left as i32;
+                origin.y += struts.top as i32;
+                resolution.w = resolution.w.saturating_sub(struts.left + struts.right);
+                resolution.h = resolution.h.saturating_sub(struts.top + struts.bottom);
                 Some(Geometry {
                     origin: origin,
                     size: resolution
@@ -298,6 +405,35 @@ impl Container {
         }
     }
 
+    /// Gets the rectangle of the title strip for a container whose children
+    /// are arranged with the given layout.
+    ///
+    /// The strip is the area between the top of the container and where its
+    /// children start (e.g the tabs of a tabbed container).
+    ///
+    /// Only tabbed and stacked layouts have a title strip, so for other
+    /// layouts (or non-containers, or containers without titles) this
+    /// returns `None`.
+    pub fn title_rect(&self, layout: Layout) -> Option<Geometry> {
+        match layout {
+            Layout::Tabbed | Layout::Stacked => {},
+            Layout::Horizontal | Layout::Vertical => return None
+        }
+        match *self {
+            Container::Container { geometry, apparent_geometry, .. } => {
+                let height = apparent_geometry.origin.y - geometry.origin.y;
+                if height <= 0 {
+                    return None
+                }
+                Some(Geometry {
+                    origin: geometry.origin,
+                    size: Size { w: geometry.size.w, h: height as u32 }
+                })
+            },
+            _ => None
+        }
+    }
+
     /// Gets the actual geometry for a `WlcView`, `Container`, or `WlcOutput`.
     ///
     /// Unlike `get_geometry`, this does not account for borders/gaps,
@@ -312,8 +448,13 @@ impl Container {
         match *self {
             Container::View { handle, .. } => handle.get_geometry(),
             Container::Container { geometry, .. } => Some(geometry),
-            Container::Output { handle, .. } => {
+            Container::Output { handle, transform, .. } => {
                 handle.get_resolution()
+                    .map(|size| if transform.swaps_dimensions() {
+                        Size { w: size.h, h: size.w }
+                    } else {
+                        size
+                    })
                     .map(|size|
                          Geometry {
                              origin: Point { x: 0, y: 0 },
@@ -332,8 +473,13 @@ impl Container {
         match *self {
             Container::Root(_) =>
                 panic!("Tried to set the geometry of the root!"),
-            Container::Output { ref handle, .. } => {
-                handle.set_resolution(geo.size, 1);
+            Container::Output { ref handle, transform, .. } => {
+                let size = if transform.swaps_dimensions() {
+                    Size { w: geo.size.h, h: geo.size.w }
+                } else {
+                    geo.size
+                };
+                handle.set_resolution(size, 1);
             },
             Container::Workspace { ref mut geometry, .. } |
             Container::Container { ref mut geometry, .. } => {
@@ -391,6 +537,205 @@ impl Container {
     }
 
 
+    /// Gets the marks on this container.
+    ///
+    /// Only views and containers can be marked, all others return `None`.
+    pub fn marks(&self) -> Option<&HashSet<String>> {
+        match *self {
+            Container::View { ref marks, .. } |
+            Container::Container { ref marks, .. } => Some(marks),
+            Container::Workspace { .. } | Container::Output { .. } | Container::Root(_) => None
+        }
+    }
+
+    /// Gets the marks on this container, so they can be modified.
+    ///
+    /// Only views and containers can be marked, all others return `None`.
+    pub fn marks_mut(&mut self) -> Option<&mut HashSet<String>> {
+        match *self {
+            Container::View { ref mut marks, .. } |
+            Container::Container { ref mut marks, .. } => Some(marks),
+            Container::Workspace { .. } | Container::Output { .. } | Container::Root(_) => None
+        }
+    }
+
+    /// Determines if the view follows the user between workspaces.
+    ///
+    /// Only views can be sticky, all others return false.
+    pub fn sticky(&self) -> bool {
+        match *self {
+            Container::View { sticky, .. } => sticky,
+            _ => false
+        }
+    }
+
+    /// Sets whether the view follows the user between workspaces.
+    ///
+    /// If not set on a view, the type of the container is returned.
+    pub fn set_sticky(&mut self, val: bool) -> Result<(), ContainerType> {
+        match *self {
+            Container::View { ref mut sticky, .. } => {
+                *sticky = val;
+                Ok(())
+            },
+            ref container => Err(container.get_type())
+        }
+    }
+
+    /// Determines if the view can take the keyboard focus.
+    ///
+    /// Only views can be made unfocusable, all others return true.
+    pub fn focusable(&self) -> bool {
+        match *self {
+            Container::View { focusable, .. } => focusable,
+            _ => true
+        }
+    }
+
+    /// Sets whether the view can take the keyboard focus.
+    ///
+    /// If not set on a view, the type of the container is returned.
+    pub fn set_focusable(&mut self, val: bool) -> Result<(), ContainerType> {
+        match *self {
+            Container::View { ref mut focusable, .. } => {
+                *focusable = val;
+                Ok(())
+            },
+            ref container => Err(container.get_type())
+        }
+    }
+
+    /// Determines if the view wants the user's attention.
+    ///
+    /// Only views can be urgent, all others return false.
+    pub fn urgent(&self) -> bool {
+        match *self {
+            Container::View { urgent, .. } => urgent,
+            _ => false
+        }
+    }
+
+    /// Sets whether the view wants the user's attention.
+    ///
+    /// If not set on a view, the type of the container is returned.
+    pub fn set_urgent(&mut self, val: bool) -> Result<(), ContainerType> {
+        match *self {
+            Container::View { ref mut urgent, .. } => {
+                *urgent = val;
+                Ok(())
+            },
+            ref container => Err(container.get_type())
+        }
+    }
+
+    /// Gets the geometry the view had the last time it stopped floating.
+    ///
+    /// Only views remember this, all others return `None`.
+    pub fn last_floating_geometry(&self) -> Option<Geometry> {
+        match *self {
+            Container::View { last_floating_geometry, .. } => last_floating_geometry,
+            _ => None
+        }
+    }
+
+    /// Remembers the geometry the view had when it stopped floating.
+    ///
+    /// If not set on a view, the type of the container is returned.
+    pub fn set_last_floating_geometry(&mut self, geometry: Geometry)
+                                      -> Result<(), ContainerType> {
+        match *self {
+            Container::View { ref mut last_floating_geometry, .. } => {
+                *last_floating_geometry = Some(geometry);
+                Ok(())
+            },
+            ref container => Err(container.get_type())
+        }
+    }
+
+    /// Gets the view this view is a dialog of.
+    ///
+    /// Only views can be dialogs, all others return `None`.
+    pub fn transient_for(&self) -> Option<Uuid> {
+        match *self {
+            Container::View { transient_for, .. } => transient_for,
+            _ => None
+        }
+    }
+
+    /// Sets the view this view is a dialog of.
+    ///
+    /// If not set on a view, the type of the container is returned.
+    pub fn set_transient_for(&mut self, parent: Option<Uuid>) -> Result<(), ContainerType> {
+        match *self {
+            Container::View { ref mut transient_for, .. } => {
+                *transient_for = parent;
+                Ok(())
+            },
+            ref container => Err(container.get_type())
+        }
+    }
+
+    /// Gets the application id of the view, e.g to match it in window rules.
+    ///
+    /// Only views have an application id, all others return `None`.
+    pub fn app_id(&self) -> Option<&str> {
+        match *self {
+            Container::View { ref app_id, .. } => Some(app_id.as_str()),
+            _ => None
+        }
+    }
+
+    /// Gets the smallest size the view can be tiled at.
+    ///
+    /// Only views have a minimum size, all others return `None`.
+    pub fn min_size(&self) -> Option<Size> {
+        match *self {
+            Container::View { min_size, .. } => min_size,
+            _ => None
+        }
+    }
+
+    /// Sets the smallest size the view can be tiled at.
+    ///
+    /// If not set on a view, the type of the container is returned.
+    pub fn set_min_size(&mut self, size: Option<Size>) -> Result<(), ContainerType> {
+        match *self {
+            Container::View { ref mut min_size, .. } => {
+                *min_size = size;
+                Ok(())
+            },
+            ref container => Err(container.get_type())
+        }
+    }
+
+    /// Gets how opaque the view is, from 0 (invisible) to 1 (opaque).
+    ///
+    /// Only views have an opacity, all others return `None`.
+    pub fn opacity(&self) -> Option<f32> {
+        match *self {
+            Container::View { opacity, .. } => Some(opacity as f32 / 1000.0),
+            _ => None
+        }
+    }
+
+    /// Sets how opaque the view is, clamped to be between 0 and 1.
+    ///
+    /// If not set on a view, the type of the container is returned.
+    pub fn set_opacity(&mut self, new_opacity: f32) -> Result<(), ContainerType> {
+        match *self {
+            Container::View { ref mut opacity, .. } => {
+                let clamped = if new_opacity.is_nan() {
+                    1.0
+                } else {
+                    new_opacity.max(0.0).min(1.0)
+                };
+                *opacity = (clamped * 1000.0).round() as u16;
+                Ok(())
+            },
+            ref container => Err(container.get_type())
+        }
+    }
+
     // TODO Make these set_* functions that can fail return a proper error type.
 
     /// If not set on a view or container, error is returned telling what
@@ -399,8 +744,12 @@ impl Container {
         let c_type = self.get_type();
         let mut v_g;
         match *self {
-            Container::View { handle, ref mut floating, .. } => {
+            Container::View { handle, ref mut floating, ref mut sticky, .. } => {
                 *floating = val;
+                // Tiled views can't follow the user around
+                if !val {
+                    *sticky = false;
+                }
                 // And now we update the geometry, if necessary.
                 v_g = handle.get_geometry() .expect("View had no geometry");
                 // Make it the min size
@@ -503,7 +852,7 @@ impl Container {
                         Some(index) => { fullscreen_c.remove(index); },
                         None => {}
                     }
-                } else {
+                } else if !fullscreen_c.contains(&id) {
                     fullscreen_c.push(id);
                 }
                 Ok(())
@@ -677,6 +1026,7 @@ impl Container {
             Container::View { ref mut borders, .. } |
             Container::Container { ref mut borders, .. }=> {
                 if let Some(borders_) = borders.as_mut() {
+                    borders_.set_active(c_type == ContainerType::View);
                     let color = Borders::active_color();
                     let title_color = Borders::active_title_color();
                     let title_font_color = Borders::active_title_font_color();
@@ -701,6 +1051,7 @@ impl Container {
             Container::View { ref mut borders, .. } |
             Container::Container { ref mut borders, .. }=> {
                 if let Some(borders_) = borders.as_mut() {
+                    borders_.set_active(false);
                     borders_.set_color(None);
                     borders_.set_title_color(None);
                     borders_.set_title_font_color(None);
@@ -829,6 +1180,34 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn title_rect_test() {
+        let geometry = Geometry {
+            origin: Point { x: 10, y: 20 },
+            size: Size { w: 400, h: 300 }
+        };
+        let mut container = Container::new_container(geometry,
+                                                     WlcView::root().as_output(),
+                                                     None);
+        // No room was made for titles yet
+        assert_eq!(container.title_rect(Layout::Tabbed), None);
+        match container {
+            Container::Container { ref mut apparent_geometry, .. } => {
+                apparent_geometry.origin.y += 25;
+                apparent_geometry.size.h -= 25;
+            },
+            _ => unreachable!()
+        }
+        assert_eq!(container.title_rect(Layout::Tabbed), Some(Geometry {
+            origin: Point { x: 10, y: 20 },
+            size: Size { w: 400, h: 25 }
+        }));
+        assert_eq!(container.title_rect(Layout::Horizontal), None);
+        assert_eq!(container.title_rect(Layout::Vertical), None);
+        let view = Container::new_view(WlcView::root(), None);
+        assert_eq!(view.title_rect(Layout::Tabbed), None);
+    }
+
     #[test]
     fn layout_change_test() {
         let root = Container::new_root();
@@ -0,0 +1,82 @@
+//! Helpers for comparing geometries.
+
+use rustwlc::Geometry;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+/// How far each edge moved between two geometries, in pixels.
+///
+/// Positive values mean the edge moved right (for `left` and `right`)
+/// or down (for `top` and `bottom`).
+pub struct GeometryDelta {
+    pub left: i32,
+    pub right: i32,
+    pub top: i32,
+    pub bottom: i32
+}
+
+impl GeometryDelta {
+    /// Determines if none of the edges moved.
+    pub fn is_zero(&self) -> bool {
+        *self == GeometryDelta::default()
+    }
+}
+
+/// Extends `Geometry` with a way to diff it against another one.
+pub trait GeometryDiff {
+    /// Gets how far each edge of `other` is from the same edge of `self`.
+    fn diff(&self, other: &Geometry) -> GeometryDelta;
+}
+
+impl GeometryDiff for Geometry {
+    fn diff(&self, other: &Geometry) -> GeometryDelta {
+        let right = |geo: &Geometry| geo.origin.x + geo.size.w as i32;
+        let bottom = |geo: &Geometry| geo.origin.y + geo.size.h as i32;
+        GeometryDelta {
+            left: other.origin.x - self.origin.x,
+            right: right(other) - right(self),
+            top: other.origin.y - self.origin.y,
+            bottom: bottom(other) - bottom(self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustwlc::{Point, Size};
+
+    fn geo(x: i32, y: i32, w: u32, h: u32) -> Geometry {
+        Geometry {
+            origin: Point { x: x, y: y },
+            size: Size { w: w, h: h }
+        }
+    }
+
+    #[test]
+    fn same_geometry_has_no_delta() {
+        let a = geo(10, 10, 100, 100);
+        assert!(a.diff(&a).is_zero());
+    }
+
+    #[test]
+    fn moved_geometry() {
+        let a = geo(10, 10, 100, 100);
+        let b = geo(30, 5, 100, 100);
+        assert_eq!(a.diff(&b), GeometryDelta { left: 20, right: 20, top: -5, bottom: -5 });
+    }
+
+    #[test]
+    fn resized_geometry() {
+        let a = geo(0, 0, 800, 600);
+        let b = geo(0, 0, 400, 600);
+        assert_eq!(a.diff(&b), GeometryDelta { left: 0, right: -400, top: 0, bottom: 0 });
+        assert_eq!(b.diff(&a), GeometryDelta { left: 0, right: 400, top: 0, bottom: 0 });
+    }
+
+    #[test]
+    fn shrunk_on_all_sides() {
+        let a = geo(0, 0, 100, 100);
+        let b = geo(5, 5, 90, 90);
+        assert_eq!(a.diff(&b), GeometryDelta { left: 5, right: -5, top: 5, bottom: -5 });
+    }
+}
@@ -34,6 +34,7 @@ pub enum GraphError {
 }
 
 /// Layout tree implemented with petgraph.
+#[derive(Clone)]
 pub struct InnerTree {
     graph: StableGraph<Container, Path>, // Directed graph
     id_map: HashMap<Uuid, NodeIndex>,
@@ -241,6 +242,19 @@ impl InnerTree {
         child_ix
     }
 
+    /// Adds a new node that is not attached to anything in the tree,
+    /// returning its node index.
+    ///
+    /// Only use this for nodes that are deliberately kept out of the tree,
+    /// like the scratchpad workspace.
+    pub fn add_detached(&mut self, val: Container) -> NodeIndex {
+        let id = val.get_id();
+        let node_ix = self.graph.add_node(val);
+        self.id_map.insert(id, node_ix);
+        info!("Added new detached node {:?} for {:?}", node_ix, &self[node_ix]);
+        node_ix
+    }
+
     /// Add an existing node (detached in the graph) to the tree.
     /// Note that un-attached nodes shouldn't exist for too long.
     fn attach_child(&mut self, parent_ix: NodeIndex, child_ix: NodeIndex) -> EdgeIndex {
@@ -316,6 +330,32 @@ impl InnerTree {
         Ok(())
     }
 
+    /// Swaps the positions of two nodes in the tree, which do not need
+    /// to be siblings. Each node takes the parent and edge weight of the other.
+    ///
+    /// The caller must ensure neither node is an ancestor of the other.
+    pub fn swap_nodes(&mut self, node1_ix: NodeIndex, node2_ix: NodeIndex)
+                      -> Result<(), GraphError> {
+        let parent1_ix = try!(self.parent_of(node1_ix));
+        let parent2_ix = try!(self.parent_of(node2_ix));
+        if parent1_ix == parent2_ix {
+            return self.swap_node_order(node1_ix, node2_ix)
+        }
+        let edge1 = self.graph.find_edge(parent1_ix, node1_ix)
+            .expect("Could not get edge between parent and child");
+        let edge2 = self.graph.find_edge(parent2_ix, node2_ix)
+            .expect("Could not get edge between parent and child");
+        let weight1 = *self.graph.edge_weight(edge1)
+            .expect("Could not get weight between parent and child");
+        let weight2 = *self.graph.edge_weight(edge2)
+            .expect("Could not get weight between parent and child");
+        self.graph.remove_edge(edge1);
+        self.graph.remove_edge(edge2);
+        self.graph.update_edge(parent1_ix, node2_ix, weight1);
+        self.graph.update_edge(parent2_ix, node1_ix, weight2);
+        Ok(())
+    }
+
     /// Moves the node index at source so that it is a child of the target node.
     /// If the node was moved, the new parent of the source node is returned
     /// (which is always the same as the target node).
@@ -0,0 +1,115 @@
+//! Callbacks that are run when the tree changes.
+
+use std::fmt;
+use petgraph::graph::NodeIndex;
+use uuid::Uuid;
+use super::container::Layout;
+use ::layout::actions::focus::ClientInfo;
+
+/// A callback that is run after the tree is laid out.
+///
+/// It is given the node the layout started at.
+pub type ReflowHook = Box<FnMut(NodeIndex) + Send>;
+
+/// A callback that is run after the layout of a container changes.
+///
+/// It is given the id of the container, the name of its workspace,
+/// and the new layout.
+pub type LayoutChangedHook = Box<FnMut(Uuid, &str, Layout) + Send>;
+
+/// A callback that is run after a view is focused.
+///
+/// It is given the information about the newly focused view.
+pub type FocusHook = Box<FnMut(&ClientInfo) + Send>;
+
+/// A callback that is run after the active container changes.
+///
+/// It is given the id of the container that was active, if there was one
+/// still in the tree, and the id of the one that is active now.
+pub type FocusChangeHook = Box<FnMut(Option<Uuid>, Uuid) + Send>;
+
+/// The callbacks registered on the tree.
+pub struct Hooks {
+    reflow: Vec<ReflowHook>,
+    layout_changed: Vec<LayoutChangedHook>,
+    focus: Vec<FocusHook>,
+    focus_change: Vec<FocusChangeHook>
+}
+
+impl Hooks {
+    pub fn new() -> Self {
+        Hooks {
+            reflow: Vec::new(),
+            layout_changed: Vec::new(),
+            focus: Vec::new(),
+            focus_change: Vec::new()
+        }
+    }
+
+    /// Registers a callback to run after every (outermost) layout of the tree.
+    pub fn add_reflow_hook<F>(&mut self, hook: F)
+        where F: FnMut(NodeIndex) + Send + 'static
+    {
+        self.reflow.push(Box::new(hook))
+    }
+
+    /// Runs the reflow callbacks.
+    pub fn reflowed(&mut self, node_ix: NodeIndex) {
+        for hook in self.reflow.iter_mut() {
+            hook(node_ix)
+        }
+    }
+
+    /// Registers a callback to run when the layout of a container changes.
+    pub fn add_layout_changed_hook<F>(&mut self, hook: F)
+        where F: FnMut(Uuid, &str, Layout) + Send + 'static
+    {
+        self.layout_changed.push(Box::new(hook))
+    }
+
+    /// Runs the layout changed callbacks.
+    pub fn layout_changed(&mut self, id: Uuid, workspace: &str, layout: Layout) {
+        for hook in self.layout_changed.iter_mut() {
+            hook(id, workspace, layout)
+        }
+    }
+
+    /// Registers a callback to run when a view is focused.
+    pub fn add_focus_hook<F>(&mut self, hook: F)
+        where F: FnMut(&ClientInfo) + Send + 'static
+    {
+        self.focus.push(Box::new(hook))
+    }
+
+    /// Runs the focus callbacks.
+    pub fn focused(&mut self, info: &ClientInfo) {
+        for hook in self.focus.iter_mut() {
+            hook(info)
+        }
+    }
+
+    /// Registers a callback to run when the active container changes.
+    pub fn add_focus_change_hook<F>(&mut self, hook: F)
+        where F: FnMut(Option<Uuid>, Uuid) + Send + 'static
+    {
+        self.focus_change.push(Box::new(hook))
+    }
+
+    /// Runs the focus change callbacks.
+    pub fn focus_changed(&mut self, old: Option<Uuid>, new: Uuid) {
+        for hook in self.focus_change.iter_mut() {
+            hook(old, new)
+        }
+    }
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("reflow", &self.reflow.len())
+            .field("layout_changed", &self.layout_changed.len())
+            .field("focus", &self.focus.len())
+            .field("focus_change", &self.focus_change.len())
+            .finish()
+    }
+}
@@ -4,9 +4,12 @@ pub mod action;
 pub mod bar;
 pub mod borders;
 pub mod background;
+pub mod geometry;
+pub mod hooks;
 mod path;
 mod graph_tree;
 
 pub use self::tree::{Direction, TreeError};
 pub use self::graph_tree::{InnerTree, GraphError, ShiftDirection};
 pub use self::container::MIN_SIZE;
+pub use self::geometry::{GeometryDelta, GeometryDiff};
@@ -1,18 +1,20 @@
 //! Main module to handle the layout.
 //! This is where the i3-specific code is.
 
+use std::error::Error;
 use std::fmt;
 use std::collections::HashSet;
 use std::ops::Deref;
 use petgraph::graph::NodeIndex;
 use uuid::Uuid;
 use rustwlc::callback::{positioner_get_anchor_rect, positioner_get_size,};
-use rustwlc::{ResizeEdge, WlcView, WlcOutput,
+use rustwlc::{Geometry, Point, ResizeEdge, Size, WlcView, WlcOutput, VIEW_BIT_UNMANAGED,
               RESIZE_LEFT, RESIZE_RIGHT, RESIZE_TOP, RESIZE_BOTTOM};
 use ::render::{Renderable};
 use super::super::LayoutTree;
 use super::super::ActionErr;
-use super::container::{Container, ContainerType, ContainerErr, Layout, Handle};
+use super::container::{Container, ContainerType, ContainerErr, FullscreenMode, Layout, Handle,
+                       OutputTransform, Struts};
 use super::borders::{Borders};
 use super::background::MaybeBackground;
 use ::layout::actions::borders;
@@ -20,6 +22,8 @@ use ::layout::actions::focus::FocusError;
 use ::layout::actions::movement::MovementError;
 use ::layout::actions::layout::LayoutErr;
 use ::layout::actions::resize::ResizeErr;
+use ::layout::actions::dispatch::CommandErr;
+use ::layout::actions::criteria::Criteria;
 
 
 use super::super::core::graph_tree::GraphError;
@@ -135,6 +139,92 @@ pub enum TreeError {
     OutputExists(WlcOutput),
     /// Handle was not found
     HandleNotFound(Handle),
+    /// Attempted to swap a container with one of its own
+    /// ancestors or descendants.
+    SwapWithDescendant(Uuid, Uuid),
+    /// An error occurred while trying to parse or run a command string
+    Command(CommandErr),
+    /// No container in the tree holds this mark.
+    MarkNotFound(String),
+    /// The container was not floating, but it needed to be.
+    ContainerWasNotFloating(NodeIndex),
+    /// A workspace with this name already exists.
+    WorkspaceExists(String),
+    /// The name can not be used for a workspace.
+    InvalidWorkspaceName(String),
+    /// The workspace with this name is on a different output.
+    WorkspaceOnOtherOutput(String),
+    /// The view had no usable geometry, and neither did anything
+    /// that could stand in for it.
+    NoGeometry(WlcView),
+    /// Nothing that is still in the tree was focused before the
+    /// active container.
+    NoPreviousFocus,
+    /// No focusable view matched the criteria.
+    NoMatchingView(Criteria),
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TreeError::ContainerWasFloating(node_ix) =>
+                write!(f, "node {} is floating", node_ix.index()),
+            TreeError::NodeNotFound(id) => write!(f, "no container with id {}", id),
+            TreeError::NodeWasRemoved(node_ix) =>
+                write!(f, "node {} was removed from the tree", node_ix.index()),
+            TreeError::ViewNotFound(view) => write!(f, "view {:?} is not in the tree", view),
+            TreeError::OutputNotFound(output) =>
+                write!(f, "output {:?} is not in the tree", output),
+            TreeError::UuidNotAssociatedWith(c_type) =>
+                write!(f, "id is not associated with a {:?}", c_type),
+            TreeError::UuidWrongType(id, ref expected) =>
+                write!(f, "container {} is not one of {:?}", id, expected),
+            TreeError::NoActiveContainer => write!(f, "there is no focused container"),
+            TreeError::InvalidOperationOnRootContainer(id) =>
+                write!(f, "can not do that to the root container {}", id),
+            TreeError::PetGraph(ref err) => write!(f, "tree invariant broken: {:?}", err),
+            TreeError::Focus(ref err) => write!(f, "{}", err),
+            TreeError::Movement(ref err) => write!(f, "{}", err),
+            TreeError::Layout(ref err) => write!(f, "{:?}", err),
+            TreeError::Resize(ref err) => write!(f, "{}", err),
+            TreeError::Action(ref err) => write!(f, "{:?}", err),
+            TreeError::Container(ref err) => write!(f, "{:?}", err),
+            TreeError::PerformingAction(true) => write!(f, "an action is already in progress"),
+            TreeError::PerformingAction(false) => write!(f, "no action is in progress"),
+            TreeError::OutputExists(output) =>
+                write!(f, "output {:?} is already in the tree", output),
+            TreeError::HandleNotFound(ref handle) => write!(f, "{:?} is not in the tree", handle),
+            TreeError::SwapWithDescendant(id, other_id) =>
+                write!(f, "can not swap {} with its ancestor or descendant {}", id, other_id),
+            TreeError::Command(ref err) => write!(f, "bad command: {:?}", err),
+            TreeError::MarkNotFound(ref mark) => write!(f, "no container is marked \"{}\"", mark),
+            TreeError::ContainerWasNotFloating(node_ix) =>
+                write!(f, "node {} is not floating", node_ix.index()),
+            TreeError::WorkspaceExists(ref name) =>
+                write!(f, "workspace \"{}\" already exists", name),
+            TreeError::InvalidWorkspaceName(ref name) =>
+                write!(f, "\"{}\" can not be used as a workspace name", name),
+            TreeError::WorkspaceOnOtherOutput(ref name) =>
+                write!(f, "workspace \"{}\" is on another output", name),
+            TreeError::NoGeometry(view) =>
+                write!(f, "could not find a geometry for view {:?}", view),
+            TreeError::NoPreviousFocus =>
+                write!(f, "no other container has been focused"),
+            TreeError::NoMatchingView(ref criteria) =>
+                write!(f, "no view matches {:?}", criteria)
+        }
+    }
+}
+
+impl Error for TreeError {
+    fn source(&self) -> Option<&(Error + 'static)> {
+        match *self {
+            TreeError::Focus(ref err) => Some(err),
+            TreeError::Movement(ref err) => Some(err),
+            TreeError::Resize(ref err) => Some(err),
+            _ => None
+        }
+    }
 }
 
 impl From<ContainerErr> for TreeError {
@@ -168,6 +258,10 @@ impl LayoutTree {
     }
 
     /// Sets the active container by finding the node with the WlcView
+    ///
+    /// This also finds floating views, which are focused and raised
+    /// like any other view. Since they are children of the root container,
+    /// the active path then ends at the floating view.
     pub fn set_active_view(&mut self, handle: WlcView) -> CommandResult {
         if let Some(node_ix) = self.tree.descendant_with_handle(self.tree.root_ix(),
                                                                 handle.into()) {
@@ -215,6 +309,42 @@ impl LayoutTree {
         Ok(&mut self.tree[node_ix])
     }
 
+    /// Gets the UUID of the container that holds the view, if the view is in the tree.
+    pub fn uuid_of_view(&self, view: WlcView) -> Option<Uuid> {
+        self.tree.lookup_view(view)
+            .map(|node_ix| self.tree[node_ix].get_id())
+    }
+
+    /// Gets the view behind the UUID.
+    ///
+    /// Returns `None` if there is no node with that id, or if it isn't a view.
+    pub fn view_of_uuid(&self, id: Uuid) -> Option<WlcView> {
+        match self.lookup(id) {
+            Ok(&Container::View { handle, .. }) => Some(handle),
+            _ => None
+        }
+    }
+
+    /// Gets where the container behind the UUID currently is on screen.
+    ///
+    /// For views this is the geometry of the view itself, for containers
+    /// and workspaces it is the area they were laid out in.
+    pub fn geometry_of(&self, id: Uuid) -> Result<Geometry, TreeError> {
+        let container = self.lookup(id)?;
+        match *container {
+            Container::View { handle, effective_geometry, .. } => {
+                Ok(handle.get_geometry().unwrap_or(effective_geometry))
+            },
+            Container::Container { geometry, .. } |
+            Container::Workspace { geometry, .. } => Ok(geometry),
+            Container::Output { .. } | Container::Root(_) => {
+                Err(TreeError::UuidWrongType(id, vec!(ContainerType::View,
+                                                      ContainerType::Container,
+                                                      ContainerType::Workspace)))
+            }
+        }
+    }
+
     /// Sets the active container to be the given node.
     pub fn set_active_node(&mut self, node_ix: NodeIndex) -> CommandResult {
         let (container_id, container_type) = {
@@ -227,16 +357,33 @@ impl LayoutTree {
                 return Err(TreeError::Focus(FocusError::BlockedByFullscreen(container_id, fullscreen_id)))
             }
         }
+        if !self.tree[node_ix].focusable() {
+            return Err(TreeError::Focus(FocusError::NotFocusable(container_id)))
+        }
 
         info!("Active container was {}, is now {}",
                 self.active_container.map_or("not set".into(), |node| node.index().to_string()),
                 node_ix.index());
 
+        // A pending split only applies while its view stays focused
+        if self.pending_split.map(|(id, _)| id) != Some(container_id) {
+            self.pending_split = None;
+        }
+
         let old_active = self.active_container;
         self.active_container = Some(node_ix);
 
         match container_type {
-            ContainerType::View => self.focus_on(container_id)?,
+            ContainerType::View => {
+                self.focus_on(container_id)?;
+                if self.tree[node_ix].floating() {
+                    self.raise_floating(container_id)?;
+                }
+                self.raise_transients(node_ix);
+                // The user has seen it now
+                self.tree[node_ix].set_urgent(false)
+                    .expect("View could not be made not urgent");
+            },
             ContainerType::Container => {/* TODO implement this */},
             _ => return Err(
                 TreeError::UuidWrongType(container_id, vec!(ContainerType::View, ContainerType::Container)))
@@ -249,15 +396,41 @@ impl LayoutTree {
                     let parent_node = self.tree.lookup_view(parent);
                     if parent_node != old_active {
                         self.set_borders(old_active_ix, borders::Mode::Inactive)?;
+                        self.update_border_thickness(old_active_ix)?;
                     }
                 },
                 _ => {}
             }
         }
         self.set_borders(node_ix, borders::Mode::Active)?;
+        self.update_border_thickness(node_ix)?;
+        if let Ok(workspace_ix) = self.tree.ancestor_of_type(node_ix, ContainerType::Workspace) {
+            let workspace_id = self.tree[workspace_ix].get_id();
+            self.last_focused.insert(workspace_id, container_id);
+        }
+        self.push_focus_history(container_id);
+        if let Some(info) = self.focused_client_info() {
+            self.hooks.focused(&info);
+        }
+        if old_active != Some(node_ix) {
+            let old_id = old_active
+                .and_then(|old_active_ix| self.tree.get(old_active_ix))
+                .map(|container| container.get_id());
+            self.hooks.focus_changed(old_id, container_id);
+        }
         Ok(())
     }
 
+    /// Registers a callback that is run whenever the active container
+    /// changes, with the id of the old one (if there was one) and the new one.
+    ///
+    /// It is not run when focusing is blocked, e.g by a fullscreen view.
+    pub fn on_focus_change<F>(&mut self, hook: F)
+        where F: FnMut(Option<Uuid>, Uuid) + Send + 'static
+    {
+        self.hooks.add_focus_change_hook(hook)
+    }
+
     /// Unsets the active container. This should be used when focusing on
     /// a view that is not a part of the tree.
     pub fn unset_active_container(&mut self) {
@@ -341,8 +514,28 @@ impl LayoutTree {
     }
 
     /// Add a new view container with the given WlcView to the active container
+    ///
+    /// If a layout is being restored in the active workspace and the view
+    /// matches one of its placeholders, it's put there instead.
+    /// Dialogs of tiled views are floated over their parent, see
+    /// `add_transient_view`. Views with an assignment rule are put on
+    /// the workspace of the rule, see `add_assignment`.
     pub fn add_view(&mut self, view: WlcView) -> Result<&Container, TreeError> {
+        if let Some(parent_ix) = self.transient_parent(view) {
+            return self.add_transient_view(view, parent_ix)
+        }
+        if let Some(assignment) = self.assignment_for(view) {
+            return self.add_assigned_view(view, assignment)
+        }
         if let Some(mut active_ix) = self.active_container {
+            if let Some((parent_ix, pos)) = self.take_placeholder(view)? {
+                return self.add_view_at(view, parent_ix, pos)
+            }
+            if let Some((split_id, layout)) = self.pending_split.take() {
+                if self.tree[active_ix].get_id() == split_id {
+                    self.split_view(active_ix, layout)?;
+                }
+            }
             let parent_ix = try!(self.tree.parent_of(active_ix)
                                  .map_err(|err| TreeError::PetGraph(err)));
             // Get the previous position before correcting the container
@@ -353,64 +546,79 @@ impl LayoutTree {
                 active_ix = try!(self.tree.parent_of(active_ix)
                                  .map_err(|err| TreeError::PetGraph(err)));
             }
-            let draw_title = match self.tree[active_ix].get_layout()? {
-                Layout::Tabbed | Layout::Stacked => false,
-                Layout::Horizontal | Layout::Vertical => true
-            };
-            let geometry = view.get_geometry()
-                .expect("View had no geometry");
-            let output = view.get_output();
-            let borders = Borders::new(geometry, output)
-                .map(|mut b| {
-                    b.title = Container::get_title(view);
-                    b.draw_title = draw_title;
-                    b
-                });
-            let view_ix = self.tree.add_child(active_ix,
-                                              Container::new_view(view, borders),
-                                              true);
-            self.tree.set_child_pos(view_ix, prev_pos);
-            self.validate();
-            match self.set_active_node(view_ix) {
-                Ok(_) => {},
-                Err(TreeError::Focus(FocusError::BlockedByFullscreen(_, _))) => {
-                    info!("Blocked focus by fullscreen");
-                },
-                Err(err) => return Err(err)
-            }
-            return Ok(&self.tree[view_ix])
+            return self.add_view_at(view, active_ix, prev_pos)
         }
         self.validate();
         Err(TreeError::NoActiveContainer)
     }
 
+    /// Adds a new view container as a child of the container at the
+    /// node index, at the given position among its children, and focuses it.
+    fn add_view_at(&mut self, view: WlcView, parent_ix: NodeIndex, pos: u32)
+                   -> Result<&Container, TreeError> {
+        let draw_title = match self.tree[parent_ix].get_layout()? {
+            Layout::Tabbed | Layout::Stacked => false,
+            Layout::Horizontal | Layout::Vertical => true
+        };
+        let geometry = self.usable_geometry(view, parent_ix)?;
+        let output = view.get_output();
+        let borders = Borders::new(geometry, output)
+            .map(|mut b| {
+                b.title = Container::get_title(view);
+                b.draw_title = draw_title;
+                b
+            });
+        let view_ix = self.tree.add_child(parent_ix,
+                                          Container::new_view(view, borders),
+                                          true);
+        self.tree.set_child_pos(view_ix, pos);
+        self.validate();
+        match self.set_active_node(view_ix) {
+            Ok(_) => {},
+            Err(TreeError::Focus(FocusError::BlockedByFullscreen(_, _))) => {
+                info!("Blocked focus by fullscreen");
+            },
+            Err(err) => return Err(err)
+        }
+        Ok(&self.tree[view_ix])
+    }
+
     /// Adds a new view container with the given WlcView to the workspace of the active container.
     ///
     /// The view is automatically made floating, with no modifications to its geometry.
     pub fn add_floating_view(&mut self, view: WlcView, borders: Option<Borders>)
                              -> Result<&Container, TreeError> {
         if let Some(root_ix) = self.root_container_ix() {
+            let anchored_geometry = match positioner_get_anchor_rect(view) {
+                Some(anchor) => {
+                    let mut geo = self.usable_geometry(view, root_ix)?;
+                    let size = positioner_get_size(view)
+                        .and_then(|size| if size.w > 0 && size.h > 0 { Some(size) } else { None })
+                        .unwrap_or(geo.size);
+                    geo.origin = anchor.origin;
+                    geo.size = size;
+                    let parent = view.get_parent();
+                    if !parent.is_root() {
+                        if let Some(parent_geo) = parent.get_geometry() {
+                            geo.origin.x += parent_geo.origin.x;
+                            geo.origin.y += parent_geo.origin.y;
+                        }
+                    }
+                    Some(geo)
+                },
+                None => None
+            };
             let view_ix = self.tree.add_child(root_ix,
                                              Container::new_view(view, borders),
                                              false);
             let container = &mut self.tree[view_ix];
             container.set_floating(true)
                 .expect("Could not float view we just made");
-            if let Some(anchor) = positioner_get_anchor_rect(view) {
-                let mut geo = view.get_geometry().expect("View had no geometry");
-                let mut size = positioner_get_size(view).expect("View had no size");
-                if size.w <= 0 || size.h <= 0 {
-                    size = geo.size;
-                }
-                geo.origin = anchor.origin;
-                geo.size = size;
-                let parent = view.get_parent();
-                if !parent.is_root() {
-                    let parent_geo = parent.get_geometry()
-                        .expect("Parent view had no geometry");
-                    geo.origin.x += parent_geo.origin.x;
-                    geo.origin.y += parent_geo.origin.y;
-                }
+            // Unmanaged surfaces are overlays (notifications, tooltips, ...)
+            // that shouldn't take the focus from the real application.
+            container.set_focusable(!view.get_type().intersects(VIEW_BIT_UNMANAGED))
+                .expect("Could not set if the view is focusable");
+            if let Some(geo) = anchored_geometry {
                 container.set_geometry(ResizeEdge::empty(), geo);
             }
             return Ok(&*container)
@@ -419,6 +627,30 @@ impl LayoutTree {
         Err(TreeError::NoActiveContainer)
     }
 
+    /// Gets the geometry of the view, if it has one that isn't empty.
+    ///
+    /// Clients can report no geometry or an empty one (e.g when they are
+    /// just starting up), in which case the geometry of the container at
+    /// the node index is used, and failing that the view's output.
+    pub fn usable_geometry(&self, view: WlcView, fallback_ix: NodeIndex)
+                       -> Result<Geometry, TreeError> {
+        fn usable(geometry: Option<Geometry>) -> Option<Geometry> {
+            match geometry {
+                Some(geometry) if geometry.size.w > 0 && geometry.size.h > 0 =>
+                    Some(geometry),
+                _ => None
+            }
+        }
+        usable(view.get_geometry())
+            .or_else(|| usable(self.tree.get(fallback_ix)
+                               .and_then(|container| container.get_geometry())))
+            .or_else(|| usable(view.get_output().get_resolution().map(|size| Geometry {
+                origin: Point { x: 0, y: 0 },
+                size: size
+            })))
+            .ok_or(TreeError::NoGeometry(view))
+    }
+
     /// Adds the container with the node index as a child.
     /// The node at the node index is removed and
     /// made a child of the new container node.
@@ -472,16 +704,152 @@ impl LayoutTree {
         // to make a workspace that already exists will result in a crash!
         self.active_container = Some(self.init_workspace(output.0.to_string(),
                                                          output_ix));
+        self.spawn_default_background(output);
+        // The workspaces of the output that was kept while headless move here
+        if let Some(old_output) = self.headless_output.take() {
+            self.remove_output(old_output)?;
+        }
+        self.validate();
+        Ok(())
+    }
+
+    /// Removes the output from the tree, e.g when it was unplugged.
+    ///
+    /// Its workspaces are moved to another output, keeping their names as
+    /// those are already unique across the whole tree. If the active
+    /// container was on the output it stays active on the new one,
+    /// otherwise what that output showed stays visible.
+    ///
+    /// If it's the last output, the tree is kept as it is but marked as
+    /// headless, until the next output is added and takes its workspaces.
+    pub fn remove_output(&mut self, output: WlcOutput) -> CommandResult {
+        let root_ix = self.tree.root_ix();
+        let output_ix = self.tree.descendant_with_handle(root_ix, output.into())
+            .ok_or(TreeError::OutputNotFound(output))?;
+        let target_ix = match self.tree.children_of(root_ix).into_iter()
+            .find(|&other_ix| other_ix != output_ix) {
+                Some(target_ix) => target_ix,
+                None => {
+                    warn!("Removed the last output {:?}, no outputs are left", output);
+                    self.headless_output = Some(output);
+                    return Ok(())
+                }
+            };
+        let target = match self.tree[target_ix].get_handle()? {
+            Handle::Output(target) => target,
+            _ => unreachable!()
+        };
+        let visible_ix = self.tree.follow_path(target_ix);
+        for workspace_ix in self.tree.children_of(output_ix) {
+            for node_ix in self.tree.all_descendants_of(workspace_ix) {
+                match self.tree[node_ix].get_type() {
+                    ContainerType::View | ContainerType::Container => {
+                        self.tree[node_ix].update_border_output(target)?;
+                    },
+                    _ => {}
+                }
+            }
+            self.tree.move_node(workspace_ix, target_ix);
+        }
+        self.tree.remove(output_ix);
+        if self.headless_output == Some(output) {
+            self.headless_output = None;
+        }
+        // Moving the workspaces could have changed the path of the output
+        self.tree.set_ancestor_paths_active(visible_ix);
+        if let Some(active_ix) = self.active_container {
+            self.tree.set_ancestor_paths_active(active_ix);
+        }
+        let visible_workspace_ix = self.tree.follow_path_until(target_ix, ContainerType::Workspace)
+            .map_err(|_| TreeError::UuidNotAssociatedWith(ContainerType::Workspace))?;
+        for workspace_ix in self.tree.children_of(target_ix) {
+            self.set_container_visibility(workspace_ix, workspace_ix == visible_workspace_ix);
+        }
+        self.layout(target_ix);
+        self.validate();
+        Ok(())
+    }
+
+    /// Whether the last output was removed, see `remove_output`.
+    pub fn is_headless(&self) -> bool {
+        self.headless_output.is_some()
+    }
+
+    /// Reserves space on the edges of the output, so that tiled containers
+    /// are not placed there (e.g for an external panel or dock).
+    ///
+    /// Every workspace on the output is laid out again.
+    pub fn set_struts(&mut self, output: WlcOutput, struts: Struts) -> CommandResult {
+        let root_ix = self.tree.root_ix();
+        let output_ix = self.tree.descendant_with_handle(root_ix, output.into())
+            .ok_or(TreeError::OutputNotFound(output))?;
+        match self.tree[output_ix] {
+            Container::Output { struts: ref mut output_struts, .. } => {
+                if *output_struts == struts {
+                    return Ok(())
+                }
+                *output_struts = struts;
+            },
+            _ => unreachable!()
+        }
+        self.layout(output_ix);
+        Ok(())
+    }
+
+    /// Rotates or flips the contents of the output.
+    ///
+    /// Every workspace on the output is laid out again, with the width and
+    /// height swapped if the output is now on its side. Floating views are
+    /// moved and shrunk so that they stay within their workspace.
+    pub fn set_output_transform(&mut self, output: WlcOutput, transform: OutputTransform)
+                                -> CommandResult {
+        let root_ix = self.tree.root_ix();
+        let output_ix = self.tree.descendant_with_handle(root_ix, output.into())
+            .ok_or(TreeError::OutputNotFound(output))?;
+        match self.tree[output_ix] {
+            Container::Output { transform: ref mut output_transform, .. } => {
+                if *output_transform == transform {
+                    return Ok(())
+                }
+                *output_transform = transform;
+            },
+            _ => unreachable!()
+        }
+        self.layout(output_ix);
+        for workspace_ix in self.tree.children_of(output_ix) {
+            let bounds = self.tree[workspace_ix].get_geometry()
+                .expect("Workspace had no geometry");
+            let floating = self.tree.all_descendants_of(workspace_ix).into_iter()
+                .filter(|&node_ix| self.tree[node_ix].get_type() == ContainerType::View
+                        && self.tree[node_ix].floating());
+            for node_ix in floating.collect::<Vec<_>>() {
+                let container = &mut self.tree[node_ix];
+                let geometry = container.get_geometry()
+                    .expect("View had no geometry");
+                let clamped = clamp_geometry(geometry, bounds);
+                if clamped != geometry {
+                    container.set_geometry(ResizeEdge::empty(), clamped);
+                    container.resize_borders(clamped);
+                    container.draw_borders()?;
+                }
+            }
+        }
         self.validate();
         Ok(())
     }
 
     //// Remove a view container from the tree
     pub fn remove_view(&mut self, view: WlcView) -> Result<Container, TreeError> {
-        if let Some(view_ix) = self.tree.descendant_with_handle(self.tree.root_ix(),
-                                                                view.into()) {
+        let maybe_view_ix = self.tree.descendant_with_handle(self.tree.root_ix(),
+                                                             view.into())
+            .or_else(|| self.scratchpad.and_then(|scratchpad_ix| {
+                self.tree.descendant_with_handle(scratchpad_ix, view.into())
+            }));
+        if let Some(view_ix) = maybe_view_ix {
+            let workspace_ix = self.tree.ancestor_of_type(view_ix, ContainerType::Workspace)?;
             let container = self.remove_view_or_container(view_ix)
                 .expect("Could not remove node we just verified exists!");
+            self.close_dynamic_workspace(workspace_ix);
             self.validate();
             Ok(container)
         } else {
@@ -542,6 +910,22 @@ impl LayoutTree {
     /// We have to ensure that we aren't invalidating the active container
     /// when we remove a view or container.
     pub fn remove_view_or_container(&mut self, node_ix: NodeIndex) -> Result<Container, TreeError> {
+        self.remove_node(node_ix, true)
+    }
+
+    /// Removes a View or Container, like `remove_view_or_container`,
+    /// but does not focus on the next container afterwards.
+    ///
+    /// If the node was the active container, there is no active container
+    /// after this. Use this when removing many nodes, where the focus
+    /// is set explicitly afterwards.
+    pub fn remove_node_no_refocus(&mut self, node_ix: NodeIndex)
+                                  -> Result<Container, TreeError> {
+        self.remove_node(node_ix, false)
+    }
+
+    fn remove_node(&mut self, node_ix: NodeIndex, refocus: bool)
+                   -> Result<Container, TreeError> {
         // Only the root container has a non-container parent, and we can't remove that
         if self.tree.is_root_container(node_ix) {
             let id = self.tree[node_ix].get_id();
@@ -561,7 +945,9 @@ impl LayoutTree {
             .expect("Container was not part of a workspace");
         let parent_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Container)
             .unwrap_or(workspace_ix);
-        let container = try!(self.tree.remove(node_ix)
+        let index = self.tree.grounded_children(parent_ix).iter()
+            .position(|&child_ix| child_ix == node_ix);
+        let mut container = try!(self.tree.remove(node_ix)
                                 .ok_or(TreeError::NodeWasRemoved(node_ix)));
 
         // Make sure we remove other instances of the index
@@ -575,6 +961,26 @@ impl LayoutTree {
         self.tree[workspace_ix].update_fullscreen_c(uuid, false)
             .expect("workspace_ix did not point to a workspace");
 
+        // Tags the view was following
+        self.view_tags.remove(&uuid);
+
+        // Remembered scratchpad geometry
+        self.scratchpad_geometry.remove(&uuid);
+
+        // Last focused container, if it was a workspace
+        self.last_focused.remove(&uuid);
+
+        if self.global_fullscreen == Some(uuid) {
+            self.global_fullscreen = None;
+        }
+
+        if self.pending_split.map(|(id, _)| id) == Some(uuid) {
+            self.pending_split = None;
+        }
+
+        // Marks, so they are free to be used by another container
+        container.marks_mut().map(|marks| marks.clear());
+
         match container {
             Container::View { .. } | Container::Container { .. } => {},
             _ => unreachable!()
@@ -585,13 +991,24 @@ impl LayoutTree {
         match parent_type {
             ContainerType::Container => {
                 if self.tree.can_remove_empty_parent(parent_ix) {
-                    try!(self.remove_view_or_container(parent_ix));
+                    try!(self.remove_node(parent_ix, refocus));
+                } else {
+                    // Share the freed space between the siblings
+                    self.rebalance_children(parent_ix);
                 }
                 self.validate();
             }
             _ => {},
         }
-        self.focus_on_next_container(parent_ix);
+        // Nothing in the scratchpad should ever be focused.
+        // If the parent was removed too, focus was already moved then.
+        if refocus && !self.is_scratchpad(workspace_ix) && self.tree.get(parent_ix).is_some() {
+            match index {
+                Some(index) if self.active_container.is_none() =>
+                    self.focus_on_nearest_child(parent_ix, index),
+                _ => self.focus_on_next_container(parent_ix)
+            }
+        }
         trace!("Removed container {:?}, index {:?}", result, node_ix);
         result
     }
@@ -599,7 +1016,10 @@ impl LayoutTree {
     /// Removes the current active container
     pub fn remove_active(&mut self) -> Result<Container, TreeError> {
         if let Some(active_ix) = self.active_container {
-            self.remove_view_or_container(active_ix)
+            let workspace_ix = self.tree.ancestor_of_type(active_ix, ContainerType::Workspace)?;
+            let container = self.remove_view_or_container(active_ix)?;
+            self.close_dynamic_workspace(workspace_ix);
+            Ok(container)
         } else {
             Err(TreeError::NoActiveContainer)
         }
@@ -613,6 +1033,15 @@ impl LayoutTree {
         if self.tree[node_ix].get_type() != ContainerType::Workspace {
             Err(TreeError::UuidNotAssociatedWith(ContainerType::Workspace))?
         }
+        let workspace_id = self.tree[node_ix].get_id();
+        self.pending_layouts.remove(&workspace_id);
+        self.floating_order.remove(&workspace_id);
+        // Going back to it would only make it again
+        let was_prev = self.prev_workspace.as_ref().map(|name| name.as_str())
+            == self.tree[node_ix].get_name();
+        if was_prev {
+            self.prev_workspace = None;
+        }
         let mut children = self.tree.all_descendants_of(node_ix);
         // add current container to the list as well
         children.push(node_ix);
@@ -625,7 +1054,7 @@ impl LayoutTree {
             match self.tree.get(child_ix) {
                 None => return Err(TreeError::NodeWasRemoved(child_ix)),
                 Some(&Container::View { .. }) => {
-                    self.remove_view_or_container(child_ix)?;
+                    self.remove_node_no_refocus(child_ix)?;
                 }
                 Some(&Container::Container { .. }) => {
                     if self.tree.is_root_container(child_ix) {
@@ -633,7 +1062,7 @@ impl LayoutTree {
                         // because there are checks against doing this in tree
                         self.tree.remove(child_ix);
                     } else {
-                        self.remove_view_or_container(child_ix)?;
+                        self.remove_node_no_refocus(child_ix)?;
                     }
                 },
                 Some(_) => {
@@ -722,6 +1151,10 @@ impl LayoutTree {
                            .ok_or(TreeError::NodeNotFound(id)));
         let workspace_ix = try!(self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)
                                 .map_err(|err| TreeError::PetGraph(err)));
+        // A globally fullscreen container covers every output
+        if let Some(global_id) = self.global_fullscreen {
+            return Ok(Some(global_id))
+        }
         let workspace = &self.tree[workspace_ix];
         let children = try!(workspace.fullscreen_c()
                             .ok_or(TreeError::UuidWrongType(workspace.get_id(),
@@ -729,145 +1162,226 @@ impl LayoutTree {
         Ok(children.last().cloned())
     }
 
+    /// Sets the container behind the UUID to be fullscreen.
+    ///
+    /// If the container is a non-View/Container, then an error is returned
+    /// and the flag is not set (it's only tracked for Views and Containers).
+    pub fn set_fullscreen(&mut self, id: Uuid, toggle: bool) -> CommandResult {
+        if !toggle && self.global_fullscreen == Some(id) {
+            self.global_fullscreen = None;
+        }
+        {
+            let container = try!(self.lookup_mut(id));
+            try!(container.set_fullscreen(toggle)
+                 .map_err(|_| TreeError::UuidWrongType(id, vec![ContainerType::View,
+                                                                ContainerType::Container])));
+        }
+        // Now update the workspace so that it knows which children are fullscreen.
+        //
+        // This is the workspace the container lives in, not the active one,
+        // so that fullscreen is confined to that container's output. Other
+        // outputs keep showing their own workspaces and can still be focused.
+        let node_ix = self.tree.lookup_id(id)
+            .ok_or(TreeError::NodeNotFound(id))?;
+        let workspace_ix = self.tree.ancestor_of_type(node_ix, ContainerType::Workspace)?;
+        {
+            let workspace = &mut self.tree[workspace_ix];
+            workspace.update_fullscreen_c(id, toggle).map_err(
+                |_| TreeError::UuidWrongType(workspace.get_id(),
+                                             vec![ContainerType::Workspace]))?;
+        }
+        self.layout(workspace_ix);
+        Ok(())
+    }
+
+    /// Gets how the container behind the UUID is fullscreen.
+    pub fn fullscreen_mode(&self, id: Uuid) -> Result<FullscreenMode, TreeError> {
+        let container = self.lookup(id)?;
+        Ok(if self.global_fullscreen == Some(id) {
+            FullscreenMode::Global
+        } else if container.fullscreen() {
+            FullscreenMode::Workspace
+        } else {
+            FullscreenMode::None
+        })
+    }
+
+    /// Sets how the container behind the UUID is fullscreen.
+    ///
+    /// A globally fullscreen container covers all of the outputs, and blocks
+    /// focusing on anything else on every output (not just its own).
+    /// Only one container can be globally fullscreen, so if another one was
+    /// it stops being fullscreen.
+    pub fn set_fullscreen_mode(&mut self, id: Uuid, mode: FullscreenMode) -> CommandResult {
+        self.lookup(id)?;
+        if mode == FullscreenMode::Global {
+            if let Some(old_id) = self.global_fullscreen {
+                if old_id != id {
+                    self.set_fullscreen(old_id, false)?;
+                }
+            }
+            self.global_fullscreen = Some(id);
+        } else if self.global_fullscreen == Some(id) {
+            self.global_fullscreen = None;
+        }
+        self.set_fullscreen(id, mode != FullscreenMode::None)
+    }
+
+    /// Determines if any workspace, on any output, has a fullscreen container.
+    pub fn any_fullscreen(&self) -> bool {
+        let root_ix = self.tree.root_ix();
+        self.tree.children_of(root_ix).into_iter()
+            .flat_map(|output_ix| self.tree.children_of(output_ix))
+            .any(|workspace_ix| self.tree[workspace_ix].fullscreen_c()
+                 .map(|fullscreen_c| !fullscreen_c.is_empty())
+                 .unwrap_or(false))
+    }
+
     /// Validates the tree
     #[cfg(any(debug_assertions, not(disable_debug)))]
     pub fn validate(&self) {
+        // Checked once the whole transaction is done
+        if self.in_transaction() {
+            return
+        }
+        if let Err(err) = self.check_structure() {
+            error!("The tree: {:#?}", self);
+            panic!("Layout tree is invalid: {}", err);
+        }
+    }
+
+    /// Validates the tree
+    #[cfg(any(debug_assertions, not(disable_debug)))]
+    pub fn validate_path(&self) {
+        if let Err(err) = self.check_active_path() {
+            error!("The tree: {:#?}", self);
+            error!("Path: {:?}", self.tree.active_path());
+            panic!("Active path was invalid: {}", err);
+        }
+    }
+
+    /// Checks the invariants of the tree, without panicking.
+    ///
+    /// This performs the same checks as `validate` and `validate_path`,
+    /// but is always compiled in and returns a description of the first
+    /// invariant that did not hold.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        self.check_structure()?;
+        self.check_active_path()
+    }
+
+    /// Checks the shape of the tree and the active container,
+    /// see `validate`.
+    fn check_structure(&self) -> Result<(), String> {
         // Recursive method to ensure child/parent nodes are connected
-        fn validate_node_connections(this: &LayoutTree, parent_ix: NodeIndex) {
+        fn check_node_connections(this: &LayoutTree, parent_ix: NodeIndex)
+                                  -> Result<(), String> {
             for child_ix in this.tree.children_of(parent_ix) {
                 let child_parent = this.tree.parent_of(child_ix)
-                    .expect("connections: Child did not point to parent!");
+                    .map_err(|_| format!("Child {:?} did not point to a parent", child_ix))?;
                 if child_parent != parent_ix {
-                    error!("Child at {:?} has parent {:?}, expected {:?}",
-                           child_ix, child_parent, parent_ix);
-                    error!("The tree: {:#?}", this);
-                    panic!("A child node did not point to the correct parent!")
+                    return Err(format!("Child at {:?} has parent {:?}, expected {:?}",
+                                       child_ix, child_parent, parent_ix))
+                }
+                check_node_connections(this, child_ix)?;
+            }
+            Ok(())
+        }
+
+        // Ensure that edge weights are always 1..n, with no holes
+        fn check_edge_count(this: &LayoutTree, parent_ix: NodeIndex)
+                            -> Result<(), String> {
+            let mut cur_weight = 0;
+            for child_ix in this.tree.children_of(parent_ix) {
+                let weight = *this.tree.get_edge_weight_between(parent_ix, child_ix)
+                    .ok_or(format!("No edge between {:?} and {:?}", parent_ix, child_ix))?
+                    .deref();
+                if weight != cur_weight + 1 {
+                    return Err(format!("Children weights of {:?} are not 1..n, \
+                                        expected {} found {}",
+                                       parent_ix, cur_weight + 1, weight))
                 }
-                validate_node_connections(this, child_ix);
+                cur_weight = weight;
+                check_edge_count(this, child_ix)?;
             }
+            Ok(())
         }
 
-        validate_node_connections(self, self.tree.root_ix());
+        let root_ix = self.tree.root_ix();
+        check_node_connections(self, root_ix)?;
 
         // Ensure active container is in tree and of right type
         if let Some(active_ix) = self.active_container {
-            if self.active_container.is_some() {
-                let active = self.get_active_container()
-                    .expect("active_container points to invalid node");
-                match active.get_type() {
-                    ContainerType::View | ContainerType::Container => {},
-                    _ => panic!("Active container was not view or container")
-                }
-                // Check active container in tree
-                if self.tree.ancestor_of_type(active_ix, ContainerType::Root).is_err() {
-                    error!("Active container @ {:?} is not part of tree!", active_ix);
-                    error!("Active container is {:?}", active);
-                    trace!("The tree: {:#?}", self);
-                    panic!("Active container is not part of the tree!")
-                }
+            let active = self.tree.get(active_ix)
+                .ok_or(format!("Active container {:?} is not in the tree", active_ix))?;
+            match active.get_type() {
+                ContainerType::View | ContainerType::Container => {},
+                ctype => return Err(format!("Active container was a {:?}, \
+                                             not a view or container", ctype))
+            }
+            if self.tree.ancestor_of_type(active_ix, ContainerType::Root).is_err() {
+                return Err(format!("Active container {:?} is not part of the tree",
+                                   active_ix))
             }
         }
 
-        // Ensure that workspace names are unique across outputs
-        // NOTE Remove/Disable this check if this feature changes
+        // Ensure that workspace names are unique across outputs,
+        // and that workspaces have at least one child
+        // NOTE Remove/Disable the name check if this feature changes
         let mut names = HashSet::new();
-        for output_ix in self.tree.children_of(self.tree.root_ix()) {
-            for workspace_ix in self.tree.children_of(output_ix) {
+        for output_ix in self.tree.children_of(root_ix) {
+            let workspaces = self.tree.children_of(output_ix);
+            if workspaces.len() == 0 {
+                return Err(format!("Output {:?} has no workspaces", output_ix))
+            }
+            for workspace_ix in workspaces {
                 if !names.insert(self.tree[workspace_ix].name()) {
-                    error!("Duplicate workspace name found: {:?}",
-                           self.tree[workspace_ix].name());
-                    error!("Tree: {:#?}", self);
-                    panic!("Duplicate workspace found across outputs!");
+                    return Err(format!("Duplicate workspace name {:?}",
+                                       self.tree[workspace_ix].name()))
                 }
-            }
-        }
-
-        // Ensure workspace have at least one child
-        for output_ix in self.tree.children_of(self.tree.root_ix()) {
-            let children = self.tree.children_of(output_ix);
-            assert!(children.len() > 0);
-            for workspace_ix in children {
                 if self.tree.children_of(workspace_ix).len() == 0 {
-                    error!("Workspace {:#?} has no children",
-                           self.tree[workspace_ix]);
-                    error!("The tree: {:#?}", self);
-                    panic!("Workspace has no children")
+                    return Err(format!("Workspace {:?} has no children",
+                                       self.tree[workspace_ix].name()))
                 }
-                for container_ix in self.tree.all_descendants_of(workspace_ix) {
-                    match self.tree[container_ix] {
+                for node_ix in self.tree.all_descendants_of(workspace_ix) {
+                    match self.tree[node_ix] {
                         Container::Container { .. } => {
-                            let parent_ix = self.tree.parent_of(container_ix)
-                                .expect("Container had no parent");
-                            if self.tree.children_of(container_ix).len() == 0
-                                && self.tree[parent_ix].get_type() != ContainerType::Workspace {
-                                    error!("Tree in invalid state. {:?} is an empty non-root container\n \
-                                            {:#?}", container_ix, *self);
-                                    panic!("Empty non-root container detected!");
+                            if self.tree.can_remove_empty_parent(node_ix) {
+                                return Err(format!("{:?} is an empty non-root container",
+                                                   node_ix))
                             }
-                            assert!(! self.tree.can_remove_empty_parent(container_ix));
                         },
-                        Container::View { .. } => {
-                        }
-                        _ => panic!("Non-view/container as descendant of a workspace!")
+                        Container::View { .. } => {},
+                        ref container => return Err(format!(
+                            "{:?} is a descendant of a workspace", container.get_type()))
                     }
                 }
             }
         }
 
-        // Ensure that edge weights are always monotonically increasing
-        fn validate_edge_count(this: &LayoutTree, parent_ix: NodeIndex) {
-            // note that the weight should never actually be 0
-            let mut cur_weight = 0;
-            for child_ix in this.tree.children_of(parent_ix) {
-                let weight = *this.tree.get_edge_weight_between(parent_ix, child_ix)
-                    .expect("Could not get edge weights between child and parent").deref();
-                // Ensure increasing
-                if weight <= cur_weight {
-                    error!("Weights were not monotonically increasing for children of {:?}", parent_ix);
-                    error!("{:#?}", this);
-                    panic!("{:?} <= {:?}!", weight, cur_weight);
-                }
-                // Ensure no holes
-                if weight != cur_weight + 1 {
-                    error!("Weights have a hole (no child with weight {}) for children of {:?}",
-                           cur_weight + 1, parent_ix);
-                    error!("{:#?}", this);
-                    panic!("Hole in children weights");
-                }
-                cur_weight = weight;
-                validate_edge_count(this, child_ix);
-            }
-        }
-        validate_edge_count(self, self.tree.root_ix());
+        check_edge_count(self, root_ix)
     }
 
-    /// Validates the tree
-    #[cfg(any(debug_assertions, not(disable_debug)))]
-    pub fn validate_path(&self) {
-        // Ensure there is only one active path from the root
+    /// Checks that there is exactly one active path from the root,
+    /// see `validate_path`.
+    fn check_active_path(&self) -> Result<(), String> {
         let mut next_ix = Some(self.tree.root_ix());
         while let Some(cur_ix) = next_ix {
             next_ix = None;
-            let mut flipped = false;
-            // Ensure that the numbers are unique and atomically increasing.
+            // Ensure that the active numbers are unique
             let mut seen = vec![];
             for child_ix in self.tree.children_of(cur_ix) {
                 let weight = *self.tree.get_edge_weight_between(cur_ix, child_ix)
-                    .expect("Could not get edge weights between child and parent");
+                    .ok_or(format!("No edge between {:?} and {:?}", cur_ix, child_ix))?;
                 if weight.is_active() {
-                    if flipped {
-                        error!("Divergent paths detected!");
-                        error!("Tree: {:#?}", self);
-                        panic!("Divergent paths detected!");
+                    if next_ix.is_some() {
+                        return Err(format!("Divergent active paths below {:?}", cur_ix))
                     }
-                    flipped = true;
                     next_ix = Some(child_ix);
                 }
                 if seen.contains(&weight.active) {
-                    error!("Active number already used!");
-                    error!("Found {:?} in {:?}", weight.active, seen);
-                    error!("Tree: {:#?}", self);
-                    panic!("Duplicate active number found");
+                    return Err(format!("Duplicate active number {} below {:?}",
+                                       weight.active, cur_ix))
                 }
                 seen.push(weight.active);
             }
@@ -875,15 +1389,9 @@ impl LayoutTree {
                 match self.tree[cur_ix].get_type() {
                     ContainerType::Root | ContainerType::View | ContainerType::Container => {}
                     container => {
-                        //warn!("Path: {:#?}", self.tree.active_path());
-                        let children = self.tree.children_of(cur_ix);
-                        if children.len() != 0 {
-                            error!("{:#?}", self);
-                            error!("{:?}", children);
-                            error!("Path did not end at a container/view, ended at {:?}!",
-                                container);
-                            error!("Path: {:?}", self.tree.active_path());
-                            panic!("Active path was invalid");
+                        if self.tree.children_of(cur_ix).len() != 0 {
+                            return Err(format!("Path did not end at a container/view, \
+                                                ended at {:?}", container))
                         }
                     }
                 }
@@ -892,7 +1400,21 @@ impl LayoutTree {
 
         // ensure that the active container is valid
         if let Some(node_ix) = self.active_container {
-            assert!(self.tree.get(node_ix).is_some());
+            if self.tree.get(node_ix).is_none() {
+                return Err(format!("Active container {:?} is not in the tree", node_ix))
+            }
+        }
+        Ok(())
+    }
+
+    /// Panics if the tree is invalid.
+    ///
+    /// Unlike `validate`, this is compiled in regardless of the build profile,
+    /// so tests built in release mode can still assert the tree is valid.
+    pub fn debug_assert_valid(&self) {
+        if let Err(err) = self.check_invariants() {
+            error!("The tree: {:#?}", self);
+            panic!("Layout tree is invalid: {}", err);
         }
     }
 
@@ -903,15 +1425,36 @@ impl LayoutTree {
     pub fn validate_path(&self) {}
 }
 
+/// Shrinks and moves the geometry so that it fits within the bounds.
+fn clamp_geometry(geometry: Geometry, bounds: Geometry) -> Geometry {
+    let size = Size {
+        w: geometry.size.w.min(bounds.size.w),
+        h: geometry.size.h.min(bounds.size.h)
+    };
+    let clamp = |start: i32, length: u32, bounds_start: i32, bounds_length: u32| {
+        let max_start = bounds_start + (bounds_length - length) as i32;
+        start.max(bounds_start).min(max_start)
+    };
+    Geometry {
+        origin: Point {
+            x: clamp(geometry.origin.x, size.w, bounds.origin.x, bounds.size.w),
+            y: clamp(geometry.origin.y, size.h, bounds.origin.y, bounds.size.h)
+        },
+        size: size
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
-    use super::super::super::LayoutTree;
+    use super::super::super::{BorderColors, FocusWrapping, LayoutTree, Orientation};
     use super::super::super::core::container::*;
     use super::super::super::core::InnerTree;
     use super::*;
     use rustwlc::*;
 
     use uuid::Uuid;
+    use std::collections::{HashMap, VecDeque};
+    use super::super::hooks::Hooks;
 
     /// Makes a very basic tree.
     /// There is only one output,
@@ -969,7 +1512,38 @@ pub mod tests {
                                                 false);
         let mut layout_tree = LayoutTree {
             tree: tree,
-            active_container: None
+            active_container: None,
+            view_tags: HashMap::new(),
+            dynamic_workspaces: false,
+            hooks: Hooks::new(),
+            suspend_count: 0,
+            pending_layout: false,
+            layout_depth: 0,
+            scratchpad: None,
+            scratchpad_geometry: HashMap::new(),
+            inner_gap: None,
+            outer_gap: 0,
+            smart_gaps: false,
+            last_focused: HashMap::new(),
+            global_fullscreen: None,
+            pending_split: None,
+            prev_workspace: None,
+            focus_history: VecDeque::new(),
+            tiled_resize: None,
+            hide_edge_borders: borders::HideEdgeBorders::None,
+            default_layout: Layout::Horizontal,
+            default_orientation: Orientation::Horizontal,
+            pending_layouts: HashMap::new(),
+            default_background: None,
+            focus_follows_mouse: false,
+            pointer_view: None,
+            focus_wrapping: FocusWrapping::No,
+            assignments: Vec::new(),
+            floating_order: HashMap::new(),
+            headless_output: None,
+            border_colors: BorderColors::default(),
+            transaction_depth: 0,
+            inactive_opacity: None
         };
         let id = layout_tree.tree[wkspc_1_view].get_id();
         layout_tree.set_active_container(id).unwrap();
@@ -1157,6 +1731,65 @@ pub mod tests {
         assert_eq!(tree.tree.children_of(active_ix).len(), 0);
     }
 
+    #[test]
+    fn remove_output_migrates_workspaces() {
+        let mut tree = basic_tree();
+        let old_output_ix = tree.active_ix_of(ContainerType::Output).unwrap();
+        let old_output = match tree.tree[old_output_ix].get_handle().unwrap() {
+            Handle::Output(output) => output,
+            _ => panic!()
+        };
+        let new_output = WlcView::dummy(5).as_output();
+        tree.add_output(new_output).unwrap();
+        let new_output_ix = tree.active_ix_of(ContainerType::Output).unwrap();
+        let active_ix = tree.active_container;
+        tree.remove_output(old_output).unwrap();
+        let root_ix = tree.tree.root_ix();
+        assert_eq!(tree.tree.children_of(root_ix), vec![new_output_ix]);
+        // Both workspaces moved over, keeping their names
+        for name in &["1", "2", "5"] {
+            let workspace_ix = tree.tree.workspace_ix_by_name(name).unwrap();
+            assert_eq!(tree.tree.parent_of(workspace_ix), Ok(new_output_ix));
+        }
+        assert_eq!(tree.tree.children_of(new_output_ix).len(), 3);
+        // The workspace of the remaining output is still the one shown
+        assert_eq!(tree.active_container, active_ix);
+        assert_eq!(tree.current_workspace(), Ok("5"));
+        assert_eq!(tree.remove_output(old_output),
+                   Err(TreeError::OutputNotFound(old_output)));
+        assert!(!tree.is_headless());
+        tree.validate();
+    }
+
+    #[test]
+    fn remove_output_keeps_active_container() {
+        let mut tree = basic_tree();
+        let old_output_ix = tree.active_ix_of(ContainerType::Output).unwrap();
+        let old_output = match tree.tree[old_output_ix].get_handle().unwrap() {
+            Handle::Output(output) => output,
+            _ => panic!()
+        };
+        let new_output = WlcView::dummy(5).as_output();
+        tree.add_output(new_output).unwrap();
+        tree.switch_to_workspace("2");
+        let active_ix = tree.active_container;
+        tree.remove_output(old_output).unwrap();
+        assert_eq!(tree.active_container, active_ix);
+        assert_eq!(tree.current_workspace(), Ok("2"));
+        // Removing the last output keeps its workspaces around
+        tree.remove_output(new_output).unwrap();
+        assert!(tree.is_headless());
+        assert!(tree.tree.workspace_ix_by_name("2").is_some());
+        // Until another output takes them
+        let other_output = WlcView::dummy(6).as_output();
+        tree.add_output(other_output).unwrap();
+        assert!(!tree.is_headless());
+        let other_output_ix = tree.active_ix_of(ContainerType::Output).unwrap();
+        let workspace_ix = tree.tree.workspace_ix_by_name("2").unwrap();
+        assert_eq!(tree.tree.parent_of(workspace_ix), Ok(other_output_ix));
+        tree.validate();
+    }
+
     #[test]
     /// Tests that we can remove the active container and have it properly reset
     fn basic_removal() {
@@ -1296,13 +1929,14 @@ pub mod tests {
         let mut tree = basic_tree();
         /* Make sure sending to the current workspace does nothing */
         let old_view = tree.tree[tree.active_container.unwrap()].clone();
-        tree.send_to_workspace(old_view.get_id(), "1");
+        assert_eq!(tree.send_to_workspace(old_view.get_id(), "1"), Ok(()));
         assert_eq!(old_view, tree.tree[tree.active_container.unwrap()]);
         //let old_view = tree.tree[tree.active_container.unwrap()].clone();
-        tree.send_to_workspace(old_view.get_id(), "3");
+        tree.send_to_workspace(old_view.get_id(), "3").unwrap();
         // Trying to send the root container does nothing
         let root_container_id = tree.tree[tree.active_container.unwrap()].get_id();
-        tree.send_to_workspace(root_container_id, "3");
+        assert_eq!(tree.send_to_workspace(root_container_id, "3"),
+                   Err(TreeError::InvalidOperationOnRootContainer(root_container_id)));
         let active_ix = tree.active_container.unwrap();
         assert!(tree.tree.is_root_container(active_ix));
         tree.switch_to_workspace("3");
@@ -1556,13 +2190,13 @@ pub mod tests {
         tree.switch_to_workspace("1");
 
         // Try sending things to other workspaces
-        tree.send_active_to_workspace("3");
+        tree.send_active_to_workspace("3").unwrap();
         tree.validate_path();
         tree.switch_to_workspace("2");
         tree.validate_path();
         tree.switch_to_workspace("3");
         tree.validate_path();
-        tree.send_active_to_workspace("2");
+        tree.send_active_to_workspace("2").unwrap();
         tree.validate_path();
         tree.switch_to_workspace("2");
         tree.validate_path();
@@ -1599,4 +2233,276 @@ pub mod tests {
                    Err(TreeError::InvalidOperationOnRootContainer(id)));
         assert!(tree.active_container.is_some());
     }
+
+    #[test]
+    fn debug_assert_valid_on_valid_tree() {
+        let mut tree = basic_tree();
+        tree.debug_assert_valid();
+        tree.switch_to_workspace("2");
+        tree.debug_assert_valid();
+        assert_eq!(tree.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Layout tree is invalid")]
+    fn debug_assert_valid_on_corrupted_tree() {
+        let mut tree = basic_tree();
+        let workspace_ix = tree.active_ix_of(ContainerType::Workspace).unwrap();
+        tree.active_container = Some(workspace_ix);
+        assert!(tree.check_invariants().is_err());
+        tree.debug_assert_valid();
+    }
+
+    #[test]
+    fn check_invariants_catches_a_path_ending_at_a_workspace() {
+        let mut tree = basic_tree();
+        let workspace_ix = tree.active_ix_of(ContainerType::Workspace).unwrap();
+        tree.tree.set_ancestor_paths_active(workspace_ix);
+        assert!(tree.check_invariants().is_err());
+    }
+
+    #[test]
+    fn set_active_view_floating() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("3");
+        let tiled_id = tree.add_view(WlcView::root()).unwrap().get_id();
+        let floating_view = WlcView::dummy(7);
+        let floating_id = tree.add_floating_view(floating_view, None).unwrap().get_id();
+        tree.set_active_container(tiled_id).unwrap();
+        tree.set_active_view(floating_view).unwrap();
+        let floating_ix = tree.tree.lookup_id(floating_id).unwrap();
+        assert_eq!(tree.active_container, Some(floating_ix));
+        assert!(tree.tree[floating_ix].floating());
+        // Floating views are direct children of the root container,
+        // and the active path now ends there
+        let parent_ix = tree.tree.parent_of(floating_ix).unwrap();
+        assert!(tree.tree.is_root_container(parent_ix));
+        assert!(tree.tree.on_path(floating_ix));
+        assert_eq!(tree.tree.follow_path(tree.tree.root_ix()), floating_ix);
+        tree.validate();
+        tree.validate_path();
+    }
+
+    #[test]
+    fn any_fullscreen_test() {
+        let mut tree = basic_tree();
+        assert!(!tree.any_fullscreen());
+        let active_ix = tree.active_container.unwrap();
+        let id = tree.tree[active_ix].get_id();
+        tree.tree[active_ix].set_fullscreen(true).unwrap();
+        let workspace_ix = tree.tree.ancestor_of_type(active_ix, ContainerType::Workspace)
+            .unwrap();
+        tree.tree[workspace_ix].update_fullscreen_c(id, true).unwrap();
+        assert!(tree.any_fullscreen());
+        tree.tree[workspace_ix].update_fullscreen_c(id, false).unwrap();
+        assert!(!tree.any_fullscreen());
+    }
+
+    #[test]
+    fn remove_node_no_refocus_test() {
+        let mut tree = basic_tree();
+        tree.switch_to_workspace("2");
+        let active_ix = tree.active_container.unwrap();
+        assert_eq!(tree.tree[active_ix].get_type(), ContainerType::View);
+        let active_id = tree.tree[active_ix].get_id();
+        let parent_ix = tree.tree.parent_of(active_ix).unwrap();
+        tree.remove_node_no_refocus(active_ix).unwrap();
+        assert_eq!(tree.tree.lookup_id(active_id), None);
+        // Focus did not move to the sibling
+        assert_eq!(tree.active_container, None);
+        assert_eq!(tree.tree.children_of(parent_ix).len(), 1);
+        tree.validate();
+    }
+
+    #[test]
+    fn set_struts_reflows_workspaces() {
+        let mut tree = basic_tree();
+        let output = WlcView::root().as_output();
+        let output_ix = tree.active_ix_of(ContainerType::Output).unwrap();
+        tree.layout(output_ix);
+        let workspace_ix = tree.active_ix_of(ContainerType::Workspace).unwrap();
+        let view_ix = tree.active_container.unwrap();
+        let old_workspace_geo = tree.tree[workspace_ix].get_geometry().unwrap();
+        // Floating views may overlap the struts, so they aren't moved
+        tree.switch_to_workspace("2");
+        let floating_id = tree.get_active_container().unwrap().get_id();
+        tree.float_container(floating_id).unwrap();
+        let floating_geo = tree.lookup(floating_id).unwrap().get_geometry().unwrap();
+        tree.switch_to_workspace("1");
+        let struts = Struts { top: 20, bottom: 0, left: 30, right: 0 };
+        tree.set_struts(output, struts).unwrap();
+        assert_eq!(tree.lookup(floating_id).unwrap().get_geometry().unwrap(), floating_geo);
+        let workspace_geo = tree.tree[workspace_ix].get_geometry().unwrap();
+        assert_eq!(workspace_geo.origin.x, old_workspace_geo.origin.x + 30);
+        assert_eq!(workspace_geo.origin.y, old_workspace_geo.origin.y + 20);
+        let view_geo = tree.tree[view_ix].get_geometry().unwrap();
+        assert!(view_geo.origin.x >= workspace_geo.origin.x);
+        assert!(view_geo.origin.y >= workspace_geo.origin.y);
+        // Unknown outputs are an error
+        let unknown = WlcView::dummy(9).as_output();
+        assert_eq!(tree.set_struts(unknown, struts),
+                   Err(TreeError::OutputNotFound(unknown)));
+    }
+
+    #[test]
+    fn add_view_falls_back_to_usable_geometry() {
+        let mut tree = basic_tree();
+        let root_c_ix = tree.root_container_ix().unwrap();
+        let view = WlcView::dummy(7);
+        let geometry = tree.usable_geometry(view, root_c_ix).unwrap();
+        assert!(geometry.size.w > 0 && geometry.size.h > 0);
+        tree.add_view(view).unwrap();
+        assert_eq!(tree.tree.lookup_view(view), tree.active_container);
+        assert_eq!(format!("{}", TreeError::NoGeometry(view)),
+                   format!("could not find a geometry for view {:?}", view));
+    }
+
+    #[test]
+    fn set_output_transform_swaps_dimensions() {
+        let mut tree = basic_tree();
+        let output = WlcView::root().as_output();
+        let output_ix = tree.active_ix_of(ContainerType::Output).unwrap();
+        let workspace_ix = tree.active_ix_of(ContainerType::Workspace).unwrap();
+        tree.layout(output_ix);
+        let old_size = tree.tree[output_ix].get_geometry().unwrap().size;
+        let id = tree.get_active_container().unwrap().get_id();
+        tree.float_container(id).unwrap();
+        let view_ix = tree.active_container.unwrap();
+        let far_away = Geometry {
+            origin: Point { x: old_size.w as i32 * 2, y: old_size.h as i32 * 2 },
+            size: Size { w: old_size.w.max(old_size.h) + 10, h: 10 }
+        };
+        tree.tree[view_ix].set_geometry(ResizeEdge::empty(), far_away);
+
+        tree.set_output_transform(output, OutputTransform::Rotate90).unwrap();
+        let size = tree.tree[output_ix].get_geometry().unwrap().size;
+        assert_eq!(size, Size { w: old_size.h, h: old_size.w });
+        let bounds = tree.tree[workspace_ix].get_geometry().unwrap();
+        assert_eq!(bounds.size, size);
+        // The floating view was pulled back in
+        let geometry = tree.tree[view_ix].get_geometry().unwrap();
+        assert_eq!(geometry.size.w, bounds.size.w);
+        assert!(geometry.origin.x >= bounds.origin.x);
+        assert!(geometry.origin.y >= bounds.origin.y);
+        assert!(geometry.origin.y + geometry.size.h as i32 <=
+                bounds.origin.y + bounds.size.h as i32);
+
+        // Upside down keeps the original dimensions
+        tree.set_output_transform(output, OutputTransform::Rotate180).unwrap();
+        assert_eq!(tree.tree[output_ix].get_geometry().unwrap().size, old_size);
+        let unknown = WlcView::dummy(9).as_output();
+        assert_eq!(tree.set_output_transform(unknown, OutputTransform::Normal),
+                   Err(TreeError::OutputNotFound(unknown)));
+    }
+
+    #[test]
+    fn geometry_of_test() {
+        let tree = basic_tree();
+        let view_ix = tree.active_container.unwrap();
+        let view_id = tree.tree[view_ix].get_id();
+        assert_eq!(tree.geometry_of(view_id),
+                   Ok(WlcView::root().get_geometry().unwrap()));
+        let container_ix = tree.tree.parent_of(view_ix).unwrap();
+        let container_id = tree.tree[container_ix].get_id();
+        assert_eq!(tree.geometry_of(container_id),
+                   Ok(tree.tree[container_ix].get_geometry().unwrap()));
+        let output_ix = tree.active_ix_of(ContainerType::Output).unwrap();
+        let output_id = tree.tree[output_ix].get_id();
+        assert_eq!(tree.geometry_of(output_id),
+                   Err(TreeError::UuidWrongType(output_id, vec!(ContainerType::View,
+                                                                ContainerType::Container,
+                                                                ContainerType::Workspace))));
+        let unknown = Uuid::new_v4();
+        assert_eq!(tree.geometry_of(unknown), Err(TreeError::NodeNotFound(unknown)));
+    }
+
+    #[test]
+    fn to_json_test() {
+        use rustc_serialize::json::{Json, ToJson};
+        fn count_nodes(json: &Json) -> usize {
+            1 + json.find("nodes").and_then(|nodes| nodes.as_array())
+                .expect("Node had no children list")
+                .iter().map(count_nodes).sum::<usize>()
+        }
+        fn find_focused(json: &Json) -> Option<&Json> {
+            if json.find("focused").and_then(|f| f.as_boolean()) == Some(true) {
+                return Some(json)
+            }
+            json.find("nodes").and_then(|nodes| nodes.as_array())
+                .and_then(|nodes| nodes.iter().filter_map(find_focused).next())
+        }
+        let tree = basic_tree();
+        let json = tree.to_json();
+        let root_ix = tree.tree.root_ix();
+        assert_eq!(count_nodes(&json), tree.tree.all_descendants_of(root_ix).len() + 1);
+        assert_eq!(json.find("type").and_then(|t| t.as_string()), Some("Root"));
+        let focused = find_focused(&json).expect("Nothing was focused");
+        let active_id = tree.get_active_container().unwrap().get_id().to_string();
+        assert_eq!(focused.find("id").and_then(|id| id.as_string()),
+                   Some(active_id.as_str()));
+        assert_eq!(focused.find("type").and_then(|t| t.as_string()), Some("View"));
+        assert_eq!(focused.find("floating").and_then(|f| f.as_boolean()), Some(false));
+        assert_eq!(focused.find("fullscreen").and_then(|f| f.as_boolean()), Some(false));
+        assert!(focused.find("geometry").unwrap().find("size").is_some());
+    }
+
+    #[test]
+    fn global_fullscreen_test() {
+        let mut tree = basic_tree();
+        tree.add_output(WlcView::dummy(5).as_output()).unwrap();
+        tree.switch_to_workspace("1");
+        let id = tree.get_active_container().unwrap().get_id();
+        tree.switch_to_workspace("2");
+        let other_ix = tree.active_container.unwrap();
+        let other_id = tree.tree[other_ix].get_id();
+
+        tree.set_fullscreen_mode(id, FullscreenMode::Global).unwrap();
+        assert_eq!(tree.fullscreen_mode(id), Ok(FullscreenMode::Global));
+        // Blocks focus on every workspace, not just its own
+        assert_eq!(tree.in_fullscreen_workspace(other_id), Ok(Some(id)));
+        assert_eq!(tree.set_active_node(other_ix),
+                   Err(TreeError::Focus(FocusError::BlockedByFullscreen(other_id, id))));
+
+        // Only one at a time
+        tree.set_fullscreen_mode(other_id, FullscreenMode::Global).unwrap();
+        assert_eq!(tree.global_fullscreen, Some(other_id));
+        let workspace_ix = tree.tree.workspace_ix_by_name("1").unwrap();
+        assert!(!tree.tree[workspace_ix].fullscreen_c().unwrap().contains(&id));
+
+        // Going back to workspace fullscreen stops blocking the other workspaces
+        tree.set_fullscreen_mode(other_id, FullscreenMode::Workspace).unwrap();
+        assert_eq!(tree.global_fullscreen, None);
+        assert_eq!(tree.in_fullscreen_workspace(id), Ok(None));
+        assert_eq!(tree.in_fullscreen_workspace(other_id), Ok(Some(other_id)));
+        tree.set_fullscreen_mode(other_id, FullscreenMode::None).unwrap();
+        assert_eq!(tree.in_fullscreen_workspace(other_id), Ok(None));
+    }
+
+    #[test]
+    fn view_uuid_lookup_test() {
+        let tree = basic_tree();
+        let view_id = tree.get_active_container().unwrap().get_id();
+        assert_eq!(tree.view_of_uuid(view_id), Some(WlcView::root()));
+        let view_id = tree.uuid_of_view(WlcView::root()).unwrap();
+        assert_eq!(tree.view_of_uuid(view_id), Some(WlcView::root()));
+        // Non-views and unknown ids have no view
+        let workspace_ix = tree.tree.workspace_ix_by_name("1").unwrap();
+        assert_eq!(tree.view_of_uuid(tree.tree[workspace_ix].get_id()), None);
+        assert_eq!(tree.view_of_uuid(Uuid::new_v4()), None);
+        assert_eq!(tree.uuid_of_view(WlcView::dummy(42)), None);
+    }
+
+    #[test]
+    fn tree_error_display_and_source() {
+        use std::error::Error;
+        let id = Uuid::new_v4();
+        assert_eq!(TreeError::NodeNotFound(id).to_string(),
+                   format!("no container with id {}", id));
+        let err = TreeError::Resize(ResizeErr::ExpectedFloating(id));
+        assert_eq!(err.to_string(), format!("container {} is not floating", id));
+        assert!(err.source().is_some());
+        assert!(TreeError::NoActiveContainer.source().is_none());
+        let boxed: Box<Error> = Box::new(TreeError::MarkNotFound("a".into()));
+        assert_eq!(boxed.to_string(), "no container is marked \"a\"");
+    }
 }
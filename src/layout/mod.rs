@@ -6,22 +6,36 @@ pub mod commands;
 mod unit_tests;
 
 pub use self::actions::movement::MovementError;
-pub use self::actions::focus::FocusError;
-pub use self::actions::resize::ResizeErr;
+pub use self::actions::focus::{ActiveContext, FocusError, FocusWrapping, ViewInfo};
+pub use self::actions::resize::{ResizeErr, TiledResize};
+pub use self::actions::dispatch::{Command, CommandErr, Statement};
+pub use self::actions::criteria::Criteria;
+pub use self::actions::layout::Orientation;
+pub use self::actions::borders::{BorderColors, HideEdgeBorders};
+pub use self::actions::snapshot::LayoutSnapshot;
+use self::actions::snapshot::PendingLayout;
+pub use self::actions::assign::Assignment;
 pub use self::core::GraphError;
 
-pub use self::core::background::{Background, IncompleteBackground,
+pub use self::core::background::{Background, BackgroundMode, IncompleteBackground,
                                  MaybeBackground};
 pub use self::core::action::{Action, ActionErr};
-pub use self::core::container::{Container, ContainerType, Handle, Layout};
+pub use self::core::container::{Container, ContainerType, FullscreenMode, Handle, Layout,
+                                 OutputTransform, Struts};
 pub use self::core::tree::{Direction, TreeError};
 pub use self::core::bar::Bar;
 use self::core::InnerTree;
+use self::core::hooks::Hooks;
 pub use self::core::MIN_SIZE;
+pub use self::core::{GeometryDelta, GeometryDiff};
 
 use petgraph::graph::NodeIndex;
+use rustwlc::{Geometry, WlcView, WlcOutput};
+use uuid::Uuid;
 use rustc_serialize::json::{Json, ToJson};
 
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use std::sync::{Mutex, MutexGuard, TryLockError, PoisonError};
 
 /// A wrapper around tree, to hide its methods
@@ -37,7 +51,38 @@ impl Tree {
     pub fn new() -> Self {
         Tree(LayoutTree {
             tree: InnerTree::new(),
-            active_container: None
+            active_container: None,
+            view_tags: HashMap::new(),
+            dynamic_workspaces: false,
+            hooks: Hooks::new(),
+            suspend_count: 0,
+            pending_layout: false,
+            layout_depth: 0,
+            scratchpad: None,
+            scratchpad_geometry: HashMap::new(),
+            inner_gap: None,
+            outer_gap: 0,
+            smart_gaps: false,
+            last_focused: HashMap::new(),
+            global_fullscreen: None,
+            pending_split: None,
+            prev_workspace: None,
+            focus_history: VecDeque::new(),
+            tiled_resize: None,
+            hide_edge_borders: HideEdgeBorders::None,
+            default_layout: Layout::Horizontal,
+            default_orientation: Orientation::Horizontal,
+            pending_layouts: HashMap::new(),
+            default_background: None,
+            focus_follows_mouse: false,
+            pointer_view: None,
+            focus_wrapping: FocusWrapping::No,
+            assignments: Vec::new(),
+            floating_order: HashMap::new(),
+            headless_output: None,
+            border_colors: BorderColors::default(),
+            transaction_depth: 0,
+            inactive_opacity: None
         })
     }
 }
@@ -46,7 +91,71 @@ impl Tree {
 #[derive(Debug)]
 pub struct LayoutTree {
     tree: InnerTree,
-    active_container: Option<NodeIndex>
+    active_container: Option<NodeIndex>,
+    /// Extra workspaces (tags) that a view should follow when they are viewed.
+    view_tags: HashMap<Uuid, Vec<String>>,
+    /// If set, workspaces are removed when their last view is removed.
+    dynamic_workspaces: bool,
+    hooks: Hooks,
+    /// How many times layout has been suspended, see `suspend_layout`.
+    suspend_count: u32,
+    /// Whether a layout was requested while it was suspended.
+    pending_layout: bool,
+    /// How deep in nested calls to `layout` we are.
+    layout_depth: u32,
+    /// The hidden scratchpad workspace, once something has been put in it.
+    scratchpad: Option<NodeIndex>,
+    /// Floating geometry of views in the scratchpad, used when they are shown.
+    scratchpad_geometry: HashMap<Uuid, Geometry>,
+    /// Gap between tiled siblings, if unset the configured gap size is used.
+    inner_gap: Option<u32>,
+    /// Gap between the tiled views and the edges of the workspace.
+    outer_gap: u32,
+    /// If set, a workspace with a single tiled view has no gaps.
+    smart_gaps: bool,
+    /// The last focused container of each workspace, by workspace id.
+    last_focused: HashMap<Uuid, Uuid>,
+    /// The container that is fullscreen across every output, if there is one.
+    global_fullscreen: Option<Uuid>,
+    /// A view and the layout the next view added next to it should be split with.
+    pending_split: Option<(Uuid, Layout)>,
+    /// The name of the workspace that was active before the current one.
+    prev_workspace: Option<String>,
+    /// The most recently focused containers, the newest at the back.
+    /// See `focus_back`.
+    focus_history: VecDeque<Uuid>,
+    /// The pointer driven resize of a tiled container in progress, if any.
+    tiled_resize: Option<TiledResize>,
+    /// When the borders of tiled views are hidden.
+    hide_edge_borders: HideEdgeBorders,
+    /// The layout of the root container of new workspaces.
+    default_layout: Layout,
+    /// Which way splits without an explicit layout are oriented.
+    default_orientation: Orientation,
+    /// Layouts being restored, by the id of their workspace.
+    /// See `restore_workspace_layout`.
+    pending_layouts: HashMap<Uuid, PendingLayout>,
+    /// The image drawn on new outputs, and how it fills them.
+    default_background: Option<(PathBuf, BackgroundMode)>,
+    /// If set, moving the pointer over a view focuses it.
+    focus_follows_mouse: bool,
+    /// The view the pointer was last over, see `pointer_focus`.
+    pointer_view: Option<WlcView>,
+    /// What `move_focus` does at the edge of a container.
+    focus_wrapping: FocusWrapping,
+    /// Rules for which workspace new views are put on.
+    assignments: Vec<Assignment>,
+    /// The ids of the floating views of each workspace, from the bottom
+    /// of the stack to the top. See `floating_stack`.
+    floating_order: HashMap<Uuid, Vec<Uuid>>,
+    /// The last output, kept after it was removed. See `remove_output`.
+    headless_output: Option<WlcOutput>,
+    /// The colors the user set for the borders.
+    border_colors: BorderColors,
+    /// How many transactions are running, see `transaction`.
+    transaction_depth: u32,
+    /// If set, unfocused views are reported with at most this opacity.
+    inactive_opacity: Option<f32>
 }
 
 lazy_static! {
@@ -57,40 +166,57 @@ lazy_static! {
 }
 
 impl ToJson for LayoutTree {
+    /// Dumps the whole tree, similar to i3's `get_tree`.
+    ///
+    /// Every node has its type, id, geometry and whether it is focused,
+    /// as well as the nodes below it. Depending on the type there is also
+    /// the layout, the workspace name, or the view's title and app id.
     fn to_json(&self) -> Json {
         use std::collections::BTreeMap;
+        use ::convert::json::geometry_to_json;
         fn node_to_json(node_ix: NodeIndex, tree: &LayoutTree) -> Json {
-            match &tree.tree[node_ix] {
-                &Container::Workspace { ref name, .. } => {
-                    let mut inner_map = BTreeMap::new();
-                    let children = tree.tree.children_of(node_ix).iter()
-                        .map(|node| node_to_json(*node, tree)).collect();
-                    inner_map.insert(format!("Workspace {}", name), Json::Array(children));
-                    return Json::Object(inner_map);
-                }
-                &Container::Container { ref layout, id, .. } => {
-                    let mut inner_map = BTreeMap::new();
-                    let children = tree.tree.children_of(node_ix).iter()
-                        .map(|node| node_to_json(*node, tree)).collect();
-                    inner_map.insert(format!("Container w/ layout {:?} and id {:?}", layout, id),
-                                     Json::Array(children));
-                    return Json::Object(inner_map);
-                }
-                &Container::View { ref handle, id, .. } => {
-                    return Json::String(format!("View: title: \"{:?}\", class: \"{:?}\", id: {}",
-                                                handle.get_title(), handle.get_class(), id));
+            let container = &tree.tree[node_ix];
+            let mut map = BTreeMap::new();
+            map.insert("type".into(),
+                       format!("{:?}", container.get_type()).to_json());
+            map.insert("id".into(), container.get_id().to_string().to_json());
+            map.insert("geometry".into(), container.get_geometry()
+                       .map(geometry_to_json).unwrap_or(Json::Null));
+            map.insert("focused".into(),
+                       (tree.active_container == Some(node_ix)).to_json());
+            match *container {
+                Container::Output { ref handle, .. } => {
+                    map.insert("name".into(), handle.get_name().to_json());
                 },
-                ref container => {
-                    let mut inner_map = BTreeMap::new();
-                    let children = tree.tree.children_of(node_ix).iter()
-                        .map(|node| node_to_json(*node, tree)).collect();
-                    inner_map.insert(format!("{:?}", container.get_type()),
-                                     Json::Array(children));
-                    return Json::Object(inner_map)
-                }
+                Container::Workspace { ref name, .. } => {
+                    map.insert("name".into(), name.to_json());
+                    map.insert("urgent".into(), tree.is_urgent(container.get_id())
+                               .unwrap_or(false).to_json());
+                },
+                Container::Container { layout, .. } => {
+                    map.insert("layout".into(), format!("{:?}", layout).to_json());
+                    map.insert("floating".into(), container.floating().to_json());
+                    map.insert("fullscreen".into(), container.fullscreen().to_json());
+                },
+                Container::View { ref handle, .. } => {
+                    map.insert("title".into(), handle.get_title().to_json());
+                    map.insert("class".into(), handle.get_class().to_json());
+                    map.insert("app_id".into(), container.app_id().to_json());
+                    map.insert("floating".into(), container.floating().to_json());
+                    map.insert("fullscreen".into(), container.fullscreen().to_json());
+                    map.insert("sticky".into(), container.sticky().to_json());
+                    map.insert("urgent".into(), container.urgent().to_json());
+                    map.insert("opacity".into(), tree.reported_opacity(node_ix)
+                               .map(|opacity| opacity as f64).to_json());
+                },
+                Container::Root(_) => {}
             }
+            let children = tree.tree.children_of(node_ix).iter()
+                .map(|node| node_to_json(*node, tree)).collect();
+            map.insert("nodes".into(), Json::Array(children));
+            Json::Object(map)
         }
-        return node_to_json(self.tree.root_ix(), self);
+        node_to_json(self.tree.root_ix(), self)
     }
 }
 
@@ -1,4 +1,5 @@
 use super::util::*;
+use rustwlc::WlcView;
 
 #[test]
 fn ensure_workspace_switch_when_fullscreen() {
@@ -10,3 +11,23 @@ fn ensure_workspace_switch_when_fullscreen() {
     tree = tree.add_workspace("1").unwrap();
     assert_eq!(tree.active_id(), Some(active_id));
 }
+
+#[test]
+fn fullscreen_does_not_block_other_outputs() {
+    let mut tree = basic_tree();
+    let fullscreen_id = tree.active_id().unwrap();
+    let second_output = WlcView::dummy(5).as_output();
+    tree = tree.add_output_with(second_output).unwrap()
+        .add_new_view().unwrap();
+    let other_id = tree.active_id().unwrap();
+    tree = tree.focus_on(fullscreen_id).unwrap();
+    tree.set_fullscreen(fullscreen_id, true).unwrap();
+    assert!(tree.is_fullscreen(fullscreen_id).unwrap());
+    // The other output's workspace is still shown, and is not fullscreen
+    assert!(!tree.is_fullscreen(other_id).unwrap());
+    // Focus can still move to the other output
+    tree = tree.focus_on(other_id).unwrap();
+    assert_eq!(tree.active_id(), Some(other_id));
+    tree = tree.focus_on(fullscreen_id).unwrap();
+    assert_eq!(tree.active_id(), Some(fullscreen_id));
+}
@@ -2,7 +2,7 @@
 use std::ops::{Deref, DerefMut};
 
 use uuid::Uuid;
-use rustwlc::WlcView;
+use rustwlc::{WlcView, WlcOutput};
 
 use ::layout::{Tree, Layout, TreeError};
 
@@ -27,6 +27,14 @@ impl UnitTestTree {
         Ok(self)
     }
 
+    /// Attempts to add a specific output to the tree.
+    ///
+    /// Used when a test needs more than one output.
+    pub fn add_output_with(mut self, output: WlcOutput) -> TestTreeResult {
+        self.0.add_output(output)?;
+        Ok(self)
+    }
+
     /// Switches to a workspace, which is the default workspace
     /// for all the subsequent operations.
     ///
@@ -44,7 +44,11 @@ impl Mode for Default {
     }
 
     fn output_destroyed(&mut self, output: WlcOutput) {
-         // TODO Redistribute workspaces of that output.
+        if let Ok(mut tree) = try_lock_tree() {
+            if let Err(err) = tree.remove_output(output) {
+                warn!("Could not remove output {:?}: {:?}", output, err);
+            }
+        }
     }
 
     fn output_focused(&mut self, output: WlcOutput, focused: bool) {
@@ -56,9 +60,10 @@ impl Mode for Default {
         // Update the resolution of the output and its children
         let scale = 1;
         output.set_resolution(new_size_ptr, scale);
+        // Lay out the output that changed, so its background is resized too
         if let Ok(mut tree) = try_lock_tree() {
-            tree.layout_active_of(ContainerType::Output)
-                .expect("Could not layout active output");
+            tree.layout_output(output)
+                .expect("Could not layout output");
         }
     }
 
@@ -399,7 +404,14 @@ impl Mode for Default {
             .unwrap_or_else(|err|
                             warn!("handling keygrabber returned error: {:#?}", err));
         match maybe_action {
-            None => result = EVENT_PASS_THROUGH,
+            None => {
+                if let Ok(mut tree) = try_lock_tree() {
+                    tree.pointer_focus(view).unwrap_or_else(|err| {
+                        warn!("Could not focus on {:?} under the pointer: {:?}", view, err)
+                    });
+                }
+                result = EVENT_PASS_THROUGH
+            },
             Some(action) => {
                 if action.edges.bits() != 0 {
                     if let Ok(mut tree) = try_lock_tree() {